@@ -0,0 +1,18 @@
+//! Only nightly compilers can point at a character inside a string literal, so the macros do
+//! so when built by one, and point at the whole literal otherwise.
+
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(subspan)");
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default();
+    if version.contains("nightly") || version.contains("-dev") {
+        println!("cargo:rustc-cfg=subspan");
+    }
+}
@@ -0,0 +1,495 @@
+//! Macros that check box drawings at compile time.
+//!
+//! Problems found in a drawing are reported as compile errors that point at the character in
+//! the literal where they were found, or at the whole literal on a stable compiler.
+
+#![cfg_attr(subspan, feature(proc_macro_span))]
+
+extern crate proc_macro;
+
+#[cfg(test)]
+mod tests;
+
+use dottools::check::{check, Diagnostic};
+use dottools::{is_box_drawing, Error, Graph, Node};
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A string literal, with where each character of its value came from in its source.
+struct Text {
+    literal: Literal,
+    value: String,
+    /// The byte range in the literal's source of each character of the value.
+    spans: Vec<(usize, usize)>,
+}
+
+impl Text {
+    /// Reads the value of a string literal, undoing its escapes.
+    fn new(literal: Literal) -> Option<Self> {
+        let source = literal.to_string();
+        let mut value = String::new();
+        let mut spans = Vec::new();
+        if let Some(raw) = source.strip_prefix('r') {
+            let hashes = raw.chars().take_while(|&c| c == '#').count();
+            let start = hashes + 2;
+            let end = source.len().checked_sub(hashes + 1)?;
+            for (offset, c) in source.get(start..end)?.char_indices() {
+                value.push(c);
+                spans.push((start + offset, start + offset + c.len_utf8()));
+            }
+        } else if source.starts_with('"') {
+            let end = source.len().checked_sub(1)?;
+            let mut chars = source[..end].char_indices().skip(1).peekable();
+            while let Some((start, c)) = chars.next() {
+                let c = if c != '\\' {
+                    c
+                } else {
+                    match chars.next()?.1 {
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        '0' => '\0',
+                        '\\' => '\\',
+                        '\'' => '\'',
+                        '"' => '"',
+                        'x' => {
+                            let digits: String = (0..2)
+                                .filter_map(|_| chars.next())
+                                .map(|(_, c)| c)
+                                .collect();
+                            u8::from_str_radix(&digits, 16).ok()? as char
+                        }
+                        'u' => {
+                            let mut digits = String::new();
+                            for (_, c) in chars.by_ref() {
+                                match c {
+                                    '{' => {}
+                                    '}' => break,
+                                    c => digits.push(c),
+                                }
+                            }
+                            std::char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?
+                        }
+                        '\n' => {
+                            // A line continuation skips the whitespace that follows it.
+                            while chars.peek().is_some_and(|&(_, c)| c.is_whitespace()) {
+                                chars.next();
+                            }
+                            continue;
+                        }
+                        _ => return None,
+                    }
+                };
+                let next = chars.peek().map_or(end, |&(offset, _)| offset);
+                value.push(c);
+                spans.push((start, next));
+            }
+        } else {
+            return None;
+        }
+        Some(Text {
+            literal,
+            value,
+            spans,
+        })
+    }
+
+    /// Reads the value of a doc attribute. A doc comment is not a literal in the source, but
+    /// its text follows the three characters that open it without escapes.
+    fn doc(literal: Literal) -> Option<Self> {
+        let source = literal.span().source_text().unwrap_or_default();
+        let mut text = Text::new(literal)?;
+        if ["///", "//!", "/**", "/*!"]
+            .iter()
+            .any(|&prefix| source.starts_with(prefix))
+        {
+            text.spans = comment_spans(&text.value);
+        }
+        Some(text)
+    }
+
+    /// The span of the `index`th character of the value, or of the whole literal when the
+    /// compiler cannot point inside it.
+    fn span_at(&self, index: usize) -> Span {
+        self.spans
+            .get(index)
+            .and_then(|&(start, end)| subspan(&self.literal, start..end))
+            .unwrap_or_else(|| self.literal.span())
+    }
+
+    /// The span of the character that a diagnostic is about.
+    fn span(&self, diagnostic: &Diagnostic) -> Span {
+        let position = diagnostic.position;
+        let index = self
+            .value
+            .split('\n')
+            .take(position.line() - 1)
+            .map(|line| line.chars().count() + 1)
+            .sum::<usize>()
+            + position.column();
+        self.span_at(index)
+    }
+}
+
+/// The byte range in a doc comment of each character of its value, which follows the three
+/// characters that open the comment.
+fn comment_spans(value: &str) -> Vec<(usize, usize)> {
+    value
+        .char_indices()
+        .map(|(offset, c)| (3 + offset, 3 + offset + c.len_utf8()))
+        .collect()
+}
+
+/// The span of a byte range in the source of a literal.
+#[cfg(subspan)]
+fn subspan(literal: &Literal, range: Range<usize>) -> Option<Span> {
+    literal.subspan(range)
+}
+
+/// Stable compilers cannot point inside a literal.
+#[cfg(not(subspan))]
+fn subspan(_: &Literal, _: Range<usize>) -> Option<Span> {
+    None
+}
+
+/// The first string literal of the input, looking through the invisible groups that other
+/// macros wrap their arguments in.
+fn literal(input: TokenStream) -> Option<Literal> {
+    let mut tokens = input.into_iter();
+    let literal = match tokens.next()? {
+        TokenTree::Literal(literal) => literal,
+        TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+            return literal(group.stream())
+        }
+        _ => return None,
+    };
+    match tokens.next() {
+        None => Some(literal),
+        Some(_) => None,
+    }
+}
+
+/// A `compile_error!("message");` statement for each error, pointing at its span.
+fn compile_error_statements(errors: &[(String, Span)]) -> TokenStream {
+    let mut tokens: Vec<TokenTree> = Vec::new();
+    for (message, span) in errors {
+        let span = *span;
+        tokens.push(Ident::new("compile_error", span).into());
+        tokens.push(Punct::new('!', Spacing::Alone).into());
+        let mut message = Literal::string(message);
+        message.set_span(span);
+        let mut group = Group::new(
+            Delimiter::Parenthesis,
+            TokenStream::from(TokenTree::from(message)),
+        );
+        group.set_span(span);
+        tokens.push(group.into());
+        let mut semicolon = Punct::new(';', Spacing::Alone);
+        semicolon.set_span(span);
+        tokens.push(semicolon.into());
+    }
+    tokens.into_iter().collect()
+}
+
+/// The `compile_error!` statements for each error in a block, so that they stand in for an
+/// expression.
+fn compile_errors(errors: &[(String, Span)]) -> TokenStream {
+    let span = errors.first().map_or_else(Span::call_site, |error| error.1);
+    let mut block = Group::new(Delimiter::Brace, compile_error_statements(errors));
+    block.set_span(span);
+    TokenStream::from(TokenTree::from(block))
+}
+
+/// The value of a `doc` attribute, given what is inside its brackets.
+fn doc_attribute(attribute: TokenStream) -> Option<Text> {
+    let mut tokens = attribute.into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(name)), Some(TokenTree::Punct(equals)))
+            if name.to_string() == "doc" && equals.as_char() == '=' =>
+        {
+            literal(tokens.collect()).and_then(Text::doc)
+        }
+        _ => None,
+    }
+}
+
+/// Checks each fenced `text` block in the docs of one item that contains box drawing.
+fn check_docs(docs: &[Text], errors: &mut Vec<(String, Span)>) {
+    let values: Vec<&str> = docs.iter().map(|doc| doc.value.as_str()).collect();
+    for (message, t, i) in problems(&values) {
+        errors.push((message, docs[t].span_at(i)));
+    }
+}
+
+/// The problems in each fenced `text` block of the values of one item's doc attributes that
+/// contains box drawing, with the attribute and the index of the character in its value that
+/// each is about.
+fn problems(docs: &[&str]) -> Vec<(String, usize, usize)> {
+    let mut problems = Vec::new();
+    // Each character of each line, with the doc attribute and index in its value it came from.
+    let mut lines: Vec<Vec<(usize, usize, char)>> = Vec::new();
+    for (t, doc) in docs.iter().enumerate() {
+        lines.push(Vec::new());
+        for (i, c) in doc.chars().enumerate() {
+            match c {
+                '\n' => lines.push(Vec::new()),
+                c => lines.last_mut().unwrap().push((t, i, c)),
+            }
+        }
+    }
+    let text =
+        |line: &[(usize, usize, char)]| -> String { line.iter().map(|&(_, _, c)| c).collect() };
+
+    let mut lines = lines.iter();
+    while let Some(line) = lines.next() {
+        let indent = line.iter().take_while(|&&(_, _, c)| c == ' ').count();
+        let opening = text(&line[indent..]);
+        let fence = match opening.chars().next() {
+            Some(c) if c == '`' || c == '~' => c,
+            _ => continue,
+        };
+        let length = opening.chars().take_while(|&c| c == fence).count();
+        if length < 3 {
+            continue;
+        }
+        let language = opening[length..]
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .find(|word| !word.is_empty())
+            .unwrap_or("");
+
+        // The lines of the block, less as much of the fence's indentation as they have.
+        let mut block = Vec::new();
+        for line in lines.by_ref() {
+            let closing = text(line);
+            let closing = closing.trim();
+            if closing.chars().take_while(|&c| c == fence).count() >= length
+                && closing.trim_start_matches(fence).is_empty()
+            {
+                break;
+            }
+            let strip = line
+                .iter()
+                .take(indent)
+                .take_while(|&&(_, _, c)| c == ' ')
+                .count();
+            block.push(&line[strip..]);
+        }
+        if language != "text"
+            || !block
+                .iter()
+                .any(|line| line.iter().any(|&(_, _, c)| is_box_drawing(c)))
+        {
+            continue;
+        }
+
+        let fence = line[indent];
+        let at = |line: usize, column: usize| -> (usize, usize) {
+            let &(t, i, _) = block
+                .get(line.wrapping_sub(1))
+                .and_then(|line| line.get(column).or_else(|| line.last()))
+                .unwrap_or(&fence);
+            (t, i)
+        };
+        let drawing: Vec<String> = block.iter().map(|line| text(line)).collect();
+        let drawing = drawing.join("\n");
+        let diagnostics = check(&drawing);
+        for diagnostic in &diagnostics {
+            let position = diagnostic.position;
+            let (t, i) = at(position.line(), position.column());
+            problems.push((diagnostic.message.clone(), t, i));
+        }
+        if diagnostics.is_empty() {
+            let (message, (t, i)) = match drawing.parse::<Graph>() {
+                Ok(_) => continue,
+                Err(Error::Syntax(position, message)) => {
+                    (message, at(position.line(), position.column()))
+                }
+                Err(error) => (format!("{:?}", error), at(0, 0)),
+            };
+            problems.push((message, t, i));
+        }
+    }
+    problems
+}
+
+/// Checks the docs of each item in `tokens`, and of the items nested inside them.
+fn check_items(tokens: TokenStream, errors: &mut Vec<(String, Span)>) {
+    let mut docs = Vec::new();
+    let mut inner = false;
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(ref punct) if punct.as_char() == '#' => {
+                let bang = match tokens.peek() {
+                    Some(TokenTree::Punct(punct)) if punct.as_char() == '!' => {
+                        tokens.next();
+                        true
+                    }
+                    _ => false,
+                };
+                if bang != inner {
+                    check_docs(&docs, errors);
+                    docs.clear();
+                    inner = bang;
+                }
+                if let Some(TokenTree::Group(group)) = tokens.peek() {
+                    if group.delimiter() == Delimiter::Bracket {
+                        docs.extend(doc_attribute(group.stream()));
+                        tokens.next();
+                    }
+                }
+            }
+            token => {
+                check_docs(&docs, errors);
+                docs.clear();
+                if let TokenTree::Group(group) = token {
+                    check_items(group.stream(), errors);
+                }
+            }
+        }
+    }
+    check_docs(&docs, errors);
+}
+
+fn node(node: &Node) -> String {
+    let (vertical, horizontal) = node.region();
+    format!(
+        "::dottools::Node::new({:?}, ::dottools::LineColumn::new({}, {}), \
+         ::dottools::LineColumn::new({}, {}), \
+         (::dottools::Region::{:?}, ::dottools::Region::{:?}))",
+        node.character(),
+        node.source().line(),
+        node.source().column(),
+        node.visual().line(),
+        node.visual().column(),
+        vertical,
+        horizontal
+    )
+}
+
+/// Parses a box drawing at compile time into a `dottools::Drawing`, which converts into a
+/// `Graph` without parsing it again.
+///
+/// ```
+/// use dottools::{Drawing, Graph};
+/// use dottools_macros::diagram;
+///
+/// const DRAWING: Drawing = diagram! {r"
+/// ┌───┐
+/// │ A ├──▶
+/// └───┘"};
+/// let graph = Graph::from(&DRAWING);
+/// assert_eq!(graph.to_string(), DRAWING.text);
+/// ```
+///
+/// Problems in the drawing are compile errors:
+///
+/// ```compile_fail
+/// use dottools_macros::diagram;
+///
+/// let drawing = diagram! {"──│"};
+/// ```
+#[proc_macro]
+pub fn diagram(input: TokenStream) -> TokenStream {
+    let text = match literal(input.clone()).and_then(Text::new) {
+        Some(text) => text,
+        None => {
+            let span = input
+                .into_iter()
+                .next()
+                .map_or_else(Span::call_site, |token| token.span());
+            return compile_errors(&[("expected a string literal".to_string(), span)]);
+        }
+    };
+
+    let diagnostics = check(&text.value);
+    if !diagnostics.is_empty() {
+        let errors: Vec<(String, Span)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.message.clone(), text.span(diagnostic)))
+            .collect();
+        return compile_errors(&errors);
+    }
+    let graph = match Graph::with_text(&text.value) {
+        Ok(graph) => graph,
+        Err(error) => {
+            return compile_errors(&[(format!("{:?}", error), text.literal.span())]);
+        }
+    };
+
+    let mut nodes: Vec<Node> = graph.nodes().collect();
+    nodes.sort();
+    let index: HashMap<Node, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let mut edges: Vec<(usize, usize, String)> = graph
+        .all_edges()
+        .map(|(v, u, edge)| {
+            let (v, u) = (index[&v], index[&u]);
+            let edge = format!(
+                "::dottools::Edge::new({:?}, ::dottools::Brush::{:?}, {:?})",
+                edge.start(),
+                edge.brush(),
+                edge.end()
+            );
+            (v.min(u), v.max(u), edge)
+        })
+        .collect();
+    edges.sort();
+
+    let nodes: Vec<String> = nodes.iter().map(node).collect();
+    let edges: Vec<String> = edges
+        .iter()
+        .map(|(v, u, edge)| format!("({}, {}, {})", v, u, edge))
+        .collect();
+    format!(
+        "{{ const DRAWING: ::dottools::Drawing = ::dottools::Drawing {{ \
+         text: {:?}, nodes: &[{}], edges: &[{}] }}; DRAWING }}",
+        text.value,
+        nodes.join(", "),
+        edges.join(", ")
+    )
+    .parse()
+    .unwrap()
+}
+
+/// Checks the box drawings in the docs of an item, and of every item inside it, when it is
+/// compiled. A drawing is a fenced `text` block that contains box drawing; problems in it are
+/// compile errors that point at the character in the doc comment where they were found.
+///
+/// ```
+/// use dottools_macros::check_diagrams;
+///
+/// #[check_diagrams]
+/// mod documented {
+///     /// ```text
+///     /// ┌───┐
+///     /// │ A ├──▶
+///     /// └───┘
+///     /// ```
+///     pub struct A;
+/// }
+/// ```
+///
+/// ```compile_fail
+/// use dottools_macros::check_diagrams;
+///
+/// #[check_diagrams]
+/// /// ```text
+/// /// ──│
+/// /// ```
+/// pub struct A;
+/// ```
+#[proc_macro_attribute]
+pub fn check_diagrams(attribute: TokenStream, item: TokenStream) -> TokenStream {
+    let mut errors = Vec::new();
+    if let Some(token) = attribute.into_iter().next() {
+        errors.push((
+            "`check_diagrams` takes no arguments".to_string(),
+            token.span(),
+        ));
+    }
+    check_items(item.clone(), &mut errors);
+    let mut output = item;
+    output.extend(compile_error_statements(&errors));
+    output
+}
@@ -0,0 +1,51 @@
+use crate::{comment_spans, problems};
+use pretty_assertions::assert_eq;
+
+/// The problems in the doc comment, with the line of each and the part of it that it is about.
+fn found(source: &[&'static str]) -> Vec<(String, usize, (usize, usize), &'static str)> {
+    let docs: Vec<&str> = source.iter().map(|line| &line[3..]).collect();
+    problems(&docs)
+        .into_iter()
+        .map(|(message, t, i)| {
+            let (start, end) = comment_spans(docs[t])[i];
+            (message, t, (start, end), &source[t][start..end])
+        })
+        .collect()
+}
+
+#[test]
+fn problems_point_at_their_character() {
+    let source = [
+        "/// A box:",
+        "///",
+        "/// ```text",
+        "/// ┌───┐",
+        "/// │ A ├──│",
+        "/// └───┘",
+        "/// ```",
+    ];
+    assert_eq!(
+        found(&source),
+        vec![(
+            "`─` does not join `│` to its east".to_string(),
+            4,
+            (16, 19),
+            "─"
+        )]
+    );
+}
+
+#[test]
+fn only_text_blocks_are_checked() {
+    let source = [
+        "/// ```text",
+        "/// ┌───┐",
+        "/// │ A ├──▶",
+        "/// └───┘",
+        "/// ```",
+        "/// ```ignore",
+        "/// ──│",
+        "/// ```",
+    ];
+    assert_eq!(found(&source), vec![]);
+}
@@ -0,0 +1,46 @@
+use dottools_macros::check_diagrams;
+
+#[check_diagrams]
+mod documented {
+    //! ```text
+    //!  ╭───╮
+    //!  │ B │
+    //!  ╰─┬─╯
+    //!    ▼
+    //! ```
+
+    /// ```text
+    /// ┌───────┐
+    /// │ Outer │
+    /// │ ┌───┐ │
+    /// │ │ A ├─┼──▶
+    /// │ └───┘ │
+    /// └───────┘
+    /// ```
+    pub struct Outer;
+
+    /// Only `text` blocks are drawings:
+    ///
+    /// ```ignore
+    /// ──│
+    /// ```
+    ///
+    /// ```text
+    /// A ◀─── B
+    /// ```
+    pub fn connected() -> bool {
+        /// ~~~~text,ignore
+        /// ┏━━━┓
+        /// ┗━━━┛
+        /// ~~~~
+        struct Nested;
+
+        let _ = (Nested, Outer);
+        true
+    }
+}
+
+#[test]
+fn items_are_unchanged() {
+    assert!(documented::connected());
+}
@@ -0,0 +1,30 @@
+use dottools::{Drawing, Graph};
+use dottools_macros::diagram;
+use pretty_assertions::assert_eq;
+
+const RAW: Drawing = diagram! {r"┌───┐
+│ A ├──▶
+└───┘"};
+
+#[test]
+fn raw() {
+    let graph = Graph::from(&RAW);
+    let expected = Graph::with_text(RAW.text).unwrap();
+    assert_eq!(graph.to_string(), expected.to_string());
+    let mut nodes: Vec<_> = graph.nodes().collect();
+    let mut expected_nodes: Vec<_> = expected.nodes().collect();
+    nodes.sort();
+    expected_nodes.sort();
+    assert_eq!(nodes, expected_nodes);
+    assert_eq!(graph.edge_count(), expected.edge_count());
+}
+
+#[test]
+fn escaped() {
+    let drawing = diagram!(
+        "┌─┐\n└\u{2500}┘\
+        "
+    );
+    assert_eq!(drawing.text, "┌─┐\n└─┘");
+    assert_eq!(Graph::from(&drawing).to_string(), "┌─┐\n└─┘");
+}
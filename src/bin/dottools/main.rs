@@ -0,0 +1,485 @@
+//! The `dottools` command, which reads box drawings from files or standard input.
+
+use dottools::embedded::{self, Kind};
+use dottools::logical::Diagram;
+use dottools::style::{self, Style};
+use dottools::{check, format};
+use dottools::{
+    csv, d2, dot, drawio, excalidraw, graphml, html, is_box_drawing, machine, mermaid, plantuml,
+    raster, svg, tikz, Error, Graph,
+};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "\
+usage: dottools <command> [options] [file...]
+
+Reads each file, or standard input when there are none or the file is `-`.
+
+commands:
+  parse [--format json|debug]  write the graph of a drawing
+  render                       draw a graph written by `parse --format json`
+  convert --to <format>        write a drawing in another format: dot, mermaid, plantuml, d2,
+                               drawio, excalidraw, graphml, csv, svg, html, tikz, png, ppm or
+                               rust (a state machine)
+  restyle --to <style>         redraw the strokes of a drawing: ascii, light, heavy or double
+  fmt [--check] [--style <style>] [--as markdown|source|drawing]
+                               tidy the drawings in each file, in place; with `--check`, list
+                               the files that are not tidy instead
+  check [--format text|json|sarif|github] [--as markdown|source|drawing]
+                               list the problems in the drawings in each file
+";
+
+/// A problem that stops a command, and whether it is in how the command was used.
+struct Failure {
+    message: String,
+    usage: bool,
+}
+
+impl Failure {
+    fn usage(message: String) -> Self {
+        Failure {
+            message,
+            usage: true,
+        }
+    }
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Failure {
+            message,
+            usage: false,
+        }
+    }
+}
+
+/// The options and files given to a command.
+struct Arguments {
+    values: HashMap<&'static str, String>,
+    flags: Vec<&'static str>,
+    files: Vec<String>,
+}
+
+impl Arguments {
+    /// Reads `--name value` and `--name=value` for each of the given names, `--flag` for each of
+    /// the given flags, and takes the rest as files.
+    fn parse(
+        args: &[String],
+        names: &[&'static str],
+        flags: &[&'static str],
+    ) -> Result<Self, Failure> {
+        let mut values = HashMap::new();
+        let mut set = Vec::new();
+        let mut files = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                files.push(arg.clone());
+                continue;
+            }
+            if let Some(flag) = flags.iter().find(|&&flag| arg[2..] == *flag) {
+                set.push(*flag);
+                continue;
+            }
+            let (name, value) = match arg.find('=') {
+                Some(i) => (&arg[2..i], Some(arg[i + 1..].to_string())),
+                None => (&arg[2..], None),
+            };
+            let name = names
+                .iter()
+                .find(|&&known| known == name)
+                .ok_or_else(|| Failure::usage(format!("unknown option `{}`", arg)))?;
+            let value = value
+                .or_else(|| args.next().cloned())
+                .ok_or_else(|| Failure::usage(format!("`--{}` needs a value", name)))?;
+            values.insert(*name, value);
+        }
+        Ok(Arguments {
+            values,
+            flags: set,
+            files,
+        })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    fn required(&self, name: &str) -> Result<&str, Failure> {
+        self.value(name)
+            .ok_or_else(|| Failure::usage(format!("`--{}` is required", name)))
+    }
+
+    /// The name and contents of each input.
+    fn inputs(&self) -> Result<Vec<(String, String)>, Failure> {
+        let mut inputs = Vec::new();
+        if self.files.is_empty() {
+            inputs.push(("<stdin>".to_string(), read("-")?));
+        }
+        for file in &self.files {
+            let name = if file == "-" { "<stdin>" } else { file };
+            inputs.push((name.to_string(), read(file)?));
+        }
+        Ok(inputs)
+    }
+}
+
+fn read(file: &str) -> Result<String, Failure> {
+    let mut text = String::new();
+    let result = if file == "-" {
+        io::stdin().read_to_string(&mut text).map(|_| ())
+    } else {
+        std::fs::read_to_string(file).map(|contents| text = contents)
+    };
+    result.map_err(|error| format!("{}: {}", file, error))?;
+    Ok(text)
+}
+
+/// Describes an error in the named input; columns are counted from one, like an editor does.
+fn describe(name: &str, error: Error) -> String {
+    match error {
+        Error::Syntax(position, message) => format!(
+            "{}:{}:{}: {}",
+            name,
+            position.line(),
+            position.column() + 1,
+            message
+        ),
+        error => format!("{}: {:?}", name, error),
+    }
+}
+
+fn graph(name: &str, text: &str) -> Result<Graph, Failure> {
+    Graph::with_text(text).map_err(|error| describe(name, error).into())
+}
+
+fn parse(arguments: &Arguments, out: &mut Vec<u8>) -> Result<(), Failure> {
+    let format = arguments.value("format").unwrap_or("json");
+    for (name, text) in arguments.inputs()? {
+        let graph = graph(&name, &text)?;
+        match format {
+            "json" => {
+                let json = serde_json::to_string_pretty(&graph).map_err(|e| e.to_string())?;
+                writeln!(out, "{}", json).unwrap();
+            }
+            "debug" => writeln!(out, "{:#?}", graph).unwrap(),
+            _ => return Err(Failure::usage(format!("unknown format `{}`", format))),
+        }
+    }
+    Ok(())
+}
+
+fn render(arguments: &Arguments, out: &mut Vec<u8>) -> Result<(), Failure> {
+    for (name, json) in arguments.inputs()? {
+        let graph: Graph =
+            serde_json::from_str(&json).map_err(|error| format!("{}: {}", name, error))?;
+        writeln!(out, "{}", graph).unwrap();
+    }
+    Ok(())
+}
+
+fn convert(arguments: &Arguments, out: &mut Vec<u8>) -> Result<(), Failure> {
+    let format = arguments.required("to")?;
+    for (name, text) in arguments.inputs()? {
+        // ASCII drawings are read like those drawn with box drawing characters.
+        let text = if text.contains(is_box_drawing) {
+            text
+        } else {
+            style::restyle(&text, Style::Light)
+        };
+        let graph = graph(&name, &text)?;
+        let diagram = Diagram::from(&graph);
+        let converted = match format {
+            "dot" => dot::render(&diagram),
+            "mermaid" => mermaid::render(&diagram),
+            "plantuml" => plantuml::render(&diagram),
+            "d2" => d2::render(&diagram),
+            "drawio" => drawio::render(&diagram),
+            "excalidraw" => excalidraw::render(&graph),
+            "graphml" => graphml::render_diagram(&diagram),
+            "csv" => csv::edges(&diagram),
+            "svg" => svg::render(&graph, &svg::Options::default()),
+            "html" => html::render(&graph),
+            "tikz" => tikz::render(&graph, &tikz::Options::default()),
+            "png" => {
+                let image = raster::render(&graph, &raster::Options::default());
+                out.extend(image.to_png());
+                continue;
+            }
+            "ppm" => {
+                let image = raster::render(&graph, &raster::Options::default());
+                out.extend(image.to_ppm());
+                continue;
+            }
+            "rust" => machine::Machine::from_diagram(&diagram)
+                .map_err(|error| describe(&name, error))?
+                .to_rust(),
+            _ => return Err(Failure::usage(format!("unknown format `{}`", format))),
+        };
+        out.extend(converted.as_bytes());
+        if !converted.ends_with('\n') {
+            out.push(b'\n');
+        }
+    }
+    Ok(())
+}
+
+fn restyle(arguments: &Arguments, out: &mut Vec<u8>) -> Result<(), Failure> {
+    let style: Style = arguments.required("to")?.parse().map_err(Failure::usage)?;
+    for (_, text) in arguments.inputs()? {
+        out.extend(style::restyle(&text, style).as_bytes());
+    }
+    Ok(())
+}
+
+/// The kind of document an input is, from `--as` or else its name. Standard input is a drawing.
+fn kind(arguments: &Arguments, name: &str) -> Result<Kind, Failure> {
+    match arguments.value("as") {
+        Some("markdown") => Ok(Kind::Markdown),
+        Some("source") => Ok(Kind::Source),
+        Some("drawing") => Ok(Kind::Drawing),
+        Some(kind) => Err(Failure::usage(format!("unknown kind `{}`", kind))),
+        None if name == "<stdin>" => Ok(Kind::Drawing),
+        None => Ok(Kind::of(Path::new(name))),
+    }
+}
+
+/// Writes the output so far to standard output, for a command that fails on what it reports.
+fn flush(out: &mut Vec<u8>) {
+    io::stdout().write_all(out).unwrap();
+    out.clear();
+}
+
+/// Tidies the drawings in each file, in place, or lists the files that are not tidy with
+/// `--check`. Standard input is tidied to standard output.
+fn fmt(arguments: &Arguments, out: &mut Vec<u8>) -> Result<(), Failure> {
+    let options = format::Options {
+        style: match arguments.value("style") {
+            Some(style) => Some(style.parse().map_err(Failure::usage)?),
+            None => None,
+        },
+    };
+    let check = arguments.flag("check");
+    let mut untidy = 0;
+    for (name, text) in arguments.inputs()? {
+        let formatted = match kind(arguments, &name)? {
+            Kind::Markdown => format::format_markdown(&text, &options),
+            Kind::Source => format::format_source(&text, &options),
+            Kind::Drawing => format::format(&text, &options),
+        };
+        if formatted == text {
+            if name == "<stdin>" && !check {
+                out.extend(formatted.as_bytes());
+            }
+            continue;
+        }
+        untidy += 1;
+        if check {
+            writeln!(out, "{}", name).unwrap();
+        } else if name == "<stdin>" {
+            out.extend(formatted.as_bytes());
+        } else {
+            std::fs::write(&name, formatted).map_err(|error| format!("{}: {}", name, error))?;
+        }
+    }
+    if check && untidy > 0 {
+        flush(out);
+        let files = if untidy == 1 {
+            "input is"
+        } else {
+            "inputs are"
+        };
+        return Err(format!("{} {} not tidy", untidy, files).into());
+    }
+    Ok(())
+}
+
+/// A problem in a drawing, at a line and column of the file it is in, counted from one.
+struct Problem {
+    file: String,
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+/// The problems that `check` finds in a drawing, at their positions in the file.
+fn problems(file: &str, drawing: &embedded::Embedded) -> Vec<Problem> {
+    check::check(&drawing.text())
+        .into_iter()
+        .map(|diagnostic| {
+            let position = drawing.source(diagnostic.position);
+            Problem {
+                file: file.to_string(),
+                line: position.line(),
+                column: position.column() + 1,
+                message: diagnostic.message,
+            }
+        })
+        .collect()
+}
+
+/// Escapes a GitHub workflow command's message, or with `property`, one of its properties.
+fn escape(text: &str, property: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            '\r' => escaped.push_str("%0D"),
+            '\n' => escaped.push_str("%0A"),
+            ':' if property => escaped.push_str("%3A"),
+            ',' if property => escaped.push_str("%2C"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Lists the problems in the drawings in each file, and fails if there are any.
+fn check(arguments: &Arguments, out: &mut Vec<u8>) -> Result<(), Failure> {
+    let format = arguments.value("format").unwrap_or("text");
+    let mut found = Vec::new();
+    for (name, text) in arguments.inputs()? {
+        let kind = kind(arguments, &name)?;
+        for drawing in embedded::drawings(&text, kind) {
+            found.extend(problems(&name, &drawing));
+        }
+    }
+    match format {
+        "text" => {
+            for problem in &found {
+                writeln!(
+                    out,
+                    "{}:{}:{}: {}",
+                    problem.file, problem.line, problem.column, problem.message
+                )
+                .unwrap();
+            }
+        }
+        "json" => {
+            let problems: Vec<serde_json::Value> = found
+                .iter()
+                .map(|problem| {
+                    serde_json::json!({
+                        "file": problem.file,
+                        "line": problem.line,
+                        "column": problem.column,
+                        "message": problem.message,
+                    })
+                })
+                .collect();
+            let json = serde_json::to_string_pretty(&problems).map_err(|e| e.to_string())?;
+            writeln!(out, "{}", json).unwrap();
+        }
+        "sarif" => {
+            let results: Vec<serde_json::Value> = found
+                .iter()
+                .map(|problem| {
+                    serde_json::json!({
+                        "ruleId": "drawing",
+                        "level": "error",
+                        "message": { "text": problem.message },
+                        "locations": [{
+                            "physicalLocation": {
+                                "artifactLocation": { "uri": problem.file },
+                                "region": {
+                                    "startLine": problem.line,
+                                    "startColumn": problem.column,
+                                },
+                            },
+                        }],
+                    })
+                })
+                .collect();
+            let sarif = serde_json::json!({
+                "version": "2.1.0",
+                "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": "dottools",
+                            "version": env!("CARGO_PKG_VERSION"),
+                            "rules": [{
+                                "id": "drawing",
+                                "shortDescription": { "text": "A problem in a box drawing" },
+                            }],
+                        },
+                    },
+                    "results": results,
+                }],
+            });
+            let json = serde_json::to_string_pretty(&sarif).map_err(|e| e.to_string())?;
+            writeln!(out, "{}", json).unwrap();
+        }
+        "github" => {
+            for problem in &found {
+                writeln!(
+                    out,
+                    "::error file={},line={},col={}::{}",
+                    escape(&problem.file, true),
+                    problem.line,
+                    problem.column,
+                    escape(&problem.message, false)
+                )
+                .unwrap();
+            }
+        }
+        _ => return Err(Failure::usage(format!("unknown format `{}`", format))),
+    }
+    if !found.is_empty() {
+        flush(out);
+        let problems = if found.len() == 1 {
+            "problem"
+        } else {
+            "problems"
+        };
+        return Err(format!("{} {} found", found.len(), problems).into());
+    }
+    Ok(())
+}
+
+/// Runs a command, writing its output to `out`, which is only written to standard output when
+/// the whole command succeeds.
+fn run(args: &[String], out: &mut Vec<u8>) -> Result<(), Failure> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => return Err(Failure::usage("missing command".to_string())),
+    };
+    match command {
+        "parse" => parse(&Arguments::parse(args, &["format"], &[])?, out),
+        "render" => render(&Arguments::parse(args, &[], &[])?, out),
+        "convert" => convert(&Arguments::parse(args, &["to"], &[])?, out),
+        "restyle" => restyle(&Arguments::parse(args, &["to"], &[])?, out),
+        "fmt" => fmt(&Arguments::parse(args, &["style", "as"], &["check"])?, out),
+        "check" => check(&Arguments::parse(args, &["format", "as"], &[])?, out),
+        "help" | "--help" | "-h" => {
+            out.extend(USAGE.as_bytes());
+            Ok(())
+        }
+        _ => Err(Failure::usage(format!("unknown command `{}`", command))),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut out = Vec::new();
+    match run(&args, &mut out) {
+        Ok(()) => {
+            io::stdout().write_all(&out).unwrap();
+        }
+        Err(failure) => {
+            eprintln!("dottools: {}", failure.message);
+            if failure.usage {
+                eprint!("\n{}", USAGE);
+                process::exit(2);
+            }
+            process::exit(1);
+        }
+    }
+}
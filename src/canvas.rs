@@ -0,0 +1,453 @@
+use crate::{
+    arrowhead, is_box_drawing, is_drawing, is_word, junction, Character, Error, Graph, Region,
+};
+use std::fmt;
+use unicode_normalization::char::is_combining_mark;
+
+/// A single character cell; lines are recorded as the brush of each arm that leaves the cell so
+/// that overlapping strokes merge into the right junction.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Cell {
+    text: Option<char>,
+    /// The character that was read into the cell, kept for as long as its arms are unchanged.
+    original: Option<char>,
+    pub(crate) north: Option<char>,
+    pub(crate) east: Option<char>,
+    pub(crate) south: Option<char>,
+    pub(crate) west: Option<char>,
+}
+
+/// The cells of a box, inclusive of its border.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Rectangle {
+    pub(crate) top: usize,
+    pub(crate) left: usize,
+    pub(crate) bottom: usize,
+    pub(crate) right: usize,
+}
+
+impl Rectangle {
+    /// Returns true for the cells inside the border.
+    pub(crate) fn is_inside(&self, line: usize, column: usize) -> bool {
+        self.top < line && line < self.bottom && self.left < column && column < self.right
+    }
+
+    /// Returns true for the cells of the border, corners included.
+    pub(crate) fn is_border(&self, line: usize, column: usize) -> bool {
+        (self.top..=self.bottom).contains(&line)
+            && (self.left..=self.right).contains(&column)
+            && !self.is_inside(line, column)
+    }
+}
+
+/// A grid of cells that strokes and text are drawn onto before being parsed into a `Graph`.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Canvas {
+    lines: Vec<Vec<Cell>>,
+}
+
+/// Maps a dashed brush onto the solid brush of the same weight.
+fn solid(brush: char) -> char {
+    match brush {
+        '┄' | '┈' | '╌' => '─',
+        '┅' | '┉' | '╍' => '━',
+        '┆' | '┊' | '╎' => '│',
+        '┇' | '┋' | '╏' => '┃',
+        brush => brush,
+    }
+}
+
+/// The brush of the vertical stroke with the same weight and dash as the horizontal `brush`.
+pub(crate) fn vertical(brush: char) -> char {
+    match brush {
+        '─' => '│',
+        '━' => '┃',
+        '═' => '║',
+        '┄' => '┆',
+        '┅' => '┇',
+        '┈' => '┊',
+        '┉' => '┋',
+        '╌' => '╎',
+        '╍' => '╏',
+        '-' => '|',
+        brush => brush,
+    }
+}
+
+impl Cell {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.text.is_none() && !self.is_stroke()
+    }
+
+    /// Returns true when the cell is part of a stroke.
+    pub(crate) fn is_stroke(&self) -> bool {
+        self.north.is_some() || self.east.is_some() || self.south.is_some() || self.west.is_some()
+    }
+
+    /// The text written into the cell, if any.
+    pub(crate) fn text(&self) -> Option<char> {
+        self.text
+    }
+
+    /// The character that the cell is drawn with.
+    pub(crate) fn character(&self) -> char {
+        if let Some(c) = self.text {
+            return c;
+        }
+        let Cell {
+            original,
+            north,
+            east,
+            south,
+            west,
+            ..
+        } = *self;
+        if let Some(c) = original {
+            let character = Character::from(c);
+            if (
+                character.north,
+                character.east,
+                character.south,
+                character.west,
+            ) == (north, east, south, west)
+            {
+                return c;
+            }
+        }
+        stroke(north, east, south, west)
+    }
+}
+
+/// The character that draws the given arms: the junction with exactly those arms, or failing
+/// that the solid one, or failing that a straight line.
+pub(crate) fn stroke(
+    north: Option<char>,
+    east: Option<char>,
+    south: Option<char>,
+    west: Option<char>,
+) -> char {
+    match (north, east, south, west) {
+        (None, None, None, None) => ' ',
+        (Some(brush), None, None, None) | (None, None, Some(brush), None) => brush,
+        (None, Some(brush), None, None) | (None, None, None, Some(brush)) => brush,
+        _ => junction(north, east, south, west)
+            .or_else(|| {
+                junction(
+                    north.map(solid),
+                    east.map(solid),
+                    south.map(solid),
+                    west.map(solid),
+                )
+            })
+            .unwrap_or_else(|| east.or(west).or(north).or(south).map(solid).unwrap_or(' ')),
+    }
+}
+
+impl Canvas {
+    pub(crate) fn new() -> Self {
+        Canvas::default()
+    }
+
+    /// Reads a drawing into a canvas. Diagonal strokes cannot be extended, so they are kept as
+    /// text, as are ASCII strokes between two letters or digits, like the `-` of `lib-core`.
+    pub(crate) fn from_text(input: &str) -> Self {
+        let mut canvas = Canvas::new();
+        for (line, text) in input.lines().enumerate() {
+            let characters: Vec<char> = text.chars().filter(|&c| !is_combining_mark(c)).collect();
+            for (column, &c) in characters.iter().enumerate() {
+                if c.is_whitespace() {
+                    continue;
+                }
+                let before = column.checked_sub(1).map(|column| characters[column]);
+                let after = characters.get(column + 1).cloned();
+                let character = Character::from(c);
+                let cell = canvas.cell(line, column);
+                if is_drawing(c)
+                    && (is_box_drawing(c) || !(is_word(before) && is_word(after)))
+                    && character.north_east.is_none()
+                    && character.north_west.is_none()
+                    && (
+                        character.north,
+                        character.east,
+                        character.south,
+                        character.west,
+                    ) != (None, None, None, None)
+                {
+                    cell.original = Some(c);
+                    cell.north = character.north;
+                    cell.east = character.east;
+                    cell.south = character.south;
+                    cell.west = character.west;
+                } else {
+                    cell.text = Some(c);
+                }
+            }
+        }
+        canvas
+    }
+
+    /// Returns the cell at the given position, which is empty when nothing has been drawn there.
+    pub(crate) fn get(&self, line: usize, column: usize) -> Cell {
+        self.lines
+            .get(line)
+            .and_then(|cells| cells.get(column))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns true for a cell of text. An ASCII stroke, a diagonal or an `X` is only text when
+    /// it is in a word with another letter or digit, as in `X/Y`, or when it is inside one of
+    /// the `rectangles`. Inside, so is a stroke that joins no other, like the `─` of `a ─ b`.
+    pub(crate) fn is_text(&self, rectangles: &[Rectangle], line: usize, column: usize) -> bool {
+        let inside = rectangles
+            .iter()
+            .any(|rectangle| rectangle.is_inside(line, column));
+        let c = match self.get(line, column).text() {
+            Some(c) => c,
+            None => return inside && self.is_lone(line, column),
+        };
+        if !is_drawing(c) || inside {
+            return true;
+        }
+        if is_box_drawing(c) {
+            return false;
+        }
+        let width = self.lines.get(line).map_or(0, Vec::len);
+        let text = |column: usize| self.get(line, column).text();
+        let before = (0..column).rev().map(text).take_while(Option::is_some);
+        let after = (column + 1..width).map(text).take_while(Option::is_some);
+        before
+            .chain(after)
+            .any(|c| c.is_some_and(|c| !is_drawing(c) && c.is_alphanumeric()))
+    }
+
+    /// Returns true for a stroke none of whose arms meets another stroke or an arrowhead.
+    fn is_lone(&self, line: usize, column: usize) -> bool {
+        let cell = self.get(line, column);
+        if !cell.is_stroke() {
+            return false;
+        }
+        let meets = |arm: Option<char>, next: Option<(usize, usize)>, direction: Region| {
+            arm.is_some()
+                && next.is_some_and(|(line, column)| {
+                    let next = self.get(line, column);
+                    let back = match direction {
+                        Region::North => next.south,
+                        Region::East => next.west,
+                        Region::South => next.north,
+                        _ => next.east,
+                    };
+                    back.is_some() || next.text().and_then(arrowhead) == Some(direction)
+                })
+        };
+        !(meets(
+            cell.north,
+            line.checked_sub(1).map(|l| (l, column)),
+            Region::North,
+        ) || meets(cell.east, Some((line, column + 1)), Region::East)
+            || meets(cell.south, Some((line + 1, column)), Region::South)
+            || meets(
+                cell.west,
+                column.checked_sub(1).map(|c| (line, c)),
+                Region::West,
+            ))
+    }
+
+    /// Returns true when a stroke leads into the cell from the side opposite `direction`, which is
+    /// what makes a glyph there that points in that direction an arrowhead.
+    pub(crate) fn leads_into(&self, line: usize, column: usize, direction: Region) -> bool {
+        match direction {
+            Region::North => self.get(line + 1, column).north.is_some(),
+            Region::East => column > 0 && self.get(line, column - 1).east.is_some(),
+            Region::South => line > 0 && self.get(line - 1, column).south.is_some(),
+            Region::West => self.get(line, column + 1).west.is_some(),
+            Region::Center => false,
+        }
+    }
+
+    /// The number of lines, and the length of the longest line.
+    pub(crate) fn size(&self) -> (usize, usize) {
+        let width = self.lines.iter().map(Vec::len).max().unwrap_or(0);
+        (self.lines.len(), width)
+    }
+
+    /// Finds the boxes of the drawing; a box that is divided by a stroke is found as the boxes
+    /// on either side of the stroke.
+    pub(crate) fn rectangles(&self) -> Vec<Rectangle> {
+        let mut rectangles = Vec::new();
+        let (height, width) = self.size();
+        for top in 0..height {
+            for left in 0..width {
+                let corner = self.get(top, left);
+                if corner.east.is_none() || corner.south.is_none() {
+                    continue;
+                }
+                if let Some(rectangle) = self.rectangle_from(top, left, width, height) {
+                    rectangles.push(rectangle);
+                }
+            }
+        }
+        rectangles
+    }
+
+    /// Finds the smallest box whose top left corner is at the given cell.
+    fn rectangle_from(
+        &self,
+        top: usize,
+        left: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<Rectangle> {
+        for right in left + 1..width {
+            let cell = self.get(top, right);
+            cell.west?;
+            if cell.south.is_none() {
+                continue;
+            }
+            for bottom in top + 1..height {
+                let (l, r) = (self.get(bottom, left), self.get(bottom, right));
+                if l.north.is_none() || r.north.is_none() {
+                    break;
+                }
+                if l.east.is_some()
+                    && r.west.is_some()
+                    && (left + 1..right).all(|column| {
+                        let cell = self.get(bottom, column);
+                        cell.east.is_some() && cell.west.is_some()
+                    })
+                {
+                    return Some(Rectangle {
+                        top,
+                        left,
+                        bottom,
+                        right,
+                    });
+                }
+                if l.south.is_none() || r.south.is_none() {
+                    break;
+                }
+            }
+            cell.east?;
+        }
+        None
+    }
+
+    fn cell(&mut self, line: usize, column: usize) -> &mut Cell {
+        if self.lines.len() < line + 1 {
+            self.lines.resize_with(line + 1, Default::default);
+        }
+        let cells = &mut self.lines[line];
+        if cells.len() < column + 1 {
+            cells.resize(column + 1, Cell::default());
+        }
+        &mut cells[column]
+    }
+
+    /// Returns true when nothing has been drawn at the given cell.
+    pub(crate) fn is_free(&self, line: usize, column: usize) -> bool {
+        self.lines
+            .get(line)
+            .and_then(|cells| cells.get(column))
+            .map(Cell::is_empty)
+            .unwrap_or(true)
+    }
+
+    /// Returns true when nothing has been drawn in the `width` cells starting at the given cell.
+    pub(crate) fn is_span_free(&self, line: usize, column: usize, width: usize) -> bool {
+        (column..column + width).all(|column| self.is_free(line, column))
+    }
+
+    /// Draws a horizontal stroke between two columns (inclusive) of a line.
+    pub(crate) fn horizontal(&mut self, line: usize, from: usize, to: usize, brush: char) {
+        let (from, to) = if from < to { (from, to) } else { (to, from) };
+        for column in from..=to {
+            let cell = self.cell(line, column);
+            if column < to {
+                cell.east = Some(brush);
+            }
+            if column > from {
+                cell.west = Some(brush);
+            }
+        }
+        // A lone cell is drawn as a short stroke, unless it already belongs to another.
+        let cell = self.cell(line, from);
+        if from == to && cell.is_empty() {
+            cell.east = Some(brush);
+        }
+    }
+
+    /// Draws a vertical stroke between two lines (inclusive) of a column.
+    pub(crate) fn vertical(&mut self, column: usize, from: usize, to: usize, brush: char) {
+        let (from, to) = if from < to { (from, to) } else { (to, from) };
+        for line in from..=to {
+            let cell = self.cell(line, column);
+            if line < to {
+                cell.south = Some(brush);
+            }
+            if line > from {
+                cell.north = Some(brush);
+            }
+        }
+        let cell = self.cell(from, column);
+        if from == to && cell.is_empty() {
+            cell.south = Some(brush);
+        }
+    }
+
+    /// Draws a rectangle with the given corners, using `brush` for its horizontal sides.
+    pub(crate) fn rectangle(
+        &mut self,
+        (top, left): (usize, usize),
+        (bottom, right): (usize, usize),
+        brush: char,
+    ) {
+        self.horizontal(top, left, right, brush);
+        self.horizontal(bottom, left, right, brush);
+        self.vertical(left, top, bottom, vertical(brush));
+        self.vertical(right, top, bottom, vertical(brush));
+    }
+
+    /// Writes a single character that is not part of any stroke.
+    pub(crate) fn put(&mut self, line: usize, column: usize, c: char) {
+        self.cell(line, column).text = Some(c);
+    }
+
+    /// Draws a polyline through the given cells, each consecutive pair of which must share a
+    /// line or a column.
+    pub(crate) fn polyline(&mut self, points: &[(usize, usize)], brush: char) {
+        for pair in points.windows(2) {
+            let ((l0, c0), (l1, c1)) = (pair[0], pair[1]);
+            if l0 == l1 {
+                self.horizontal(l0, c0, c1, brush);
+            } else {
+                self.vertical(c0, l0, l1, vertical(brush));
+            }
+        }
+    }
+
+    /// Writes a line of text starting at the given cell.
+    pub(crate) fn text(&mut self, line: usize, column: usize, text: &str) {
+        for (i, c) in text.chars().enumerate() {
+            self.put(line, column + i, c);
+        }
+    }
+
+    /// Parses the drawing, keeping its text, into a `Graph`.
+    pub(crate) fn to_graph(&self) -> Result<Graph, Error> {
+        Graph::with_text(&self.to_string())
+    }
+}
+
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self
+            .lines
+            .iter()
+            .map(|cells| {
+                let line: String = cells.iter().map(Cell::character).collect();
+                line.trim_end().to_string()
+            })
+            .collect();
+        f.write_str(lines.join("\n").trim_end())
+    }
+}
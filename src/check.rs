@@ -0,0 +1,98 @@
+//! Problems in a drawing that parse without error, but are unlikely to be what was meant.
+//!
+//! ```
+//! use dottools::check::check;
+//!
+//! let diagnostics = check("┌──┐\n│  │\n└──┘ ─│");
+//! assert_eq!(diagnostics.len(), 1);
+//! assert_eq!(diagnostics[0].message, "`─` does not join `│` to its east");
+//! assert_eq!((diagnostics[0].position.line(), diagnostics[0].position.column()), (3, 5));
+//! ```
+
+use crate::canvas::Canvas;
+use crate::{arrowhead, is_box_drawing, LineColumn};
+use unicode_normalization::char::is_combining_mark;
+
+/// A problem at a position in the text, counted in characters like a node's `source`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub position: LineColumn,
+    pub message: String,
+}
+
+/// Finds the tabs, the strokes that touch another stroke without joining it, and the
+/// arrowheads that no stroke leads into.
+pub fn check(text: &str) -> Vec<Diagnostic> {
+    let canvas = Canvas::from_text(text);
+    let glyphs: Vec<Vec<char>> = text
+        .lines()
+        .map(|line| line.chars().filter(|&c| !is_combining_mark(c)).collect())
+        .collect();
+    let mut diagnostics = Vec::new();
+    for (line, text) in text.lines().enumerate() {
+        // The source column of each visual column.
+        let mut columns = Vec::new();
+        for (column, c) in text.chars().enumerate() {
+            if c == '\t' {
+                diagnostics.push(Diagnostic {
+                    position: LineColumn {
+                        line: line + 1,
+                        column,
+                    },
+                    message: "tab in drawing; drawings are aligned with spaces".to_string(),
+                });
+            }
+            if !is_combining_mark(c) {
+                columns.push((column, c));
+            }
+        }
+        for (visual, &(column, c)) in columns.iter().enumerate() {
+            let position = LineColumn {
+                line: line + 1,
+                column,
+            };
+            let cell = canvas.get(line, visual);
+            let neighbours = [
+                (
+                    cell.north,
+                    line.checked_sub(1).map(|l| (l, visual)),
+                    "north",
+                ),
+                (cell.east, Some((line, visual + 1)), "east"),
+                (cell.south, Some((line + 1, visual)), "south"),
+                (cell.west, visual.checked_sub(1).map(|v| (line, v)), "west"),
+            ];
+            for &(arm, neighbour, side) in &neighbours {
+                let (l, v) = match (arm, neighbour) {
+                    (Some(_), Some(neighbour)) => neighbour,
+                    _ => continue,
+                };
+                let other = canvas.get(l, v);
+                let joined = match side {
+                    "north" => other.south.is_some(),
+                    "east" => other.west.is_some(),
+                    "south" => other.north.is_some(),
+                    _ => other.east.is_some(),
+                };
+                if other.is_stroke() && !joined {
+                    let glyph = glyphs[l][v];
+                    diagnostics.push(Diagnostic {
+                        position,
+                        message: format!("`{}` does not join `{}` to its {}", c, glyph, side),
+                    });
+                }
+            }
+            if let (true, Some(direction)) = (is_box_drawing(c), arrowhead(c)) {
+                if !canvas.leads_into(line, visual, direction) {
+                    diagnostics.push(Diagnostic {
+                        position,
+                        message: format!("`{}` is not at the end of a stroke", c),
+                    });
+                }
+            }
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.position.line, diagnostic.position.column));
+    diagnostics
+}
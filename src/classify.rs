@@ -0,0 +1,91 @@
+//! How likely a block of text is to be a drawing, rather than prose, a table ruled with dashes,
+//! or code full of `-`, `=` and `|`.
+//!
+//! The text is parsed, and the confidence grows with the boxes found, with the share of wire ends
+//! that reach a box or an arrowhead rather than dangling, with the share of strokes drawn with
+//! box drawing characters, and with how much of the text is strokes rather than words. Straight
+//! rules that make no box and lead nowhere are never taken to be drawings.
+//!
+//! ```
+//! use dottools::classify::{confidence, THRESHOLD};
+//!
+//! assert!(confidence("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘") > 0.95);
+//! assert!(confidence("+---+\n| A |\n+---+") > THRESHOLD);
+//! assert!(confidence("| a | b |\n|---|---|\n| 1 | 2 |") < THRESHOLD);
+//! assert!(confidence("if a == b || c - d > 0 {}") < THRESHOLD);
+//! ```
+
+use crate::logical::Diagram;
+use crate::style::{restyle, Style};
+use crate::{is_box_drawing, is_drawing, is_word, Graph};
+use std::collections::HashSet;
+use std::mem::discriminant;
+
+/// The confidence from which text is taken to be a drawing.
+pub const THRESHOLD: f64 = 0.5;
+
+/// The number of strokes, how many of them are box drawing, and the number of other characters
+/// that are not whitespace. A stroke between two letters or digits, as in `one-to-one`, is text.
+fn count(text: &str) -> (usize, usize, usize) {
+    let (mut strokes, mut boxed, mut words) = (0, 0, 0);
+    for line in text.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        for (i, &c) in chars.iter().enumerate() {
+            let before = i.checked_sub(1).map(|i| chars[i]);
+            let between = is_word(before) && is_word(chars.get(i + 1).cloned());
+            if is_box_drawing(c) {
+                strokes += 1;
+                boxed += 1;
+            } else if is_drawing(c) && !between {
+                strokes += 1;
+            } else if !c.is_whitespace() {
+                words += 1;
+            }
+        }
+    }
+    (strokes, boxed, words)
+}
+
+/// Returns the confidence, between zero and one, that the text is a drawing.
+pub fn confidence(text: &str) -> f64 {
+    let (_, boxed, _) = count(text);
+    // ASCII drawings are read like those drawn with box drawing characters.
+    let drawn = restyle(text, Style::Light);
+    let (strokes, _, words) = count(&drawn);
+    if strokes == 0 {
+        return 0.0;
+    }
+    let graph = match Graph::with_text(&drawn) {
+        Ok(graph) => graph,
+        Err(_) => return 0.0,
+    };
+    let diagram = Diagram::from(&graph);
+    let ends: Vec<_> = diagram.wires.iter().flat_map(|wire| &wire.ends).collect();
+    let connected = ends
+        .iter()
+        .filter(|end| end.block.is_some() || end.arrow)
+        .count();
+    let connected = if ends.is_empty() {
+        0.0
+    } else {
+        connected as f64 / ends.len() as f64
+    };
+    let boxes = diagram.blocks.len().min(3) as f64;
+    let boxed = boxed.min(strokes) as f64 / strokes as f64;
+    let density = strokes as f64 / (strokes + words) as f64;
+
+    let evidence = -2.0 + 1.5 * boxes + 2.0 * connected + 2.0 * boxed + 2.0 * (density - 0.5);
+    let confidence = 1.0 / (1.0 + (-evidence).exp());
+    // Strokes that all run one way, and neither make a box nor lead anywhere, are rules, like
+    // `─── Helpers ───`.
+    let directions: HashSet<_> = graph
+        .0
+        .all_edges()
+        .map(|(_, _, edge)| discriminant(&edge.1))
+        .collect();
+    if boxes == 0.0 && connected == 0.0 && directions.len() <= 1 {
+        confidence * THRESHOLD
+    } else {
+        confidence
+    }
+}
@@ -0,0 +1,85 @@
+//! Comma-separated tables of a diagram's connections, for data frames and spreadsheets.
+//!
+//! Boxes are identified as `n0`, `n1` and so on, in the order of
+//! [`Diagram::blocks`](../logical/struct.Diagram.html#structfield.blocks).
+//!
+//! ```
+//! use dottools::{csv, logical::Diagram, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! let diagram = Diagram::from(&graph);
+//! assert_eq!(
+//!     csv::edges(&diagram),
+//!     "source,target,source_label,target_label,directed,label,brush,dashed\n\
+//!      n0,n1,A,B,true,,light,false\n"
+//! );
+//! assert_eq!(csv::adjacency(&diagram), ",n0,n1\nn0,0,1\nn1,0,0\n");
+//! ```
+
+use crate::logical::Diagram;
+use crate::Weight;
+use std::fmt::Write;
+
+/// Quotes a field if it would otherwise be misread.
+fn field(text: &str) -> String {
+    if text.contains(&[',', '"', '\n', '\r'][..]) || text.starts_with(' ') || text.ends_with(' ') {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// One row per connection, with the ids and labels of the boxes at either end, whether the
+/// connection is directed, and the label and brush of its wire.
+pub fn edges(diagram: &Diagram) -> String {
+    let mut out =
+        String::from("source,target,source_label,target_label,directed,label,brush,dashed\n");
+    for connection in diagram.connections() {
+        let wire = &diagram.wires[connection.wire];
+        let weight = match wire.brush.weight() {
+            Weight::Light => "light",
+            Weight::Heavy => "heavy",
+            Weight::Double => "double",
+        };
+        writeln!(
+            out,
+            "n{},n{},{},{},{},{},{},{}",
+            connection.from,
+            connection.to,
+            field(&diagram.blocks[connection.from].label),
+            field(&diagram.blocks[connection.to].label),
+            connection.directed,
+            field(wire.label.as_ref().map_or("", String::as_str)),
+            weight,
+            wire.brush.is_dashed()
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// A square matrix with a row and a column per box, counting the connections from the box of the
+/// row to the box of the column. Undirected connections count both ways.
+pub fn adjacency(diagram: &Diagram) -> String {
+    let n = diagram.blocks.len();
+    let mut matrix = vec![vec![0; n]; n];
+    for connection in diagram.connections() {
+        matrix[connection.from][connection.to] += 1;
+        if !connection.directed {
+            matrix[connection.to][connection.from] += 1;
+        }
+    }
+    let mut out = String::new();
+    for i in 0..n {
+        write!(out, ",n{}", i).unwrap();
+    }
+    out.push('\n');
+    for (i, row) in matrix.iter().enumerate() {
+        write!(out, "n{}", i).unwrap();
+        for count in row {
+            write!(out, ",{}", count).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
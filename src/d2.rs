@@ -0,0 +1,75 @@
+//! D2 diagrams.
+//!
+//! ```
+//! use dottools::{d2, logical::Diagram, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! assert_eq!(
+//!     d2::render(&Diagram::from(&graph)),
+//!     "n0: \"A\"\nn1: \"B\"\nn0 -> n1\n"
+//! );
+//! ```
+
+use crate::logical::Diagram;
+use std::fmt::Write;
+
+const INDENT: &str = "  ";
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The key of a box, which is qualified by the keys of the boxes around it.
+fn key(diagram: &Diagram, mut block: usize) -> String {
+    let mut keys = vec![format!("n{}", block)];
+    while let Some(parent) = diagram.blocks[block].parent {
+        keys.push(format!("n{}", parent));
+        block = parent;
+    }
+    keys.reverse();
+    keys.join(".")
+}
+
+fn block(diagram: &Diagram, out: &mut String, parent: Option<usize>, depth: usize) {
+    for (i, block) in diagram.blocks.iter().enumerate() {
+        if block.parent != parent {
+            continue;
+        }
+        let indent = INDENT.repeat(depth);
+        write!(out, "{}n{}: {}", indent, i, quote(&block.label)).unwrap();
+        if diagram.blocks.iter().any(|child| child.parent == Some(i)) {
+            out.push_str(" {\n");
+            self::block(diagram, out, Some(i), depth + 1);
+            writeln!(out, "{}}}", indent).unwrap();
+        } else {
+            out.push('\n');
+        }
+    }
+}
+
+/// Writes the boxes and wires of the diagram as shapes connected by connections. Boxes that
+/// contain other boxes become containers, and dashed wires get a dashed stroke.
+pub fn render(diagram: &Diagram) -> String {
+    let mut out = String::new();
+    block(diagram, &mut out, None, 0);
+    for connection in diagram.connections() {
+        let wire = &diagram.wires[connection.wire];
+        let arrow = if connection.directed { "->" } else { "--" };
+        write!(
+            out,
+            "{} {} {}",
+            key(diagram, connection.from),
+            arrow,
+            key(diagram, connection.to)
+        )
+        .unwrap();
+        if let Some(label) = &wire.label {
+            write!(out, ": {}", quote(label)).unwrap();
+        }
+        if wire.brush.is_dashed() {
+            out.push_str(" {style.stroke-dash: 3}");
+        }
+        out.push('\n');
+    }
+    out
+}
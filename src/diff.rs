@@ -0,0 +1,365 @@
+//! What changed between two versions of a drawing, in terms of its boxes and the connections
+//! between them.
+//!
+//! Boxes are matched up by their labels, and then, for the boxes left over, by where they are
+//! drawn: a box whose label changed but that still overlaps where it was is renamed rather than
+//! removed and added. Moving a box, or restyling its wires, is not a change. A connection is
+//! rerouted when its wire leaves or enters a box on a different side, not merely when its cells
+//! move.
+//!
+//! ```
+//! use dottools::{diff, Graph};
+//!
+//! let old = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! let new = Graph::with_text("┌───┐  ┌───┐\n│ A ├─▶│ C │\n└───┘  └───┘").unwrap();
+//! let changes: Vec<String> = diff::diff(&old, &new).iter().map(ToString::to_string).collect();
+//! assert_eq!(changes, vec![r#"renamed box "B" to "C""#]);
+//! ```
+
+use crate::logical::{Block, Connection, Diagram, End};
+use crate::{Graph, LineColumn, Region};
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Write;
+
+/// The boxes at either end of a connection, by label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub from: String,
+    pub to: String,
+    pub directed: bool,
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arrow = if self.directed { "->" } else { "--" };
+        write!(f, "{:?} {} {:?}", self.from, arrow, self.to)
+    }
+}
+
+/// A change from the old version of a drawing to the new one. Boxes and wires are given by
+/// their indices in the [`Diagram`](../logical/struct.Diagram.html) of the version that they are
+/// in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    AddedBox {
+        new: usize,
+        label: String,
+    },
+    RemovedBox {
+        old: usize,
+        label: String,
+    },
+    RenamedBox {
+        old: usize,
+        new: usize,
+        from: String,
+        to: String,
+    },
+    Connected {
+        new: usize,
+        link: Link,
+    },
+    Disconnected {
+        old: usize,
+        link: Link,
+    },
+    /// The label of a connection's wire changed.
+    Relabelled {
+        old: usize,
+        new: usize,
+        link: Link,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// A connection's wire leaves or enters a box on a different side.
+    Rerouted {
+        old: usize,
+        new: usize,
+        link: Link,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::AddedBox { label, .. } => write!(f, "added box {:?}", label),
+            Change::RemovedBox { label, .. } => write!(f, "removed box {:?}", label),
+            Change::RenamedBox { from, to, .. } => write!(f, "renamed box {:?} to {:?}", from, to),
+            Change::Connected { link, .. } => write!(f, "connected {}", link),
+            Change::Disconnected { link, .. } => write!(f, "disconnected {}", link),
+            Change::Relabelled { link, from, to, .. } => write!(
+                f,
+                "relabelled {} from {:?} to {:?}",
+                link,
+                from.as_ref().map_or("", String::as_str),
+                to.as_ref().map_or("", String::as_str)
+            ),
+            Change::Rerouted { link, .. } => write!(f, "rerouted {}", link),
+        }
+    }
+}
+
+fn overlaps(a: &Block, b: &Block) -> bool {
+    a.top_left.line <= b.bottom_right.line
+        && b.top_left.line <= a.bottom_right.line
+        && a.top_left.column <= b.bottom_right.column
+        && b.top_left.column <= a.bottom_right.column
+}
+
+fn distance(a: &Block, b: &Block) -> usize {
+    let lines = (a.top_left.line as isize - b.top_left.line as isize).abs();
+    let columns = (a.top_left.column as isize - b.top_left.column as isize).abs();
+    (lines + columns) as usize
+}
+
+/// Pairs up the boxes of the old version with those of the new one, first by label and then by
+/// overlap, each time preferring the closest.
+fn match_blocks(old: &Diagram, new: &Diagram) -> Vec<Option<usize>> {
+    let mut matched = vec![None; old.blocks.len()];
+    let mut taken = vec![false; new.blocks.len()];
+    for &by_label in &[true, false] {
+        let pass = |a: &Block, b: &Block| {
+            if by_label {
+                a.label == b.label
+            } else {
+                overlaps(a, b)
+            }
+        };
+        let mut pairs: Vec<(usize, usize, usize)> = Vec::new();
+        for (i, a) in old.blocks.iter().enumerate() {
+            for (j, b) in new.blocks.iter().enumerate() {
+                if matched[i].is_none() && !taken[j] && pass(a, b) {
+                    pairs.push((distance(a, b), i, j));
+                }
+            }
+        }
+        pairs.sort();
+        for (_, i, j) in pairs {
+            if matched[i].is_none() && !taken[j] {
+                matched[i] = Some(j);
+                taken[j] = true;
+            }
+        }
+    }
+    matched
+}
+
+/// The directions that a connection's wire leaves its first box in and enters its second box
+/// in.
+fn route(diagram: &Diagram, connection: &Connection) -> Option<(Region, Region)> {
+    let wire = &diagram.wires[connection.wire];
+    let from = wire
+        .ends
+        .iter()
+        .find(|end| end.block == Some(connection.from));
+    let to = wire
+        .ends
+        .iter()
+        .find(|end| end.block == Some(connection.to) && Some(*end) != from);
+    let (from, to): (&End, &End) = match (from, to) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return None,
+    };
+    let direction = |a: LineColumn, b: LineColumn| {
+        if b.line < a.line {
+            Region::North
+        } else if b.line > a.line {
+            Region::South
+        } else if b.column > a.column {
+            Region::East
+        } else {
+            Region::West
+        }
+    };
+    let path = wire.path(from, to);
+    if path.is_empty() {
+        return None;
+    }
+    Some((
+        direction(from.position, path[0]),
+        direction(path[path.len() - 1], to.position),
+    ))
+}
+
+fn opposite(direction: Region) -> Region {
+    match direction {
+        Region::North => Region::South,
+        Region::South => Region::North,
+        Region::East => Region::West,
+        Region::West => Region::East,
+        Region::Center => Region::Center,
+    }
+}
+
+/// The changes from the old version of a drawing to the new one: the boxes that were removed,
+/// renamed and added, followed by the connections that were removed, changed and added.
+pub fn diff(old: &Graph, new: &Graph) -> Vec<Change> {
+    compare(&Diagram::from(old), &Diagram::from(new))
+}
+
+/// The changes between the diagrams of two versions of a drawing.
+pub fn compare(old: &Diagram, new: &Diagram) -> Vec<Change> {
+    let matched = match_blocks(old, new);
+    let mut changes = Vec::new();
+    for (i, block) in old.blocks.iter().enumerate() {
+        match matched[i] {
+            None => changes.push(Change::RemovedBox {
+                old: i,
+                label: block.label.clone(),
+            }),
+            Some(j) if new.blocks[j].label != block.label => changes.push(Change::RenamedBox {
+                old: i,
+                new: j,
+                from: block.label.clone(),
+                to: new.blocks[j].label.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (j, block) in new.blocks.iter().enumerate() {
+        if !matched.contains(&Some(j)) {
+            changes.push(Change::AddedBox {
+                new: j,
+                label: block.label.clone(),
+            });
+        }
+    }
+
+    // Connections are compared by the boxes of the new version that they connect.
+    let key = |from: usize, to: usize, directed: bool| {
+        if directed || from <= to {
+            (from, to, directed)
+        } else {
+            (to, from, directed)
+        }
+    };
+    let link = |diagram: &Diagram, connection: &Connection| Link {
+        from: diagram.blocks[connection.from].label.clone(),
+        to: diagram.blocks[connection.to].label.clone(),
+        directed: connection.directed,
+    };
+    let mut added = new.connections();
+    for connection in old.connections() {
+        let found = match (matched[connection.from], matched[connection.to]) {
+            (Some(from), Some(to)) => {
+                let wanted = key(from, to, connection.directed);
+                added
+                    .iter()
+                    .position(|other| key(other.from, other.to, other.directed) == wanted)
+            }
+            _ => None,
+        };
+        let other = match found {
+            Some(k) => added.remove(k),
+            None => {
+                changes.push(Change::Disconnected {
+                    old: connection.wire,
+                    link: link(old, &connection),
+                });
+                continue;
+            }
+        };
+        let (before, after) = (
+            &old.wires[connection.wire].label,
+            &new.wires[other.wire].label,
+        );
+        if before != after {
+            changes.push(Change::Relabelled {
+                old: connection.wire,
+                new: other.wire,
+                link: link(new, &other),
+                from: before.clone(),
+                to: after.clone(),
+            });
+        }
+        // Compare the routes in the same direction.
+        let mut sides = route(new, &other);
+        if matched[connection.from] != Some(other.from) {
+            sides = sides.map(|(leaves, enters)| (opposite(enters), opposite(leaves)));
+        }
+        if route(old, &connection) != sides {
+            changes.push(Change::Rerouted {
+                old: connection.wire,
+                new: other.wire,
+                link: link(new, &other),
+            });
+        }
+    }
+    for connection in added {
+        changes.push(Change::Connected {
+            new: connection.wire,
+            link: link(new, &connection),
+        });
+    }
+    changes
+}
+
+/// The cells of a box that belong to it rather than to a box inside it.
+fn block_cells(diagram: &Diagram, block: usize, cells: &mut HashSet<LineColumn>) {
+    let Block {
+        top_left,
+        bottom_right,
+        ..
+    } = diagram.blocks[block];
+    for line in top_left.line..=bottom_right.line {
+        for column in top_left.column..=bottom_right.column {
+            let position = LineColumn { line, column };
+            if diagram.block_at(position) == Some(block) {
+                cells.insert(position);
+            }
+        }
+    }
+}
+
+/// Shows the old and new versions of a drawing side by side, with the cells of the boxes and
+/// wires that changed in reverse video.
+pub fn side_by_side(old: &Graph, new: &Graph) -> String {
+    let (before, after) = (Diagram::from(old), Diagram::from(new));
+    let (old, new) = (old.to_string(), new.to_string());
+    let changes = compare(&before, &after);
+    let (mut removed, mut added) = (HashSet::new(), HashSet::new());
+    for change in &changes {
+        match *change {
+            Change::AddedBox { new, .. } => block_cells(&after, new, &mut added),
+            Change::RemovedBox { old, .. } => block_cells(&before, old, &mut removed),
+            Change::RenamedBox { old, new, .. } => {
+                block_cells(&before, old, &mut removed);
+                block_cells(&after, new, &mut added);
+            }
+            Change::Connected { new, .. } => added.extend(&after.wires[new].cells),
+            Change::Disconnected { old, .. } => removed.extend(&before.wires[old].cells),
+            Change::Relabelled { old, new, .. } | Change::Rerouted { old, new, .. } => {
+                removed.extend(&before.wires[old].cells);
+                added.extend(&after.wires[new].cells);
+            }
+        }
+    }
+
+    let highlight = |out: &mut String, text: &str, line: usize, cells: &HashSet<LineColumn>| {
+        for (column, c) in text.chars().enumerate() {
+            if c != ' ' && cells.contains(&LineColumn { line, column }) {
+                write!(out, "\x1b[7m{}\x1b[0m", c).unwrap();
+            } else {
+                out.push(c);
+            }
+        }
+    };
+    let (old, new): (Vec<&str>, Vec<&str>) = (old.lines().collect(), new.lines().collect());
+    let width = old
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for line in 0..old.len().max(new.len()) {
+        let left = old.get(line).cloned().unwrap_or("");
+        let right = new.get(line).cloned().unwrap_or("");
+        highlight(&mut out, left, line + 1, &removed);
+        out.push_str(&" ".repeat(width - left.chars().count()));
+        out.push_str(" │ ");
+        highlight(&mut out, right, line + 1, &added);
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,699 @@
+//! Reading and writing Graphviz DOT files.
+//!
+//! Only the parts of the language that matter to a box drawing are kept: nodes and their
+//! labels, edges and their labels, the nesting of `cluster` subgraphs and the `rankdir` of the
+//! graph. Other attributes are parsed and ignored.
+//!
+//! ```
+//! use dottools::{dot, logical::Diagram, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! assert_eq!(
+//!     dot::render(&Diagram::from(&graph)),
+//!     "digraph {\n  node [shape=box];\n  n0 [label=\"A\"];\n  n1 [label=\"B\"];\n  n0 -> n1;\n}\n"
+//! );
+//! ```
+
+use crate::layout::{Direction, Layout};
+use crate::logical::Diagram;
+use crate::xml::plain;
+use crate::{Error, LineColumn, Weight};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+const INDENT: &str = "  ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Node {
+    pub id: String,
+    pub label: String,
+    /// Index of the innermost cluster that the node was declared in.
+    pub cluster: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edge {
+    /// Index of the tail node.
+    pub from: usize,
+    /// Index of the head node.
+    pub to: usize,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cluster {
+    pub id: String,
+    pub label: Option<String>,
+    /// Index of the enclosing cluster.
+    pub parent: Option<usize>,
+}
+
+/// A graph read from a DOT file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Graph {
+    pub strict: bool,
+    pub directed: bool,
+    pub id: Option<String>,
+    /// The way that edges run, from the graph's `rankdir`.
+    pub direction: Direction,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub clusters: Vec<Cluster>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Id(String),
+    Quoted(String),
+    Html(String),
+    Punct(&'static str),
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    location: LineColumn,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Lexer {
+            chars: input.chars().peekable(),
+            location: LineColumn { line: 1, column: 0 },
+        }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, Error> {
+        Err(Error::Syntax(self.location, message.to_string()))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.location.line += 1;
+            self.location.column = 0;
+        } else {
+            self.location.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.bump() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), Error> {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('#') if self.location.column == 0 => self.skip_line(),
+                Some('/') => {
+                    let mut ahead = self.chars.clone();
+                    ahead.next();
+                    match ahead.next() {
+                        Some('/') => self.skip_line(),
+                        Some('*') => {
+                            self.bump();
+                            self.bump();
+                            let mut star = false;
+                            loop {
+                                match self.bump() {
+                                    Some('/') if star => break,
+                                    Some(c) => star = c == '*',
+                                    None => return self.error("unterminated comment"),
+                                }
+                            }
+                        }
+                        _ => return Ok(()),
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Returns the next token and the location that it starts at.
+    fn next_token(&mut self) -> Result<Option<(LineColumn, Token)>, Error> {
+        self.skip_whitespace_and_comments()?;
+        let start = self.location;
+        let c = match self.chars.peek() {
+            Some(&c) => c,
+            None => return Ok(None),
+        };
+        let token = match c {
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' | ':' | '+' => {
+                self.bump();
+                Token::Punct(match c {
+                    '{' => "{",
+                    '}' => "}",
+                    '[' => "[",
+                    ']' => "]",
+                    ';' => ";",
+                    ',' => ",",
+                    '=' => "=",
+                    ':' => ":",
+                    _ => "+",
+                })
+            }
+            '-' => {
+                self.bump();
+                match self.chars.peek() {
+                    Some('>') => {
+                        self.bump();
+                        Token::Punct("->")
+                    }
+                    Some('-') => {
+                        self.bump();
+                        Token::Punct("--")
+                    }
+                    _ => Token::Id(format!("-{}", self.numeral())),
+                }
+            }
+            '"' => {
+                self.bump();
+                let mut s = String::new();
+                loop {
+                    match self.bump() {
+                        Some('"') => break,
+                        Some('\\') => match self.bump() {
+                            Some('"') => s.push('"'),
+                            Some('\n') => {}
+                            Some(c) => {
+                                s.push('\\');
+                                s.push(c);
+                            }
+                            None => return self.error("unterminated string"),
+                        },
+                        Some(c) => s.push(c),
+                        None => return self.error("unterminated string"),
+                    }
+                }
+                Token::Quoted(s)
+            }
+            '<' => {
+                self.bump();
+                let mut s = String::new();
+                let mut depth = 1;
+                loop {
+                    match self.bump() {
+                        Some('<') => {
+                            depth += 1;
+                            s.push('<');
+                        }
+                        Some('>') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                            s.push('>');
+                        }
+                        Some(c) => s.push(c),
+                        None => return self.error("unterminated HTML string"),
+                    }
+                }
+                Token::Html(s)
+            }
+            c if c == '.' || c.is_ascii_digit() => Token::Id(self.numeral()),
+            c if c == '_' || c.is_alphabetic() => {
+                let mut s = String::new();
+                while let Some(&c) = self.chars.peek() {
+                    if c == '_' || c.is_alphanumeric() {
+                        s.push(c);
+                        self.bump();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Id(s)
+            }
+            _ => return self.error(&format!("unexpected character {:?}", c)),
+        };
+        Ok(Some((start, token)))
+    }
+
+    fn numeral(&mut self) -> String {
+        let mut s = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c == '.' || c.is_ascii_digit() {
+                s.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        s
+    }
+}
+
+struct Parser {
+    tokens: Vec<(LineColumn, Token)>,
+    position: usize,
+    end: LineColumn,
+    graph: Graph,
+    ids: HashMap<String, usize>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens
+            .get(self.position + offset)
+            .map(|(_, token)| token)
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, Error> {
+        let location = self
+            .tokens
+            .get(self.position)
+            .map(|(location, _)| *location)
+            .unwrap_or(self.end);
+        Err(Error::Syntax(location, message.to_string()))
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        match self.peek() {
+            Some(Token::Punct(p)) => *p == punct,
+            _ => false,
+        }
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if self.is_punct(punct) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), Error> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            self.error(&format!("expected `{}`", punct))
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Id(id)) => id.eq_ignore_ascii_case(keyword),
+            _ => false,
+        }
+    }
+
+    fn is_id(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Id(_)) | Some(Token::Quoted(_)) | Some(Token::Html(_))
+        )
+    }
+
+    fn id(&mut self) -> Result<String, Error> {
+        let id = match self.peek() {
+            Some(Token::Id(id)) => id.clone(),
+            Some(Token::Html(html)) => plain(html),
+            Some(Token::Quoted(s)) => {
+                let mut s = s.clone();
+                // Quoted strings may be concatenated with `+`.
+                while self.peek_at(1) == Some(&Token::Punct("+")) {
+                    match self.peek_at(2) {
+                        Some(Token::Quoted(t)) => {
+                            s.push_str(t);
+                            self.position += 2;
+                        }
+                        _ => break,
+                    }
+                }
+                s
+            }
+            _ => return self.error("expected an identifier"),
+        };
+        self.position += 1;
+        Ok(id)
+    }
+
+    fn node(&mut self, id: &str, cluster: Option<usize>) -> usize {
+        if let Some(&index) = self.ids.get(id) {
+            return index;
+        }
+        let index = self.graph.nodes.len();
+        self.graph.nodes.push(Node {
+            id: id.to_string(),
+            label: unescape(id, id),
+            cluster,
+        });
+        self.ids.insert(id.to_string(), index);
+        index
+    }
+
+    fn attributes(&mut self) -> Result<Vec<(String, String)>, Error> {
+        let mut attributes = Vec::new();
+        while self.eat("[") {
+            while !self.eat("]") {
+                let key = self.id()?;
+                self.expect("=")?;
+                let value = self.id()?;
+                attributes.push((key, value));
+                if !self.eat(",") {
+                    self.eat(";");
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn node_id(&mut self, cluster: Option<usize>) -> Result<usize, Error> {
+        let id = self.id()?;
+        // Ports and compass points are irrelevant to the drawing.
+        while self.eat(":") {
+            self.id()?;
+        }
+        Ok(self.node(&id, cluster))
+    }
+
+    /// Parses a node id or a subgraph, returning the nodes that it stands for in an edge.
+    fn operand(&mut self, cluster: Option<usize>) -> Result<Vec<usize>, Error> {
+        if self.is_keyword("subgraph") || self.is_punct("{") {
+            self.subgraph(cluster)
+        } else {
+            Ok(vec![self.node_id(cluster)?])
+        }
+    }
+
+    fn subgraph(&mut self, cluster: Option<usize>) -> Result<Vec<usize>, Error> {
+        let mut id = None;
+        if self.is_keyword("subgraph") {
+            self.position += 1;
+            if self.is_id() {
+                id = Some(self.id()?);
+            }
+        }
+        let cluster = match id {
+            Some(ref id) if id.starts_with("cluster") => {
+                self.graph.clusters.push(Cluster {
+                    id: id.clone(),
+                    label: None,
+                    parent: cluster,
+                });
+                Some(self.graph.clusters.len() - 1)
+            }
+            _ => cluster,
+        };
+        let mut members = Vec::new();
+        self.expect("{")?;
+        self.statements(cluster, &mut members)?;
+        self.expect("}")?;
+        members.sort();
+        members.dedup();
+        Ok(members)
+    }
+
+    /// Keeps the label of a cluster, and the `rankdir` of the graph itself.
+    fn attribute(&mut self, cluster: Option<usize>, key: &str, value: String) {
+        match (key, cluster) {
+            ("label", Some(cluster)) => {
+                self.graph.clusters[cluster].label = Some(unescape(&value, ""));
+            }
+            ("rankdir", None) => {
+                self.graph.direction = match value.to_ascii_uppercase().as_str() {
+                    "BT" => Direction::Up,
+                    "LR" => Direction::Right,
+                    "RL" => Direction::Left,
+                    _ => Direction::Down,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Takes the edge operator that fits the kind of graph, and fails on the other one.
+    fn edge_op(&mut self) -> Result<bool, Error> {
+        let (op, other) = if self.graph.directed {
+            ("->", "--")
+        } else {
+            ("--", "->")
+        };
+        if self.is_punct(other) {
+            return self.error(&format!(
+                "`{}` in a {}, which only has `{}`",
+                other,
+                if self.graph.directed {
+                    "digraph"
+                } else {
+                    "graph"
+                },
+                op
+            ));
+        }
+        Ok(self.eat(op))
+    }
+
+    fn statements(
+        &mut self,
+        cluster: Option<usize>,
+        members: &mut Vec<usize>,
+    ) -> Result<(), Error> {
+        while !self.is_punct("}") && self.peek().is_some() {
+            if self.is_keyword("graph") || self.is_keyword("node") || self.is_keyword("edge") {
+                let graph = self.is_keyword("graph");
+                self.position += 1;
+                for (key, value) in self.attributes()? {
+                    if graph {
+                        self.attribute(cluster, &key, value);
+                    }
+                }
+            } else if self.is_id() && self.peek_at(1) == Some(&Token::Punct("=")) {
+                let key = self.id()?;
+                self.expect("=")?;
+                let value = self.id()?;
+                self.attribute(cluster, &key, value);
+            } else {
+                let mut tails = self.operand(cluster)?;
+                members.extend(&tails);
+                if self.is_punct("->") || self.is_punct("--") {
+                    let mut pairs = Vec::new();
+                    while self.edge_op()? {
+                        let heads = self.operand(cluster)?;
+                        members.extend(&heads);
+                        for &from in &tails {
+                            for &to in &heads {
+                                pairs.push((from, to));
+                            }
+                        }
+                        tails = heads;
+                    }
+                    let label = self
+                        .attributes()?
+                        .into_iter()
+                        .rev()
+                        .find(|(key, _)| key == "label")
+                        .map(|(_, value)| unescape(&value, ""));
+                    for (from, to) in pairs {
+                        self.graph.edges.push(Edge {
+                            from,
+                            to,
+                            label: label.clone(),
+                        });
+                    }
+                } else {
+                    for (key, value) in self.attributes()? {
+                        if key == "label" {
+                            for &node in &tails {
+                                let id = self.graph.nodes[node].id.clone();
+                                self.graph.nodes[node].label = unescape(&value, &id);
+                            }
+                        }
+                    }
+                }
+            }
+            self.eat(";");
+        }
+        Ok(())
+    }
+}
+
+/// Interprets the escape sequences of a label, where `\N` stands for the node's id and each of
+/// `\n`, `\l` and `\r` end a line.
+fn unescape(label: &str, id: &str) -> String {
+    let mut s = String::with_capacity(label.len());
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            s.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('l') | Some('r') => s.push('\n'),
+            Some('N') | Some('G') => s.push_str(id),
+            Some(c) => s.push(c),
+            None => s.push('\\'),
+        }
+    }
+    s.trim_end_matches('\n').to_string()
+}
+
+impl FromStr for Graph {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut lexer = Lexer::new(input);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token()? {
+            tokens.push(token);
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: lexer.location,
+            graph: Graph {
+                strict: false,
+                directed: false,
+                id: None,
+                direction: Direction::Down,
+                nodes: Vec::new(),
+                edges: Vec::new(),
+                clusters: Vec::new(),
+            },
+            ids: HashMap::new(),
+        };
+        if parser.is_keyword("strict") {
+            parser.graph.strict = true;
+            parser.position += 1;
+        }
+        if parser.is_keyword("digraph") {
+            parser.graph.directed = true;
+        } else if !parser.is_keyword("graph") {
+            return parser.error("expected `graph` or `digraph`");
+        }
+        parser.position += 1;
+        if parser.is_id() {
+            parser.graph.id = Some(parser.id()?);
+        }
+        parser.expect("{")?;
+        parser.statements(None, &mut Vec::new())?;
+        parser.expect("}")?;
+        if parser.peek().is_some() {
+            return parser.error("unexpected input after the graph");
+        }
+        Ok(parser.graph)
+    }
+}
+
+impl Graph {
+    /// Lays out the DOT graph as labelled boxes connected by orthogonal wires, with clusters
+    /// drawn as dashed boxes around their members.
+    pub fn to_graph(&self) -> Result<crate::Graph, Error> {
+        let mut layout = Layout::new(self.directed);
+        layout.set_direction(self.direction);
+        for cluster in &self.clusters {
+            layout.add_cluster(cluster.label.clone(), cluster.parent)?;
+        }
+        for node in &self.nodes {
+            layout.add_node(&node.label, node.cluster);
+        }
+        for edge in &self.edges {
+            layout.add_edge(edge.from, edge.to, edge.label.clone())?;
+        }
+        layout.render()
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn has_children(diagram: &Diagram, block: usize) -> bool {
+    diagram
+        .blocks
+        .iter()
+        .any(|child| child.parent == Some(block))
+}
+
+fn block(diagram: &Diagram, out: &mut String, parent: Option<usize>, depth: usize) {
+    for (i, block) in diagram.blocks.iter().enumerate() {
+        if block.parent != parent {
+            continue;
+        }
+        let indent = INDENT.repeat(depth);
+        if has_children(diagram, i) {
+            writeln!(out, "{}subgraph cluster_n{} {{", indent, i).unwrap();
+            writeln!(out, "{}{}label={};", indent, INDENT, quote(&block.label)).unwrap();
+            // Edges cannot end on a cluster, so they end on a hidden node inside it instead.
+            if diagram
+                .connections()
+                .iter()
+                .any(|connection| connection.from == i || connection.to == i)
+            {
+                writeln!(
+                    out,
+                    "{}{}n{} [shape=point, style=invis];",
+                    indent, INDENT, i
+                )
+                .unwrap();
+            }
+            self::block(diagram, out, Some(i), depth + 1);
+            writeln!(out, "{}}}", indent).unwrap();
+        } else {
+            writeln!(out, "{}n{} [label={}];", indent, i, quote(&block.label)).unwrap();
+        }
+    }
+}
+
+/// Writes the boxes and wires of the diagram as a digraph. Boxes that contain other boxes become
+/// clusters, and undirected wires are drawn without arrowheads.
+pub fn render(diagram: &Diagram) -> String {
+    let mut out = String::from("digraph {\n");
+    let connections = diagram.connections();
+    let compound = connections
+        .iter()
+        .any(|c| has_children(diagram, c.from) || has_children(diagram, c.to));
+    if compound {
+        writeln!(out, "{}compound=true;", INDENT).unwrap();
+    }
+    writeln!(out, "{}node [shape=box];", INDENT).unwrap();
+    block(diagram, &mut out, None, 1);
+    for connection in connections {
+        let wire = &diagram.wires[connection.wire];
+        let mut attributes = Vec::new();
+        if let Some(label) = &wire.label {
+            attributes.push(format!("label={}", quote(label)));
+        }
+        if !connection.directed {
+            attributes.push("dir=none".to_string());
+        }
+        if wire.brush.is_dashed() {
+            attributes.push("style=dashed".to_string());
+        }
+        match wire.brush.weight() {
+            Weight::Light => {}
+            Weight::Heavy => attributes.push("penwidth=2".to_string()),
+            Weight::Double => attributes.push("color=\"black:invis:black\"".to_string()),
+        }
+        if has_children(diagram, connection.from) {
+            attributes.push(format!("ltail=cluster_n{}", connection.from));
+        }
+        if has_children(diagram, connection.to) {
+            attributes.push(format!("lhead=cluster_n{}", connection.to));
+        }
+        write!(out, "{}n{} -> n{}", INDENT, connection.from, connection.to).unwrap();
+        if !attributes.is_empty() {
+            write!(out, " [{}]", attributes.join(", ")).unwrap();
+        }
+        out.push_str(";\n");
+    }
+    out.push_str("}\n");
+    out
+}
@@ -0,0 +1,641 @@
+//! draw.io (mxGraph) files.
+//!
+//! Each character cell is `CELL_WIDTH` by `CELL_HEIGHT` units of the draw.io canvas. Boxes are
+//! written as vertices whose corners are at the centres of the corner cells, and wires as
+//! orthogonal edges with a waypoint for every turn. Files that are read back are snapped to the
+//! character grid and their connectors routed anew; only rectangles and connectors that are
+//! attached at both ends are kept, and compressed diagrams are not supported.
+//!
+//! ```
+//! use dottools::{drawio, logical::Diagram, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! let xml = drawio::render(&Diagram::from(&graph));
+//! let model: drawio::Model = xml.parse().unwrap();
+//! assert_eq!(model.to_graph().unwrap().to_string(), graph.to_string());
+//! ```
+
+use crate::canvas::Canvas;
+use crate::logical::{Diagram, End};
+use crate::xml::{escape, plain, unescape};
+use crate::{Error, Graph, LineColumn};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// The width of a character cell on the draw.io canvas.
+pub const CELL_WIDTH: f64 = 10.0;
+/// The height of a character cell on the draw.io canvas.
+pub const CELL_HEIGHT: f64 = 20.0;
+
+const BOX: &str = "rounded=0;whiteSpace=wrap;html=1;";
+const CONTAINER: &str =
+    "rounded=0;whiteSpace=wrap;html=1;verticalAlign=top;align=left;spacingLeft=10;";
+const WIRE: &str = "edgeStyle=orthogonalEdgeStyle;rounded=0;html=1;";
+
+/// The centre of a cell.
+pub(crate) fn centre(position: LineColumn) -> (f64, f64) {
+    (
+        (position.column as f64 + 0.5) * CELL_WIDTH,
+        (position.line as f64 - 0.5) * CELL_HEIGHT,
+    )
+}
+
+/// Where an end attaches to its box, as a fraction of the box's width and height.
+fn constraint(diagram: &Diagram, end: &End) -> Option<(f64, f64)> {
+    let block = &diagram.blocks[end.block?];
+    let (x0, y0) = centre(block.top_left);
+    let (x1, y1) = centre(block.bottom_right);
+    let (x, y) = centre(end.position);
+    Some(((x - x0) / (x1 - x0), (y - y0) / (y1 - y0)))
+}
+
+/// Writes the boxes and wires of the diagram as a draw.io file.
+pub fn render(diagram: &Diagram) -> String {
+    let mut out = String::new();
+    out.push_str("<mxfile host=\"dottools\">\n");
+    out.push_str("  <diagram id=\"dottools\" name=\"Page-1\">\n");
+    out.push_str("    <mxGraphModel grid=\"1\" gridSize=\"10\">\n");
+    out.push_str("      <root>\n");
+    out.push_str("        <mxCell id=\"0\"/>\n");
+    out.push_str("        <mxCell id=\"1\" parent=\"0\"/>\n");
+
+    for (i, block) in diagram.blocks.iter().enumerate() {
+        let (mut x, mut y) = centre(block.top_left);
+        let (right, bottom) = centre(block.bottom_right);
+        let (width, height) = (right - x, bottom - y);
+        let parent = match block.parent {
+            Some(parent) => {
+                let (px, py) = centre(diagram.blocks[parent].top_left);
+                x -= px;
+                y -= py;
+                format!("n{}", parent)
+            }
+            None => "1".to_string(),
+        };
+        let container = diagram.blocks.iter().any(|child| child.parent == Some(i));
+        let mut style = if container { CONTAINER } else { BOX }.to_string();
+        if block.brush.is_dashed() {
+            style.push_str("dashed=1;");
+        }
+        writeln!(
+            out,
+            r#"        <mxCell id="n{}" value="{}" style="{}" vertex="1" parent="{}">"#,
+            i,
+            escape(&block.label),
+            style,
+            parent
+        )
+        .unwrap();
+        writeln!(
+            out,
+            r#"          <mxGeometry x="{}" y="{}" width="{}" height="{}" as="geometry"/>"#,
+            x, y, width, height
+        )
+        .unwrap();
+        out.push_str("        </mxCell>\n");
+    }
+
+    let mut edge = 0;
+    for wire in &diagram.wires {
+        // Ends without a box are kept, as connectors may end anywhere.
+        for (from, to, directed) in wire.pairs(|_| true) {
+            let mut style = WIRE.to_string();
+            style.push_str(if directed {
+                "endArrow=classic;"
+            } else {
+                "endArrow=none;"
+            });
+            if wire.brush.is_dashed() {
+                style.push_str("dashed=1;");
+            }
+            if let Some((x, y)) = constraint(diagram, from) {
+                write!(style, "exitX={};exitY={};", x, y).unwrap();
+            }
+            if let Some((x, y)) = constraint(diagram, to) {
+                write!(style, "entryX={};entryY={};", x, y).unwrap();
+            }
+            write!(
+                out,
+                r#"        <mxCell id="w{}" value="{}" style="{}" edge="1" parent="1""#,
+                edge,
+                escape(wire.label.as_ref().map_or("", String::as_str)),
+                style
+            )
+            .unwrap();
+            if let Some(block) = from.block {
+                write!(out, r#" source="n{}""#, block).unwrap();
+            }
+            if let Some(block) = to.block {
+                write!(out, r#" target="n{}""#, block).unwrap();
+            }
+            out.push_str(">\n");
+            out.push_str("          <mxGeometry relative=\"1\" as=\"geometry\">\n");
+            for &(end, name) in &[(from, "sourcePoint"), (to, "targetPoint")] {
+                if end.block.is_none() {
+                    let (x, y) = centre(end.position);
+                    writeln!(
+                        out,
+                        r#"            <mxPoint x="{}" y="{}" as="{}"/>"#,
+                        x, y, name
+                    )
+                    .unwrap();
+                }
+            }
+            let path = wire.path(from, to);
+            if path.len() > 2 {
+                out.push_str("            <Array as=\"points\">\n");
+                for &cell in &path[1..path.len() - 1] {
+                    let (x, y) = centre(cell);
+                    writeln!(out, r#"              <mxPoint x="{}" y="{}"/>"#, x, y).unwrap();
+                }
+                out.push_str("            </Array>\n");
+            }
+            out.push_str("          </mxGeometry>\n");
+            out.push_str("        </mxCell>\n");
+            edge += 1;
+        }
+    }
+
+    out.push_str("      </root>\n");
+    out.push_str("    </mxGraphModel>\n");
+    out.push_str("  </diagram>\n");
+    out.push_str("</mxfile>\n");
+    out
+}
+
+/// A rectangle, or a piece of text when its style says so.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vertex {
+    pub id: String,
+    pub label: String,
+    /// The position of the top left corner, relative to the parent vertex if there is one.
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Index of the vertex that this one is inside of.
+    pub parent: Option<usize>,
+    pub text: bool,
+    pub dashed: bool,
+}
+
+/// A connector between two vertices.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edge {
+    pub id: String,
+    pub label: Option<String>,
+    /// Index of the vertex that the connector leaves from.
+    pub source: Option<usize>,
+    /// Index of the vertex that the connector leads to.
+    pub target: Option<usize>,
+    /// Set unless the connector's style has no arrowhead at its end.
+    pub arrow: bool,
+}
+
+/// The vertices and edges of the first diagram of a draw.io file.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Model {
+    pub vertices: Vec<Vertex>,
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Event {
+    Start(String, HashMap<String, String>, bool),
+    End(String),
+    /// Text, along with where it starts.
+    Text(String, LineColumn),
+}
+
+/// Just enough of an XML reader for draw.io files.
+struct Reader {
+    chars: Vec<char>,
+    position: usize,
+    location: LineColumn,
+}
+
+impl Reader {
+    fn error<T>(&self, message: &str) -> Result<T, Error> {
+        Err(Error::Syntax(self.location, message.to_string()))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.position + i) == Some(&c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        if c == '\n' {
+            self.location.line += 1;
+            self.location.column = 0;
+        } else {
+            self.location.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), Error> {
+        while !self.starts_with(end) {
+            if self.bump().is_none() {
+                return self.error(&format!("expected `{}`", end));
+            }
+        }
+        for _ in end.chars() {
+            self.bump();
+        }
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn name(&mut self) -> Result<String, Error> {
+        let mut name = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || "_-:.".contains(c) {
+                name.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        if name.is_empty() {
+            return self.error("expected a name");
+        }
+        Ok(name)
+    }
+
+    fn next(&mut self) -> Result<Option<Event>, Error> {
+        loop {
+            if self.peek().is_none() {
+                return Ok(None);
+            }
+            if self.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.starts_with("<![CDATA[") {
+                self.skip_past("]]>")?;
+            } else if self.starts_with("<!") {
+                self.skip_past(">")?;
+            } else if self.starts_with("</") {
+                self.bump();
+                self.bump();
+                let name = self.name()?;
+                self.skip_whitespace();
+                if self.bump() != Some('>') {
+                    return self.error("expected `>`");
+                }
+                return Ok(Some(Event::End(name)));
+            } else if self.peek() == Some('<') {
+                self.bump();
+                let name = self.name()?;
+                let mut attributes = HashMap::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some('/') => {
+                            self.bump();
+                            if self.bump() != Some('>') {
+                                return self.error("expected `>`");
+                            }
+                            return Ok(Some(Event::Start(name, attributes, true)));
+                        }
+                        Some('>') => {
+                            self.bump();
+                            return Ok(Some(Event::Start(name, attributes, false)));
+                        }
+                        _ => {}
+                    }
+                    let key = self.name()?;
+                    self.skip_whitespace();
+                    if self.bump() != Some('=') {
+                        return self.error("expected `=`");
+                    }
+                    self.skip_whitespace();
+                    let quote = match self.bump() {
+                        Some(quote) if quote == '"' || quote == '\'' => quote,
+                        _ => return self.error("expected a quoted value"),
+                    };
+                    let mut value = String::new();
+                    loop {
+                        match self.bump() {
+                            Some(c) if c == quote => break,
+                            Some(c) => value.push(c),
+                            None => return self.error("unterminated value"),
+                        }
+                    }
+                    attributes.insert(key, unescape(&value));
+                }
+            } else {
+                let location = self.location;
+                let mut text = String::new();
+                while self.peek().is_some_and(|c| c != '<') {
+                    text.extend(self.bump());
+                }
+                return Ok(Some(Event::Text(unescape(&text), location)));
+            }
+        }
+    }
+}
+
+fn number(attributes: &HashMap<String, String>, key: &str) -> f64 {
+    attributes
+        .get(key)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0.0)
+}
+
+impl FromStr for Model {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut reader = Reader {
+            chars: input.chars().collect(),
+            position: 0,
+            location: LineColumn { line: 1, column: 0 },
+        };
+        let mut model = Model::default();
+        let mut parents: Vec<Option<String>> = Vec::new();
+        // Where each vertex starts in the input.
+        let mut locations: Vec<LineColumn> = Vec::new();
+        let mut sources: Vec<(Option<String>, Option<String>)> = Vec::new();
+        // The label and id of an `object` that wraps the next cell.
+        let mut object: Option<(Option<String>, Option<String>)> = None;
+        // The cell whose geometry is being read, as the index of a vertex or an edge.
+        let mut cell: Option<Result<usize, usize>> = None;
+        let mut elements: Vec<String> = Vec::new();
+        let mut diagrams = 0;
+        loop {
+            let location = reader.location;
+            let event = match reader.next()? {
+                Some(event) => event,
+                None => break,
+            };
+            match event {
+                Event::Start(name, attributes, empty) => {
+                    match name.as_str() {
+                        "diagram" => {
+                            diagrams += 1;
+                        }
+                        "object" | "UserObject" => {
+                            object = Some((
+                                attributes.get("label").cloned(),
+                                attributes.get("id").cloned(),
+                            ));
+                        }
+                        "mxCell" if diagrams <= 1 => {
+                            let (label, id) = object.take().unwrap_or((None, None));
+                            let id = id.or_else(|| attributes.get("id").cloned());
+                            let label = label.or_else(|| attributes.get("value").cloned());
+                            let label = plain(&label.unwrap_or_default());
+                            let style = attributes.get("style").cloned().unwrap_or_default();
+                            let parent = attributes.get("parent").cloned();
+                            if attributes.get("vertex").map(String::as_str) == Some("1") {
+                                model.vertices.push(Vertex {
+                                    id: id.unwrap_or_default(),
+                                    label,
+                                    x: 0.0,
+                                    y: 0.0,
+                                    width: 0.0,
+                                    height: 0.0,
+                                    parent: None,
+                                    text: style.split(';').any(|s| s == "text"),
+                                    dashed: style.contains("dashed=1"),
+                                });
+                                parents.push(parent);
+                                locations.push(location);
+                                cell = Some(Ok(model.vertices.len() - 1));
+                            } else if attributes.get("edge").map(String::as_str) == Some("1") {
+                                model.edges.push(Edge {
+                                    id: id.unwrap_or_default(),
+                                    label: Some(label).filter(|label| !label.is_empty()),
+                                    source: None,
+                                    target: None,
+                                    arrow: !style.contains("endArrow=none"),
+                                });
+                                sources.push((
+                                    attributes.get("source").cloned(),
+                                    attributes.get("target").cloned(),
+                                ));
+                                cell = Some(Err(model.edges.len() - 1));
+                            } else {
+                                cell = None;
+                            }
+                        }
+                        "mxGeometry" => {
+                            if let Some(Ok(v)) = cell {
+                                let vertex = &mut model.vertices[v];
+                                vertex.x = number(&attributes, "x");
+                                vertex.y = number(&attributes, "y");
+                                vertex.width = number(&attributes, "width");
+                                vertex.height = number(&attributes, "height");
+                            }
+                        }
+                        _ => {}
+                    }
+                    if !empty {
+                        elements.push(name);
+                    } else if name == "mxCell" {
+                        cell = None;
+                    }
+                }
+                Event::End(name) => {
+                    if elements.pop().as_ref() != Some(&name) {
+                        return reader.error(&format!("unexpected `</{}>`", name));
+                    }
+                    if name == "mxCell" {
+                        cell = None;
+                    }
+                }
+                Event::Text(text, location) => {
+                    if elements.last().map(String::as_str) == Some("diagram")
+                        && !text.trim().is_empty()
+                    {
+                        return Err(Error::Syntax(
+                            location,
+                            "compressed diagrams are not supported".to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+        if !elements.is_empty() {
+            return reader.error(&format!("expected `</{}>`", elements[elements.len() - 1]));
+        }
+
+        let find = |id: &Option<String>| -> Option<usize> {
+            let id = id.as_ref()?;
+            model.vertices.iter().position(|vertex| &vertex.id == id)
+        };
+        let parents: Vec<Option<usize>> = parents.iter().map(find).collect();
+        let sources: Vec<(Option<usize>, Option<usize>)> = sources
+            .iter()
+            .map(|(source, target)| (find(source), find(target)))
+            .collect();
+        for (vertex, &parent) in model.vertices.iter_mut().zip(&parents) {
+            vertex.parent = parent;
+        }
+        for (v, &location) in locations.iter().enumerate() {
+            // A vertex that is inside of itself has no position.
+            let mut ancestor = parents[v];
+            for _ in 0..parents.len() {
+                match ancestor {
+                    Some(a) if a == v => {
+                        return Err(Error::Syntax(
+                            location,
+                            format!("`{}` is inside of itself", model.vertices[v].id),
+                        ));
+                    }
+                    Some(a) => ancestor = parents[a],
+                    None => break,
+                }
+            }
+        }
+        for (edge, (source, target)) in model.edges.iter_mut().zip(sources) {
+            edge.source = source;
+            edge.target = target;
+        }
+        Ok(model)
+    }
+}
+
+impl Model {
+    /// The position of a vertex's top left corner on the draw.io canvas.
+    fn origin(&self, vertex: usize) -> (f64, f64) {
+        let Vertex { x, y, parent, .. } = self.vertices[vertex];
+        match parent {
+            Some(parent) => {
+                let (px, py) = self.origin(parent);
+                (px + x, py + y)
+            }
+            None => (x, y),
+        }
+    }
+
+    /// Snaps the vertices to the character grid, draws them as boxes and routes the connectors
+    /// between them as wires. A connector that no wire can be routed for is left out, and the
+    /// rest are still drawn.
+    pub fn to_graph(&self) -> Result<Graph, Error> {
+        let origins: Vec<(f64, f64)> = (0..self.vertices.len()).map(|v| self.origin(v)).collect();
+        // The drawing starts at the top left of the leftmost and topmost vertices.
+        let min_x = origins.iter().map(|o| o.0).fold(f64::INFINITY, f64::min);
+        let min_y = origins.iter().map(|o| o.1).fold(f64::INFINITY, f64::min);
+
+        let mut boxes = Vec::new();
+        for (vertex, &(x, y)) in self.vertices.iter().zip(&origins) {
+            let (x, y) = (x - min_x, y - min_y);
+            let left = (x / CELL_WIDTH).floor() as usize;
+            let top = (y / CELL_HEIGHT).floor() as usize;
+            let lines: Vec<&str> = vertex.label.lines().collect();
+            let width = lines
+                .iter()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0);
+            let right = ((x + vertex.width) / CELL_WIDTH).floor() as usize;
+            let bottom = ((y + vertex.height) / CELL_HEIGHT).floor() as usize;
+            let right = right.max(left + width + 3);
+            let bottom = bottom.max(top + lines.len() + 1);
+            boxes.push((top, left, bottom, right));
+        }
+
+        let mut canvas = Canvas::new();
+        for (v, vertex) in self.vertices.iter().enumerate() {
+            let (top, left, bottom, right) = boxes[v];
+            let lines: Vec<&str> = vertex.label.lines().collect();
+            if vertex.text {
+                for (k, line) in lines.iter().enumerate() {
+                    canvas.text(top + k, left, line);
+                }
+                continue;
+            }
+            let brush = if vertex.dashed { '┄' } else { '─' };
+            canvas.rectangle((top, left), (bottom, right), brush);
+            if self.vertices.iter().any(|child| child.parent == Some(v)) {
+                for (k, line) in lines.iter().enumerate() {
+                    canvas.text(top + 1 + k, left + 2, line);
+                }
+            } else {
+                let inner = (bottom - top - 1, right - left - 1);
+                let first = top + 1 + (inner.0 - lines.len()) / 2;
+                for (k, line) in lines.iter().enumerate() {
+                    let w = line.chars().count();
+                    canvas.text(first + k, left + 1 + (inner.1 - w) / 2, line);
+                }
+            }
+        }
+
+        let mut graph = canvas.to_graph()?;
+        for edge in &self.edges {
+            let (source, target) = match (edge.source, edge.target) {
+                (Some(source), Some(target)) if !self.vertices[source].text => (source, target),
+                _ => continue,
+            };
+            let inside = |v: usize| {
+                let (top, left, _, _) = boxes[v];
+                LineColumn {
+                    line: top + 2,
+                    column: left + 1,
+                }
+            };
+            let before = graph.to_string();
+            match graph.route(inside(source), inside(target), edge.arrow) {
+                Err(Error::NoRoute) => continue,
+                result => result?,
+            }
+            if let Some(ref label) = edge.label {
+                graph = labelled(&before, &graph, label)?;
+            }
+        }
+        Ok(graph)
+    }
+}
+
+/// Writes a label beside the wire that was drawn since `before`.
+fn labelled(before: &str, graph: &Graph, label: &str) -> Result<Graph, Error> {
+    let after = graph.to_string();
+    let before: Vec<Vec<char>> = before.lines().map(|line| line.chars().collect()).collect();
+    let mut drawn = Vec::new();
+    for (l, line) in after.lines().enumerate() {
+        for (c, ch) in line.chars().enumerate() {
+            let old = before.get(l).and_then(|line| line.get(c)).cloned();
+            if ch != ' ' && old != Some(ch) {
+                drawn.push((l, c));
+            }
+        }
+    }
+    let mut canvas = Canvas::from_text(&after);
+    let width = label.chars().count();
+    // Try the middle of the wire first.
+    let middle = drawn.len() / 2;
+    let mut order: Vec<usize> = (0..drawn.len()).collect();
+    order.sort_by_key(|&i| i.abs_diff(middle));
+    for i in order {
+        // Beside the wire where it runs up or down, or else above it.
+        let (line, column) = drawn[i];
+        if canvas.is_span_free(line, column + 1, width + 2) {
+            canvas.text(line, column + 2, label);
+            return canvas.to_graph();
+        }
+        if line > 0 && column > 0 && canvas.is_span_free(line - 1, column - 1, width + 2) {
+            canvas.text(line - 1, column, label);
+            return canvas.to_graph();
+        }
+    }
+    Graph::with_text(&after)
+}
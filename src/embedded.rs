@@ -0,0 +1,315 @@
+//! Finding the drawings in other documents: in the code blocks of Markdown, and in the comments
+//! of source files.
+//!
+//! Each line of a drawing is kept along with what comes before it on its line in the document,
+//! so that a position in the drawing can be found in the document, and the drawing can be
+//! written back in place. A drawing's graph has the positions of the document.
+//!
+//! ```
+//! use dottools::embedded::{drawings, Kind};
+//!
+//! let source = "fn main() {}\n\n// ┌───┐\n// │ A │\n// └───┘\n";
+//! let found = drawings(source, Kind::Source);
+//! assert_eq!(found.len(), 1);
+//! assert_eq!(found[0].line, 2);
+//! assert_eq!(found[0].text(), "┌───┐\n│ A │\n└───┘");
+//!
+//! let graph = found[0].graph().unwrap();
+//! let a = graph.nodes().find(|node| node.character() == 'A').unwrap();
+//! assert_eq!((a.source().line(), a.source().column()), (4, 5));
+//! ```
+
+use crate::classify::{confidence, THRESHOLD};
+use crate::{is_box_drawing, Error, Graph, LineColumn, Node};
+use petgraph::graphmap::UnGraphMap;
+use std::path::Path;
+use unicode_normalization::char::is_combining_mark;
+
+/// What kind of document a drawing is in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Kind {
+    /// Drawings are in code blocks: fenced ones whose info string is empty or `text`, and
+    /// indented ones.
+    Markdown,
+    /// Drawings are in runs of comment lines.
+    Source,
+    /// The whole document is a drawing.
+    Drawing,
+}
+
+impl Kind {
+    /// Guesses the kind of a file from its extension: `.md` and `.markdown` are Markdown, `.txt`
+    /// is a drawing, and anything else is source.
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("md") | Some("markdown") => Kind::Markdown,
+            Some("txt") => Kind::Drawing,
+            _ => Kind::Source,
+        }
+    }
+}
+
+/// A drawing in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Embedded<'a> {
+    /// The index of the drawing's first line in the document.
+    pub line: usize,
+    /// Each line of the drawing, as what comes before it on the line and the line of the drawing.
+    /// What comes after it, like the `*/` that closes a block comment, is the rest of the line in
+    /// the document.
+    pub lines: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Embedded<'a> {
+    /// The drawing on its own.
+    pub fn text(&self) -> String {
+        let lines: Vec<&str> = self.lines.iter().map(|&(_, line)| line).collect();
+        lines.join("\n")
+    }
+
+    /// The position in the document of a position in the drawing, counting characters.
+    pub fn source(&self, position: LineColumn) -> LineColumn {
+        let prefix = self.prefix(position.line).chars().count();
+        LineColumn::new(self.line + position.line, prefix + position.column)
+    }
+
+    /// The position in the document of a position in the drawing, counting cells.
+    pub fn visual(&self, position: LineColumn) -> LineColumn {
+        let prefix = self.prefix(position.line);
+        let prefix = prefix.chars().filter(|&c| !is_combining_mark(c)).count();
+        LineColumn::new(self.line + position.line, prefix + position.column)
+    }
+
+    /// What comes before the drawing on the given line of it, counted from one.
+    fn prefix(&self, line: usize) -> &'a str {
+        line.checked_sub(1)
+            .and_then(|line| self.lines.get(line))
+            .map_or("", |&(prefix, _)| prefix)
+    }
+
+    /// Parses the drawing like `Graph::with_text`, with the positions of its nodes in the
+    /// document.
+    pub fn graph(&self) -> Result<Graph, Error> {
+        let graph = Graph::with_text(&self.text())?;
+        let node = |node: Node| Node {
+            source: self.source(node.source),
+            visual: self.visual(node.visual),
+            ..node
+        };
+        let mut moved = UnGraphMap::with_capacity(graph.node_count(), graph.edge_count());
+        for n in graph.nodes() {
+            moved.add_node(node(n));
+        }
+        for (v, u, &edge) in graph.all_edges() {
+            moved.add_edge(node(v), node(u), edge);
+        }
+        Ok(Graph(moved))
+    }
+}
+
+/// The number of spaces that a line starts with.
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Returns true for a line with nothing but whitespace.
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// The indented code block that starts at the given line, if it is one, and the index of the line
+/// after it. A code block is indented by at least four spaces and follows a blank line, since it
+/// cannot interrupt a paragraph; the four spaces are not part of the drawing. A fence starts a
+/// fenced code block instead, however it is indented, as it is in a list.
+fn indented<'a>(lines: &[&'a str], start: usize) -> Option<(Embedded<'a>, usize)> {
+    let first = lines[start];
+    if indentation(first) < 4
+        || is_blank(first)
+        || (start > 0 && !is_blank(lines[start - 1]))
+        || ["```", "~~~"]
+            .iter()
+            .any(|fence| first.trim_start().starts_with(fence))
+    {
+        return None;
+    }
+    let mut end = start;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        if is_blank(line) {
+            continue;
+        }
+        if indentation(line) < 4 {
+            break;
+        }
+        end = i + 1;
+    }
+    let block = Embedded {
+        line: start,
+        lines: lines[start..end]
+            .iter()
+            .map(|line| line.split_at(indentation(line).min(4)))
+            .collect(),
+    };
+    Some((block, end))
+}
+
+/// The code blocks of a Markdown document which contain box drawing: fenced ones whose info
+/// string is empty or `text`, and indented ones. The fence's indentation is not part of the
+/// drawing.
+fn markdown(document: &str) -> Vec<Embedded<'_>> {
+    let lines: Vec<&str> = document.split('\n').collect();
+    let mut drawings = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if let Some((block, end)) = indented(&lines, i) {
+            if block
+                .lines
+                .iter()
+                .any(|(_, line)| line.contains(is_box_drawing))
+            {
+                drawings.push(block);
+            }
+            i = end;
+            continue;
+        }
+        let line = lines[i];
+        i += 1;
+        let indent = indentation(line);
+        let opening = &line[indent..];
+        let fence = match opening.chars().next() {
+            Some(c) if c == '`' || c == '~' => c,
+            _ => continue,
+        };
+        let length = opening.chars().take_while(|&c| c == fence).count();
+        if length < 3 {
+            continue;
+        }
+        let language = opening[length..].split_whitespace().next().unwrap_or("");
+        let end = lines[i..]
+            .iter()
+            .position(|line| {
+                let closing = line.trim();
+                closing.chars().take_while(|&c| c == fence).count() >= length
+                    && closing.trim_start_matches(fence).is_empty()
+            })
+            .map_or(lines.len(), |end| i + end);
+        let block = &lines[i..end];
+        if (language.is_empty() || language == "text")
+            && block.iter().any(|line| line.contains(is_box_drawing))
+        {
+            drawings.push(Embedded {
+                line: i,
+                lines: block
+                    .iter()
+                    .map(|line| line.split_at(indentation(line).min(indent)))
+                    .collect(),
+            });
+        }
+        i = end + 1;
+    }
+    drawings
+}
+
+/// The markers that start a line comment, or continue a block comment, longest first.
+const COMMENTS: &[&str] = &["/**", "/*!", "/*", "///", "//!", "//", "#", "--", "*"];
+
+/// Splits a line into its comment prefix and the rest, up to any `*/` that closes a block
+/// comment. A prefix may open a block comment, as in `/* `. The prefix takes in repeats of the marker's last character, as in `////` or `###`,
+/// and the space after them, so that no part of a marker is read as a stroke.
+fn comment(line: &str) -> Option<(&str, &str)> {
+    let indent = line.len() - line.trim_start().len();
+    let marker = COMMENTS
+        .iter()
+        .find(|marker| line[indent..].starts_with(*marker))?;
+    let repeat = marker.chars().last()?;
+    let mut end = indent + marker.len();
+    end += line[end..].chars().take_while(|&c| c == repeat).count();
+    match line[end..].chars().next() {
+        None => {}
+        Some(' ') => end += 1,
+        Some(_) if repeat == '*' => return None,
+        Some(_) => {}
+    }
+    let rest = &line[end..];
+    let rest = match rest.rfind("*/") {
+        Some(close) if rest[close + 2..].trim().is_empty() => rest[..close].trim_end(),
+        _ => rest,
+    };
+    Some((&line[..end], rest))
+}
+
+/// Returns true when a line with the prefix can follow a line with the prefix `first` in a
+/// drawing: the prefixes are the same, or the first opens a block comment that the `*` of the
+/// other continues in the same column, as in `/* ` and ` * `.
+fn continues(first: &str, prefix: &str) -> bool {
+    first == prefix
+        || (first.trim_start().starts_with("/*")
+            && prefix.trim() == "*"
+            && first.chars().count() == prefix.chars().count()
+            && first.find('*') == prefix.find('*'))
+}
+
+/// The runs of consecutive comment lines with the same prefix, each of which contains box
+/// drawing.
+fn source(source: &str) -> Vec<Embedded<'_>> {
+    let lines: Vec<&str> = source.split('\n').collect();
+    let mut drawings: Vec<Embedded> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let (prefix, rest) = match comment(line) {
+            Some((prefix, rest)) if rest.contains(is_box_drawing) => (prefix, rest),
+            _ => continue,
+        };
+        match drawings.last_mut() {
+            Some(drawing)
+                if drawing.line + drawing.lines.len() == i
+                    && continues(drawing.lines[0].0, prefix) =>
+            {
+                drawing.lines.push((prefix, rest))
+            }
+            _ => drawings.push(Embedded {
+                line: i,
+                lines: vec![(prefix, rest)],
+            }),
+        }
+    }
+    drawings
+}
+
+/// Finds the drawings in a document. Of the code blocks and comments with box drawing, only
+/// those that `classify` takes to be drawings are kept, so that e.g. code that prints a line of
+/// `─` is left alone.
+pub fn drawings(document: &str, kind: Kind) -> Vec<Embedded<'_>> {
+    let mut drawings = match kind {
+        Kind::Markdown => markdown(document),
+        Kind::Source => source(document),
+        Kind::Drawing => {
+            return vec![Embedded {
+                line: 0,
+                lines: document.split('\n').map(|line| ("", line)).collect(),
+            }]
+        }
+    };
+    drawings.retain(|drawing| confidence(&drawing.text()) >= THRESHOLD);
+    drawings
+}
+
+/// Replaces each drawing in a document with what `f` makes of it, which must have as many lines.
+pub(crate) fn replace<F>(document: &str, kind: Kind, mut f: F) -> String
+where
+    F: FnMut(&str) -> String,
+{
+    let original: Vec<&str> = document.split('\n').collect();
+    let mut lines: Vec<String> = original.iter().map(|line| line.to_string()).collect();
+    for drawing in drawings(document, kind) {
+        let replaced = f(&drawing.text());
+        for (i, (&(prefix, old), new)) in drawing.lines.iter().zip(replaced.split('\n')).enumerate()
+        {
+            let line = drawing.line + i;
+            let suffix = &original[line][prefix.len() + old.len()..];
+            lines[line] = format!("{}{}{}", prefix, new, suffix)
+                .trim_end()
+                .to_string();
+        }
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,281 @@
+//! Excalidraw scenes.
+//!
+//! Boxes become rectangles with their labels bound inside, wires with an arrowhead become arrows
+//! bound to the boxes at either end, other wires become lines, and the remaining text becomes
+//! free text. Each character cell is 10 by 20 pixels of the scene.
+//!
+//! ```
+//! use dottools::{excalidraw, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! let scene = excalidraw::render(&graph);
+//! assert!(scene.contains(r#""type":"arrow""#));
+//! assert!(scene.contains(r#""startBinding":{"elementId":"b0","focus":0,"gap":1}"#));
+//! ```
+
+use crate::drawio::{centre, CELL_HEIGHT, CELL_WIDTH};
+use crate::json::string;
+use crate::logical::{Diagram, End};
+use crate::{Graph, Weight};
+use std::fmt::Write;
+
+const FONT_SIZE: f64 = 16.0;
+
+struct Scene {
+    elements: Vec<String>,
+    /// Used for the seeds that Excalidraw draws its strokes with.
+    seed: usize,
+}
+
+impl Scene {
+    /// Adds an element with the given type, bounds and stroke, followed by the fields that are
+    /// particular to the type.
+    #[allow(clippy::too_many_arguments)]
+    fn element(
+        &mut self,
+        id: &str,
+        kind: &str,
+        (x, y): (f64, f64),
+        (width, height): (f64, f64),
+        weight: Weight,
+        dashed: bool,
+        bound: &[(String, &str)],
+        rest: &str,
+    ) {
+        self.seed += 1;
+        let mut element = String::new();
+        write!(
+            element,
+            concat!(
+                r#"{{"id":{},"type":{},"x":{},"y":{},"width":{},"height":{},"angle":0,"#,
+                r##""strokeColor":"#1e1e1e","backgroundColor":"transparent","##,
+                r#""fillStyle":"solid","strokeWidth":{},"strokeStyle":{},"roughness":1,"#,
+                r#""opacity":100,"groupIds":[],"frameId":null,"roundness":null,"#,
+                r#""seed":{},"version":1,"versionNonce":{},"isDeleted":false,"#,
+                r#""boundElements":"#
+            ),
+            string(id),
+            string(kind),
+            x,
+            y,
+            width,
+            height,
+            if weight == Weight::Light { 1 } else { 2 },
+            string(if dashed { "dashed" } else { "solid" }),
+            self.seed,
+            self.seed,
+        )
+        .unwrap();
+        if bound.is_empty() {
+            element.push_str("null");
+        } else {
+            let bound: Vec<String> = bound
+                .iter()
+                .map(|(id, kind)| format!(r#"{{"id":{},"type":{}}}"#, string(id), string(kind)))
+                .collect();
+            write!(element, "[{}]", bound.join(",")).unwrap();
+        }
+        write!(
+            element,
+            r#","updated":1,"link":null,"locked":false{}}}"#,
+            rest
+        )
+        .unwrap();
+        self.elements.push(element);
+    }
+
+    /// Adds a text element, centred on the given point, optionally bound to a container and
+    /// aligned to its top or middle.
+    fn text(
+        &mut self,
+        id: &str,
+        text: &str,
+        (x, y): (f64, f64),
+        container: Option<&str>,
+        align: &str,
+    ) {
+        let lines: Vec<&str> = text.lines().collect();
+        let columns = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let size = (
+            columns as f64 * CELL_WIDTH,
+            lines.len() as f64 * CELL_HEIGHT,
+        );
+        let rest = format!(
+            concat!(
+                r#","text":{0},"fontSize":{1},"fontFamily":3,"textAlign":"center","#,
+                r#""verticalAlign":{3},"containerId":{2},"originalText":{0},"#,
+                r#""lineHeight":1.25"#
+            ),
+            string(text),
+            FONT_SIZE,
+            container.map_or("null".to_string(), string),
+            string(align),
+        );
+        self.element(
+            id,
+            "text",
+            (x - size.0 / 2.0, y - size.1 / 2.0),
+            size,
+            Weight::Light,
+            false,
+            &[],
+            &rest,
+        );
+    }
+}
+
+/// Renders the drawing as an Excalidraw scene.
+pub fn render(graph: &Graph) -> String {
+    let diagram = Diagram::from(graph);
+    let mut scene = Scene {
+        elements: Vec::new(),
+        seed: 0,
+    };
+
+    // Work out the wires first, so that the boxes know what is bound to them.
+    let mut wires = Vec::new();
+    for (w, wire) in diagram.wires.iter().enumerate() {
+        for (from, to, directed) in wire.pairs(|_| true) {
+            wires.push((w, from, to, directed));
+        }
+    }
+    let id = |i: usize, &(_, _, _, directed): &(usize, &End, &End, bool)| {
+        format!("{}{}", if directed { "a" } else { "l" }, i)
+    };
+
+    for (b, block) in diagram.blocks.iter().enumerate() {
+        let (x0, y0) = centre(block.top_left);
+        let (x1, y1) = centre(block.bottom_right);
+        let mut bound = Vec::new();
+        if !block.label.is_empty() {
+            bound.push((format!("b{}-label", b), "text"));
+        }
+        for (i, wire) in wires.iter().enumerate() {
+            let (_, from, to, directed) = *wire;
+            if directed && (from.block == Some(b) || to.block == Some(b)) {
+                bound.push((id(i, wire), "arrow"));
+            }
+        }
+        scene.element(
+            &format!("b{}", b),
+            "rectangle",
+            (x0, y0),
+            (x1 - x0, y1 - y0),
+            block.brush.weight(),
+            block.brush.is_dashed(),
+            &bound,
+            "",
+        );
+        if !block.label.is_empty() {
+            // Boxes with others inside are labelled along their top, out of the others' way.
+            let container = diagram.blocks.iter().any(|other| other.parent == Some(b));
+            let (y, align) = if container {
+                (y0 + CELL_HEIGHT, "top")
+            } else {
+                ((y0 + y1) / 2.0, "middle")
+            };
+            scene.text(
+                &format!("b{}-label", b),
+                &block.label,
+                ((x0 + x1) / 2.0, y),
+                Some(&format!("b{}", b)),
+                align,
+            );
+        }
+    }
+
+    for (i, wire) in wires.iter().enumerate() {
+        let (w, from, to, directed) = *wire;
+        let path = diagram.wires[w].path(from, to);
+        if path.is_empty() {
+            continue;
+        }
+        let mut points: Vec<(f64, f64)> = path.iter().cloned().map(centre).collect();
+        // Wires that end on a box reach all the way to its border.
+        for &(end, index) in &[(from, 0), (to, points.len() - 1)] {
+            if end.block.is_some() {
+                let (x, y) = centre(end.position);
+                let point = &mut points[index];
+                *point = ((point.0 + x) / 2.0, (point.1 + y) / 2.0);
+            }
+        }
+        let (x, y) = points[0];
+        let xs = points.iter().map(|p| p.0);
+        let ys = points.iter().map(|p| p.1);
+        let width = xs.clone().fold(x, f64::max) - xs.fold(x, f64::min);
+        let height = ys.clone().fold(y, f64::max) - ys.fold(y, f64::min);
+        let relative: Vec<String> = points
+            .iter()
+            .map(|p| format!("[{},{}]", p.0 - x, p.1 - y))
+            .collect();
+        let binding = |end: &End| match end.block {
+            Some(block) => format!(r#"{{"elementId":"b{}","focus":0,"gap":1}}"#, block),
+            None => "null".to_string(),
+        };
+        let id = id(i, wire);
+        let label = diagram.wires[w].label.as_ref();
+        let (kind, rest) = if directed {
+            (
+                "arrow",
+                format!(
+                    r#","points":[{}],"startBinding":{},"endBinding":{},"startArrowhead":null,"endArrowhead":"arrow""#,
+                    relative.join(","),
+                    binding(from),
+                    binding(to)
+                ),
+            )
+        } else {
+            (
+                "line",
+                format!(
+                    r#","points":[{}],"startBinding":null,"endBinding":null,"startArrowhead":null,"endArrowhead":null"#,
+                    relative.join(",")
+                ),
+            )
+        };
+        let bound = match label {
+            Some(_) if directed => vec![(format!("{}-label", id), "text")],
+            _ => Vec::new(),
+        };
+        let brush = diagram.wires[w].brush;
+        scene.element(
+            &id,
+            kind,
+            (x, y),
+            (width, height),
+            brush.weight(),
+            brush.is_dashed(),
+            &bound,
+            &rest,
+        );
+        if let Some(label) = label {
+            let middle = points[points.len() / 2];
+            let container = if directed { Some(id.as_str()) } else { None };
+            scene.text(&format!("{}-label", id), label, middle, container, "middle");
+        }
+    }
+
+    for (t, text) in diagram.texts.iter().enumerate() {
+        let (x, y) = centre(text.position);
+        let width = text.text.chars().count() as f64 * CELL_WIDTH;
+        scene.text(
+            &format!("t{}", t),
+            &text.text,
+            (x - CELL_WIDTH / 2.0 + width / 2.0, y),
+            None,
+            "middle",
+        );
+    }
+
+    let mut out = String::new();
+    out.push_str("{\"type\":\"excalidraw\",\"version\":2,\"source\":\"dottools\",\"elements\":[\n");
+    out.push_str(&scene.elements.join(",\n"));
+    out.push_str(
+        "\n],\"appState\":{\"viewBackgroundColor\":\"#ffffff\",\"gridSize\":null},\"files\":{}}\n",
+    );
+    out
+}
@@ -0,0 +1,112 @@
+//! Tidying drawings, on their own or where they appear in Markdown and source files.
+//!
+//! A drawing is tidied by joining the strokes that meet without a junction, redrawing it in
+//! one style when one is given, and trimming the whitespace from the end of each line, like
+//! `clean_string` does. Everything around a drawing is left as it is.
+//!
+//! ```
+//! use dottools::format::{format, Options};
+//!
+//! let drawing = "┌───── \n│ A  │\n└────┘";
+//! assert_eq!(format(drawing, &Options::default()), "┌────┐\n│ A  │\n└────┘");
+//! ```
+
+use crate::canvas::{stroke, Canvas};
+use crate::embedded::{replace, Kind};
+use crate::style::{restyle, Style};
+use unicode_normalization::char::is_combining_mark;
+
+/// How to tidy a drawing.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// The style to redraw strokes in, or `None` to keep the style they are drawn in.
+    pub style: Option<Style>,
+}
+
+/// Adds the arms that strokes lead into each other with, and removes the arms that a stroke
+/// which gained one has towards empty cells, so that e.g. a `─` above a `│` becomes a corner.
+/// ASCII has no junctions but `+`, so ASCII strokes are left as they are and not joined to.
+fn join(text: &str) -> String {
+    let canvas = Canvas::from_text(text);
+    let is_free = |line: Option<usize>, column: Option<usize>| match (line, column) {
+        (Some(line), Some(column)) => canvas.is_free(line, column),
+        _ => true,
+    };
+    let mut out = String::with_capacity(text.len());
+    for (line, text) in text.split('\n').enumerate() {
+        if line > 0 {
+            out.push('\n');
+        }
+        let mut column = 0;
+        for c in text.chars() {
+            if is_combining_mark(c) {
+                out.push(c);
+                continue;
+            }
+            let cell = canvas.get(line, column);
+            let up = line.checked_sub(1).map(|l| canvas.get(l, column));
+            let left = column.checked_sub(1).map(|v| canvas.get(line, v));
+            let down = canvas.get(line + 1, column);
+            let right = canvas.get(line, column + 1);
+            let mut arms = [cell.north, cell.east, cell.south, cell.west];
+            let leads = [
+                up.and_then(|cell| cell.south),
+                right.west,
+                down.north,
+                left.and_then(|cell| cell.east),
+            ];
+            let mut joined = false;
+            for (arm, lead) in arms.iter_mut().zip(&leads) {
+                if arm.is_none() && matches!(lead, Some(brush) if !brush.is_ascii()) {
+                    *arm = *lead;
+                    joined = true;
+                }
+            }
+            if cell.is_stroke() && joined && !c.is_ascii() {
+                let towards = [
+                    (line.checked_sub(1), Some(column)),
+                    (Some(line), Some(column + 1)),
+                    (Some(line + 1), Some(column)),
+                    (Some(line), column.checked_sub(1)),
+                ];
+                for (arm, &(l, v)) in arms.iter_mut().zip(&towards) {
+                    if is_free(l, v) {
+                        *arm = None;
+                    }
+                }
+                let [north, east, south, west] = arms;
+                out.push(stroke(north, east, south, west));
+            } else {
+                out.push(c);
+            }
+            column += 1;
+        }
+    }
+    out
+}
+
+/// Tidies a drawing.
+pub fn format(drawing: &str, options: &Options) -> String {
+    let mut drawing = join(drawing);
+    if let Some(style) = options.style {
+        drawing = restyle(&drawing, style);
+    }
+    drawing
+        .split('\n')
+        .map(str::trim_end)
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// Tidies the drawings in the code blocks of a Markdown document: fenced ones whose info string
+/// is empty or `text`, and indented ones, which contain box drawing.
+pub fn format_markdown(document: &str, options: &Options) -> String {
+    replace(document, Kind::Markdown, |drawing| format(drawing, options))
+}
+
+/// Tidies the drawings in the comments of a source file. A drawing is a run of consecutive
+/// comment lines with the same prefix, each of which contains box drawing.
+pub fn format_source(source: &str, options: &Options) -> String {
+    replace(source, Kind::Source, |drawing| format(drawing, options))
+}
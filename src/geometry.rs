@@ -0,0 +1,155 @@
+//! The geometry of a drawing, in cell units, shared by the vector exports.
+//!
+//! A point `(x, y)` is measured from the top left corner of the drawing, so the cell on visual
+//! line `l` and column `c` spans `c..c + 1` horizontally and `l - 1..l` vertically.
+
+use crate::canvas::Canvas;
+use crate::{arrowhead, Brush, Graph, Node, Region};
+
+/// A straight stroke between two points.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Stroke {
+    pub(crate) from: (f64, f64),
+    pub(crate) to: (f64, f64),
+    pub(crate) brush: Brush,
+}
+
+/// A run of text on one line, starting at the left edge of a cell.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Label {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) text: String,
+}
+
+impl Label {
+    /// The column just after the text.
+    pub(crate) fn end(&self) -> usize {
+        self.column + self.text.chars().count()
+    }
+}
+
+/// An arrowhead at the end of a stroke, pointing in `direction`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Arrow {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) direction: Region,
+}
+
+impl Arrow {
+    /// The points where the arrowhead's cell is entered and left.
+    pub(crate) fn points(&self) -> ((f64, f64), (f64, f64)) {
+        let (x, y) = (self.column as f64, self.line as f64 - 1.0);
+        match self.direction {
+            Region::North => ((x + 0.5, y + 1.0), (x + 0.5, y)),
+            Region::South => ((x + 0.5, y), (x + 0.5, y + 1.0)),
+            Region::West => ((x + 1.0, y + 0.5), (x, y + 0.5)),
+            _ => ((x, y + 0.5), (x + 1.0, y + 0.5)),
+        }
+    }
+}
+
+/// Everything that a drawing is made of.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Geometry {
+    pub(crate) strokes: Vec<Stroke>,
+    pub(crate) labels: Vec<Label>,
+    pub(crate) arrows: Vec<Arrow>,
+    /// The number of lines, and the length of the longest line.
+    pub(crate) size: (usize, usize),
+}
+
+/// The point of a node within its cell.
+fn point(node: &Node) -> (f64, f64) {
+    let x = node.visual.column as f64
+        + match node.region.1 {
+            Region::West => 0.0,
+            Region::East => 1.0,
+            _ => 0.5,
+        };
+    let y = node.visual.line as f64 - 1.0
+        + match node.region.0 {
+            Region::North => 0.0,
+            Region::South => 1.0,
+            _ => 0.5,
+        };
+    (x, y)
+}
+
+impl Geometry {
+    pub(crate) fn from_graph(graph: &Graph) -> Self {
+        let canvas = Canvas::from_text(&graph.to_string());
+        let mut geometry = Geometry {
+            size: canvas.size(),
+            ..Geometry::default()
+        };
+
+        let rectangles = canvas.rectangles();
+        let mut edges: Vec<_> = graph.all_edges().collect();
+        edges.sort_by_key(|&(v, u, _)| if v < u { (v, u) } else { (u, v) });
+        // Strokes within words, like the `-` of `lib-core`, are drawn as text.
+        let is_text = |node: &Node| {
+            let (line, column) = (node.visual.line - 1, node.visual.column);
+            canvas.is_text(&rectangles, line, column)
+                && !arrowhead(node.character)
+                    .is_some_and(|direction| canvas.leads_into(line, column, direction))
+        };
+        for (v, u, edge) in edges {
+            if is_text(&v) || is_text(&u) {
+                continue;
+            }
+            let (v, u) = if v < u { (v, u) } else { (u, v) };
+            let (mut from, mut to) = (point(&v), point(&u));
+            // Strokes run from top to bottom, and from left to right.
+            if (to.1, to.0) < (from.1, from.0) {
+                std::mem::swap(&mut from, &mut to);
+            }
+            if from != to {
+                geometry.strokes.push(Stroke {
+                    from,
+                    to,
+                    brush: edge.1,
+                });
+            }
+        }
+
+        let (height, width) = geometry.size;
+        for line in 1..=height {
+            for column in 0..width {
+                if !canvas.is_text(&rectangles, line - 1, column) {
+                    continue;
+                }
+                let character = canvas.get(line - 1, column).character();
+                if let Some(direction) = arrowhead(character) {
+                    if canvas.leads_into(line - 1, column, direction) {
+                        geometry.arrows.push(Arrow {
+                            line,
+                            column,
+                            direction,
+                        });
+                        continue;
+                    }
+                }
+                // Words separated by a single space are kept together.
+                match geometry.labels.last_mut() {
+                    Some(label)
+                        if label.line == line
+                            && (label.end()..=label.end() + 1).contains(&column) =>
+                    {
+                        if column > label.end() {
+                            label.text.push(' ');
+                        }
+                        label.text.push(character);
+                    }
+                    _ => geometry.labels.push(Label {
+                        line,
+                        column,
+                        text: character.to_string(),
+                    }),
+                }
+            }
+        }
+        geometry
+    }
+}
@@ -0,0 +1,195 @@
+//! GraphML documents, for graph tools such as yEd and networkx.
+//!
+//! A drawing can be written either as its raw graph, where every node is a point of a character
+//! cell and every edge is a stroke between two of them, or as its logical graph, where every node
+//! is a box and every edge is a wire between two boxes. Both carry their positions, glyphs,
+//! brushes and labels as typed attributes.
+//!
+//! ```
+//! use dottools::{graphml, logical::Diagram, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! let graphml = graphml::render_diagram(&Diagram::from(&graph));
+//! assert!(graphml.contains(r#"<edge id="e0" source="n0" target="n1" directed="true">"#));
+//! ```
+
+use crate::logical::Diagram;
+use crate::xml::escape;
+use crate::{Brush, Graph, Node, Region, Weight};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+fn region(region: Region) -> &'static str {
+    match region {
+        Region::Center => "center",
+        Region::North => "north",
+        Region::East => "east",
+        Region::South => "south",
+        Region::West => "west",
+    }
+}
+
+fn weight(brush: Brush) -> &'static str {
+    match brush.weight() {
+        Weight::Light => "light",
+        Weight::Heavy => "heavy",
+        Weight::Double => "double",
+    }
+}
+
+fn direction(brush: Brush) -> &'static str {
+    match brush {
+        Brush::NorthSouth(_) => "north-south",
+        Brush::EastWest(_) => "east-west",
+        Brush::NorthEastSouthWest(_) => "north-east-south-west",
+        Brush::NorthWestSouthEast(_) => "north-west-south-east",
+    }
+}
+
+/// A document under construction, with its keys declared up front.
+struct Document {
+    out: String,
+}
+
+impl Document {
+    fn new(keys: &[(&str, &str, &str)], edgedefault: &str) -> Self {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (id, domain, kind) in keys {
+            writeln!(
+                out,
+                r#"  <key id="{0}" for="{1}" attr.name="{0}" attr.type="{2}"/>"#,
+                id, domain, kind
+            )
+            .unwrap();
+        }
+        writeln!(out, r#"  <graph id="G" edgedefault="{}">"#, edgedefault).unwrap();
+        Document { out }
+    }
+
+    fn data(&mut self, key: &str, value: &dyn std::fmt::Display) {
+        writeln!(
+            self.out,
+            r#"      <data key="{}">{}</data>"#,
+            key,
+            escape(&value.to_string())
+        )
+        .unwrap();
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push_str("  </graph>\n</graphml>\n");
+        self.out
+    }
+}
+
+/// Writes the raw graph of the drawing. Nodes carry their visual `line` and `column`, the
+/// `glyph` of their character and the `region` of the cell that they are in; edges carry the
+/// `glyph`, `brush`, `dashed` style and `direction` of their brush.
+pub fn render(graph: &Graph) -> String {
+    let mut document = Document::new(
+        &[
+            ("line", "node", "int"),
+            ("column", "node", "int"),
+            ("glyph", "all", "string"),
+            ("region", "node", "string"),
+            ("brush", "edge", "string"),
+            ("dashed", "edge", "boolean"),
+            ("direction", "edge", "string"),
+        ],
+        "undirected",
+    );
+    let mut nodes: Vec<Node> = graph.nodes().collect();
+    nodes.sort();
+    let ids: HashMap<Node, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    for (i, node) in nodes.iter().enumerate() {
+        writeln!(document.out, r#"    <node id="n{}">"#, i).unwrap();
+        document.data("line", &node.visual.line);
+        document.data("column", &node.visual.column);
+        document.data("glyph", &node.character);
+        document.data(
+            "region",
+            &format!("{} {}", region(node.region.0), region(node.region.1)),
+        );
+        document.out.push_str("    </node>\n");
+    }
+    let mut edges: Vec<(usize, usize, Brush)> = graph
+        .all_edges()
+        .map(|(v, u, edge)| {
+            let (v, u) = (ids[&v], ids[&u]);
+            (v.min(u), v.max(u), edge.1)
+        })
+        .collect();
+    edges.sort_by_key(|&(v, u, _)| (v, u));
+    for (i, (v, u, brush)) in edges.into_iter().enumerate() {
+        writeln!(
+            document.out,
+            r#"    <edge id="e{}" source="n{}" target="n{}">"#,
+            i, v, u
+        )
+        .unwrap();
+        document.data("glyph", &brush.character());
+        document.data("brush", &weight(brush));
+        document.data("dashed", &brush.is_dashed());
+        document.data("direction", &direction(brush));
+        document.out.push_str("    </edge>\n");
+    }
+    document.finish()
+}
+
+/// Writes the logical graph of the diagram. Nodes are boxes, which carry their `label`, the
+/// `line` and `column` of their top left corner, their `width` and `height`, their `parent` box
+/// and the `brush` and `dashed` style of their border; edges are connections, which carry the
+/// `label`, `brush` and `dashed` style of their wire and whether they are `directed`.
+pub fn render_diagram(diagram: &Diagram) -> String {
+    let mut document = Document::new(
+        &[
+            ("label", "all", "string"),
+            ("line", "node", "int"),
+            ("column", "node", "int"),
+            ("width", "node", "int"),
+            ("height", "node", "int"),
+            ("parent", "node", "string"),
+            ("brush", "all", "string"),
+            ("dashed", "all", "boolean"),
+        ],
+        "undirected",
+    );
+    for (i, block) in diagram.blocks.iter().enumerate() {
+        writeln!(document.out, r#"    <node id="n{}">"#, i).unwrap();
+        document.data("label", &block.label);
+        document.data("line", &block.top_left.line);
+        document.data("column", &block.top_left.column);
+        document.data(
+            "width",
+            &(block.bottom_right.column - block.top_left.column + 1),
+        );
+        document.data(
+            "height",
+            &(block.bottom_right.line - block.top_left.line + 1),
+        );
+        if let Some(parent) = block.parent {
+            document.data("parent", &format!("n{}", parent));
+        }
+        document.data("brush", &weight(block.brush));
+        document.data("dashed", &block.brush.is_dashed());
+        document.out.push_str("    </node>\n");
+    }
+    for (i, connection) in diagram.connections().iter().enumerate() {
+        let wire = &diagram.wires[connection.wire];
+        writeln!(
+            document.out,
+            r#"    <edge id="e{}" source="n{}" target="n{}" directed="{}">"#,
+            i, connection.from, connection.to, connection.directed
+        )
+        .unwrap();
+        if let Some(label) = &wire.label {
+            document.data("label", label);
+        }
+        document.data("brush", &weight(wire.brush));
+        document.data("dashed", &wire.brush.is_dashed());
+        document.out.push_str("    </edge>\n");
+    }
+    document.finish()
+}
@@ -0,0 +1,85 @@
+//! Rendering a drawing as an HTML `<pre>` element.
+//!
+//! Every character of the drawing is wrapped in a `<span>` that carries its visual position in
+//! `data-position`, the index of the wire it belongs to in `data-wire` and the label of the box
+//! it belongs to in `data-box`. The accompanying stylesheet highlights the whole of a wire while
+//! any part of it is hovered, without needing any script.
+//!
+//! ```
+//! use dottools::{html, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐\n│ A ├──\n└───┘").unwrap();
+//! let html = html::render(&graph);
+//! assert!(html.contains(r#"<span data-position="2:5" data-wire="0">─</span>"#));
+//! assert!(html.contains(r#"<span data-position="2:2" data-box="A">A</span>"#));
+//! ```
+
+use crate::logical::Diagram;
+use crate::xml::escape;
+use crate::{Graph, LineColumn};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// The class of the `<pre>` element, which scopes the stylesheet.
+const CLASS: &str = "dottools";
+const HIGHLIGHT: &str = "background-color: #ffe58f;";
+
+/// Renders the drawing as a `<style>` element followed by a `<pre>` element.
+pub fn render(graph: &Graph) -> String {
+    let diagram = Diagram::from(graph);
+    let mut wires = HashMap::new();
+    for (i, wire) in diagram.wires.iter().enumerate() {
+        for &cell in &wire.cells {
+            wires.insert(cell, i);
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str("<style>\n");
+    writeln!(
+        out,
+        ".{} span[data-wire]:hover {{ cursor: pointer; }}",
+        CLASS
+    )
+    .unwrap();
+    for i in 0..diagram.wires.len() {
+        writeln!(
+            out,
+            r#".{0}:has(span[data-wire="{1}"]:hover) span[data-wire="{1}"] {{ {2} }}"#,
+            CLASS, i, HIGHLIGHT
+        )
+        .unwrap();
+    }
+    out.push_str("</style>\n");
+
+    write!(out, r#"<pre class="{}">"#, CLASS).unwrap();
+    for (line, text) in graph.to_string().lines().enumerate() {
+        if line > 0 {
+            out.push('\n');
+        }
+        for (column, c) in text.chars().enumerate() {
+            if c.is_whitespace() {
+                out.push(c);
+                continue;
+            }
+            let position = LineColumn {
+                line: line + 1,
+                column,
+            };
+            write!(out, r#"<span data-position="{}:{}""#, position.line, column).unwrap();
+            if let Some(wire) = wires.get(&position) {
+                write!(out, r#" data-wire="{}""#, wire).unwrap();
+            } else if let Some(block) = diagram.block_at(position) {
+                write!(
+                    out,
+                    r#" data-box="{}""#,
+                    escape(&diagram.blocks[block].label)
+                )
+                .unwrap();
+            }
+            write!(out, ">{}</span>", escape(&c.to_string())).unwrap();
+        }
+    }
+    out.push_str("</pre>\n");
+    out
+}
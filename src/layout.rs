@@ -0,0 +1,1173 @@
+//! Layered (Sugiyama style) layout of labelled boxes connected by orthogonal wires.
+//!
+//! Edges are oriented so that the graph is acyclic, nodes are assigned to layers by the longest
+//! path that reaches them and long edges are broken up by dummy items, one per layer they cross.
+//! Each layer is a band of boxes; between consecutive bands is a channel in which every wire
+//! gets a track of its own, so that wires only ever cross and never overlap. The order of the
+//! items within each layer is chosen to reduce the number of crossings. The layers follow each
+//! other down the canvas, or up it, or across it either way.
+//!
+//! Each label is written beside its own wire, or inside its own cluster, where it reads back as
+//! belonging to it and to nothing else. Where there is no such place, the item that the wire
+//! leads into, or the cluster, is widened until there is.
+//!
+//! ```
+//! use petgraph::Graph;
+//!
+//! let mut deps = Graph::<&str, ()>::new();
+//! let app = deps.add_node("app");
+//! let lib = deps.add_node("lib");
+//! deps.add_edge(app, lib, ());
+//! let drawing = dottools::layout::layered(&deps).unwrap();
+//! assert_eq!(
+//!     drawing.to_string(),
+//!     "┌─────┐\n│ app │\n└──┬──┘\n   │\n   │\n   ▼\n┌─────┐\n│ lib │\n└─────┘"
+//! );
+//! ```
+
+use crate::canvas::{vertical, Canvas, Rectangle};
+use crate::{Error, Graph};
+use petgraph::visit::{
+    EdgeRef, GraphProp, IntoEdgeReferences, IntoNodeReferences, NodeIndexable, NodeRef,
+};
+use std::cmp::max;
+use std::collections::HashMap;
+use std::fmt;
+use std::mem::swap;
+
+const BOX: char = '─';
+const WIRE: char = '─';
+const CLUSTER: char = '┄';
+
+/// The number of sweeps made when reducing crossings.
+const SWEEPS: usize = 8;
+
+/// Barycenters are scaled by this much so that they can be compared as integers.
+const SCALE: usize = 64;
+
+/// The number of times the layout is widened to make room for labels that do not fit.
+const WIDENINGS: usize = 8;
+
+/// How close, in cells, a label is read as belonging to a wire.
+const LABEL_DISTANCE: usize = 2;
+
+#[derive(Debug, Clone)]
+struct Vertex {
+    label: Vec<String>,
+    cluster: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Connection {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+    /// Set when the connection ends in an arrowhead at `to`.
+    directed: bool,
+    /// Set when the connection ends in an arrowhead at `from`.
+    backward: bool,
+    brush: char,
+}
+
+#[derive(Debug, Clone)]
+struct Cluster {
+    label: Option<String>,
+    parent: Option<usize>,
+}
+
+/// The way that edges run from one layer to the next.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    Down,
+    Up,
+    Right,
+    Left,
+}
+
+/// An abstract graph of labelled nodes, optionally grouped into nested clusters, to be laid
+/// out.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    directed: bool,
+    direction: Direction,
+    vertices: Vec<Vertex>,
+    connections: Vec<Connection>,
+    clusters: Vec<Cluster>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum Item {
+    Vertex(usize),
+    /// The part of a long connection that passes through a layer.
+    Dummy(usize),
+}
+
+/// A connection between two items of consecutive layers.
+#[derive(Debug, Copy, Clone)]
+struct Segment {
+    connection: usize,
+    layer: usize,
+    upper: usize,
+    lower: usize,
+}
+
+/// The layers, in their final order, along with the width of each item.
+struct Columns<'a> {
+    layers: &'a [Vec<Item>],
+    widths: &'a [Vec<usize>],
+    dummies: &'a [Option<usize>],
+    /// The columns added after the members of each cluster.
+    room: &'a [usize],
+}
+
+/// The room added to items and clusters whose labels did not fit.
+#[derive(Debug, Clone)]
+struct Room {
+    /// Extra width for the item of a layer that a labelled connection leads into.
+    items: HashMap<(usize, Item), usize>,
+    clusters: Vec<usize>,
+}
+
+/// A label to be written next to a connection, or inside a cluster, at the first of its
+/// places that it can be read back from.
+struct Label {
+    text: String,
+    owner: Owner,
+    places: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Owner {
+    /// The connection, along with the item that it leads into from the labelled segment.
+    Connection(usize, (usize, Item)),
+    /// The cluster, along with its top left and bottom right corners.
+    Cluster(usize, (usize, usize), (usize, usize)),
+}
+
+/// Where an item ended up on the canvas.
+#[derive(Debug, Default, Copy, Clone)]
+struct Placement {
+    left: usize,
+    right: usize,
+    top: usize,
+    bottom: usize,
+}
+
+impl Layout {
+    /// Starts an empty layout; the edges of a directed layout end in arrowheads.
+    pub fn new(directed: bool) -> Self {
+        Layout {
+            directed,
+            direction: Direction::Down,
+            vertices: Vec::new(),
+            connections: Vec::new(),
+            clusters: Vec::new(),
+        }
+    }
+
+    /// Adds a cluster, within the `parent` cluster if there is one, and returns its index, or
+    /// fails with `Error::NoCluster` when the parent has not been added yet. A cluster can only
+    /// be put in one added before it, so that no cluster ends up within itself.
+    pub fn add_cluster(
+        &mut self,
+        label: Option<String>,
+        parent: Option<usize>,
+    ) -> Result<usize, Error> {
+        if let Some(parent) = parent.filter(|&parent| parent >= self.clusters.len()) {
+            return Err(Error::NoCluster(parent));
+        }
+        self.clusters.push(Cluster { label, parent });
+        Ok(self.clusters.len() - 1)
+    }
+
+    /// Adds a node, labelled with one or more lines of text, and returns its index.
+    pub fn add_node(&mut self, label: &str, cluster: Option<usize>) -> usize {
+        let label = label.lines().map(|line| line.trim().to_string()).collect();
+        self.vertices.push(Vertex { label, cluster });
+        self.vertices.len() - 1
+    }
+
+    /// Adds an edge between the nodes with the given indices and returns its index, or fails
+    /// with `Error::NoNode` when either node has not been added. Edges from a node to itself are
+    /// not drawn.
+    pub fn add_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        label: Option<String>,
+    ) -> Result<usize, Error> {
+        if let Some(&node) = [from, to].iter().find(|&&node| node >= self.vertices.len()) {
+            return Err(Error::NoNode(node));
+        }
+        let label = label
+            .map(|label| label.lines().collect::<Vec<_>>().join(" "))
+            .filter(|label| !label.trim().is_empty());
+        self.connections.push(Connection {
+            from,
+            to,
+            label,
+            directed: self.directed,
+            backward: false,
+            brush: WIRE,
+        });
+        Ok(self.connections.len() - 1)
+    }
+
+    /// The edge with the given index, or `Error::NoEdge` when it has not been added.
+    fn connection(&mut self, edge: usize) -> Result<&mut Connection, Error> {
+        self.connections.get_mut(edge).ok_or(Error::NoEdge(edge))
+    }
+
+    /// Sets whether the edge with the given index ends in an arrowhead, which by default it does
+    /// when the layout is directed.
+    pub fn set_directed(&mut self, edge: usize, directed: bool) -> Result<(), Error> {
+        self.connection(edge)?.directed = directed;
+        Ok(())
+    }
+
+    /// Sets whether the edge with the given index also ends in an arrowhead at the node it
+    /// starts from, which by default it does not.
+    pub fn set_backward(&mut self, edge: usize, backward: bool) -> Result<(), Error> {
+        self.connection(edge)?.backward = backward;
+        Ok(())
+    }
+
+    /// Sets the way that edges run from one layer to the next, which by default is down.
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    /// Sets the horizontal brush that the edge with the given index is drawn with, such as `┄`
+    /// for a dotted edge.
+    pub fn set_brush(&mut self, edge: usize, brush: char) -> Result<(), Error> {
+        self.connection(edge)?.brush = brush;
+        Ok(())
+    }
+
+    /// Orients every connection so that the graph is acyclic, returning true for each connection
+    /// that has to be drawn against its direction.
+    fn reversed(&self) -> Vec<bool> {
+        let n = self.vertices.len();
+        let mut successors = vec![Vec::new(); n];
+        for c in &self.connections {
+            successors[c.from].push(c.to);
+        }
+        let mut post = vec![None; n];
+        let mut counter = 0;
+        let mut visited = vec![false; n];
+        for root in 0..n {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![(root, 0)];
+            while let Some(&mut (v, ref mut i)) = stack.last_mut() {
+                if let Some(&w) = successors[v].get(*i) {
+                    *i += 1;
+                    if !visited[w] {
+                        visited[w] = true;
+                        stack.push((w, 0));
+                    }
+                } else {
+                    post[v] = Some(counter);
+                    counter += 1;
+                    stack.pop();
+                }
+            }
+        }
+        // Only back edges lead to a vertex that finished later.
+        self.connections
+            .iter()
+            .map(|c| post[c.to] > post[c.from])
+            .collect()
+    }
+
+    /// Assigns each vertex to the layer given by the longest path that reaches it.
+    fn layers(&self, reversed: &[bool]) -> Vec<usize> {
+        let n = self.vertices.len();
+        let mut layer = vec![0; n];
+        // Longest path over an acyclic orientation converges after at most n passes.
+        for _ in 0..n {
+            let mut changed = false;
+            for (c, &reversed) in self.connections.iter().zip(reversed) {
+                let (upper, lower) = if reversed {
+                    (c.to, c.from)
+                } else {
+                    (c.from, c.to)
+                };
+                if upper != lower && layer[lower] < layer[upper] + 1 {
+                    layer[lower] = layer[upper] + 1;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        layer
+    }
+
+    /// The chain of clusters from the outermost down to `cluster`.
+    fn path(&self, mut cluster: Option<usize>) -> Vec<usize> {
+        let mut path = Vec::new();
+        while let Some(c) = cluster {
+            path.push(c);
+            cluster = self.clusters[c].parent;
+        }
+        path.reverse();
+        path
+    }
+
+    fn cluster(&self, item: Item, clusters: &[Option<usize>]) -> Option<usize> {
+        match item {
+            Item::Vertex(v) => self.vertices[v].cluster,
+            Item::Dummy(c) => clusters[c],
+        }
+    }
+
+    /// The number of nested cluster borders that surround the members of `cluster`, itself
+    /// included.
+    fn height(&self, cluster: usize) -> usize {
+        1 + (0..self.clusters.len())
+            .filter(|&c| self.clusters[c].parent == Some(cluster))
+            .map(|c| self.height(c))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Places the items that belong directly to `cluster` in every layer, followed by the bands
+    /// of its child clusters, starting at column `left`. Returns the last column used.
+    fn place_columns(
+        &self,
+        cluster: Option<usize>,
+        left: usize,
+        columns: &Columns,
+        placements: &mut [Vec<Placement>],
+        bands: &mut [Option<(usize, usize)>],
+    ) -> Option<usize> {
+        let Columns {
+            layers,
+            widths,
+            dummies,
+            room,
+        } = *columns;
+        let rows: Vec<Vec<usize>> = layers
+            .iter()
+            .map(|items| {
+                (0..items.len())
+                    .filter(|&i| self.cluster(items[i], dummies) == cluster)
+                    .collect()
+            })
+            .collect();
+        let width = |l: usize| {
+            rows[l].iter().map(|&i| widths[l][i]).sum::<usize>()
+                + 2 * rows[l].len().saturating_sub(1)
+        };
+        let region = (0..layers.len()).map(width).max().unwrap_or(0);
+        let mut last = None;
+        for (l, row) in rows.iter().enumerate() {
+            if row.is_empty() {
+                continue;
+            }
+            let mut column = left + (region - width(l)) / 2;
+            for &i in row {
+                placements[l][i].left = column;
+                placements[l][i].right = column + widths[l][i] - 1;
+                column += widths[l][i] + 2;
+            }
+            last = Some(left + region - 1);
+        }
+        let children: Vec<usize> = (0..self.clusters.len())
+            .filter(|&c| self.clusters[c].parent == cluster)
+            .collect();
+        for child in children {
+            let start = last.map(|last| last + 2).unwrap_or(left);
+            if let Some(inner) =
+                self.place_columns(Some(child), start + 2, columns, placements, bands)
+            {
+                bands[child] = Some((start, inner + 2 + room[child]));
+                last = Some(inner + 2 + room[child]);
+            }
+        }
+        last
+    }
+
+    /// Orders the items of each layer by the barycenter of their neighbours, sweeping down and up
+    /// the layers and keeping the order with the fewest crossings. The members of a cluster are
+    /// kept together.
+    fn order(
+        &self,
+        layers: &[Vec<Item>],
+        segments: &[Segment],
+        dummies: &[Option<usize>],
+    ) -> Vec<Vec<usize>> {
+        let paths: Vec<Vec<Vec<usize>>> = layers
+            .iter()
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|&item| self.path(self.cluster(item, dummies)))
+                    .collect()
+            })
+            .collect();
+        let mut order: Vec<Vec<usize>> = layers
+            .iter()
+            .map(|items| (0..items.len()).collect())
+            .collect();
+        for (l, order) in order.iter_mut().enumerate() {
+            order.sort_by_key(|&i| paths[l][i].clone());
+        }
+        let positions = |order: &[Vec<usize>]| -> Vec<Vec<usize>> {
+            order
+                .iter()
+                .map(|order| {
+                    let mut position = vec![0; order.len()];
+                    for (p, &i) in order.iter().enumerate() {
+                        position[i] = p;
+                    }
+                    position
+                })
+                .collect()
+        };
+        let mut best = order.clone();
+        let mut fewest = crossings(segments, &positions(&order));
+        for sweep in 0..SWEEPS {
+            let down = sweep % 2 == 0;
+            let range: Vec<usize> = if down {
+                (1..layers.len()).collect()
+            } else {
+                (0..layers.len().saturating_sub(1)).rev().collect()
+            };
+            for l in range {
+                let position = positions(&order);
+                let mut sums = vec![(0, 0); layers[l].len()];
+                for segment in segments {
+                    if down && segment.layer + 1 == l {
+                        let sum = &mut sums[segment.lower];
+                        sum.0 += position[l - 1][segment.upper];
+                        sum.1 += 1;
+                    } else if !down && segment.layer == l {
+                        let sum = &mut sums[segment.upper];
+                        sum.0 += position[l + 1][segment.lower];
+                        sum.1 += 1;
+                    }
+                }
+                // Items without neighbours stay where they are.
+                let barycenter = |i: usize| match sums[i] {
+                    (_, 0) => position[l][i] * SCALE,
+                    (sum, n) => sum * SCALE / n,
+                };
+                order[l].sort_by_key(|&i| (paths[l][i].clone(), barycenter(i)));
+            }
+            let count = crossings(segments, &positions(&order));
+            if count < fewest {
+                fewest = count;
+                best = order.clone();
+            }
+        }
+        best
+    }
+
+    /// Draws the graph as boxes connected by orthogonal wires.
+    pub fn render(&self) -> Result<Graph, Error> {
+        // Edges that run up or left are drawn as the reversed edges running down or right.
+        let flipped = match self.direction {
+            Direction::Up => Some(Direction::Down),
+            Direction::Left => Some(Direction::Right),
+            _ => None,
+        };
+        if let Some(direction) = flipped {
+            let mut layout = self.clone();
+            layout.direction = direction;
+            for c in &mut layout.connections {
+                swap(&mut c.from, &mut c.to);
+                swap(&mut c.directed, &mut c.backward);
+            }
+            return layout.render();
+        }
+        let mut room = Room {
+            items: HashMap::new(),
+            clusters: vec![0; self.clusters.len()],
+        };
+        let mut pass = 0;
+        loop {
+            pass += 1;
+            let (canvas, fits) = self.draw(&mut room, pass == WIDENINGS);
+            if fits || pass == WIDENINGS {
+                return canvas.to_graph();
+            }
+        }
+    }
+
+    /// Draws the graph onto a canvas, with the room given to labels so far. Where a label does
+    /// not fit, more room is made for it and false is returned, unless this is the `last` try,
+    /// in which case the label is written wherever it can be.
+    fn draw(&self, room: &mut Room, last: bool) -> (Canvas, bool) {
+        let across = self.direction == Direction::Right;
+        let reversed = self.reversed();
+        let layer = self.layers(&reversed);
+        let depth = layer.iter().cloned().max().map(|l| l + 1).unwrap_or(0);
+
+        // Break connections into segments between consecutive layers.
+        let mut layers: Vec<Vec<Item>> = vec![Vec::new(); depth];
+        for (v, &l) in layer.iter().enumerate() {
+            layers[l].push(Item::Vertex(v));
+        }
+        let mut dummies = vec![None; self.connections.len()];
+        let mut chains = Vec::with_capacity(self.connections.len());
+        for (i, c) in self.connections.iter().enumerate() {
+            let (upper, lower) = if reversed[i] {
+                (c.to, c.from)
+            } else {
+                (c.from, c.to)
+            };
+            if upper == lower {
+                chains.push(Vec::new());
+                continue;
+            }
+            let (a, b) = (
+                self.path(self.vertices[upper].cluster),
+                self.path(self.vertices[lower].cluster),
+            );
+            dummies[i] = a
+                .iter()
+                .zip(&b)
+                .take_while(|(a, b)| a == b)
+                .last()
+                .map(|(&a, _)| a);
+            let mut chain = vec![(
+                layer[upper],
+                layers[layer[upper]]
+                    .iter()
+                    .position(|&item| item == Item::Vertex(upper))
+                    .unwrap(),
+            )];
+            let crossed = layers
+                .iter_mut()
+                .enumerate()
+                .take(layer[lower])
+                .skip(layer[upper] + 1);
+            for (l, items) in crossed {
+                items.push(Item::Dummy(i));
+                chain.push((l, items.len() - 1));
+            }
+            chain.push((
+                layer[lower],
+                layers[layer[lower]]
+                    .iter()
+                    .position(|&item| item == Item::Vertex(lower))
+                    .unwrap(),
+            ));
+            chains.push(chain);
+        }
+
+        let segments: Vec<Segment> = chains
+            .iter()
+            .enumerate()
+            .flat_map(|(connection, chain)| {
+                chain.windows(2).map(move |pair| Segment {
+                    connection,
+                    layer: pair[0].0,
+                    upper: pair[0].1,
+                    lower: pair[1].1,
+                })
+            })
+            .collect();
+        let order = self.order(&layers, &segments, &dummies);
+        let mut position = vec![Vec::new(); depth];
+        for l in 0..depth {
+            position[l] = vec![0; layers[l].len()];
+            for (p, &i) in order[l].iter().enumerate() {
+                position[l][i] = p;
+            }
+        }
+
+        // Ports along the bottom and top of each vertex, ordered by where the other end lies.
+        let mut bottoms: Vec<Vec<Vec<usize>>> = layers
+            .iter()
+            .map(|items| vec![Vec::new(); items.len()])
+            .collect();
+        let mut tops = bottoms.clone();
+        for (s, segment) in segments.iter().enumerate() {
+            bottoms[segment.layer][segment.upper].push(s);
+            tops[segment.layer + 1][segment.lower].push(s);
+        }
+        for segments_of in &mut bottoms {
+            for ports in segments_of.iter_mut() {
+                ports.sort_by_key(|&s| position[segments[s].layer + 1][segments[s].lower]);
+            }
+        }
+        for segments_of in &mut tops {
+            for ports in segments_of.iter_mut() {
+                ports.sort_by_key(|&s| position[segments[s].layer][segments[s].upper]);
+            }
+        }
+
+        let label_width = |v: usize| {
+            self.vertices[v]
+                .label
+                .iter()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0)
+        };
+        // The size of a vertex across the layers, and along them.
+        let width = |l: usize, item: Item, ports: usize| {
+            room.items.get(&(l, item)).cloned().unwrap_or(0)
+                + match item {
+                    Item::Dummy(_) => 1,
+                    Item::Vertex(v) if across => {
+                        max(self.vertices[v].label.len() + 2, 2 * ports + 3)
+                    }
+                    Item::Vertex(v) => max(label_width(v) + 4, 2 * ports + 3),
+                }
+        };
+        let length = |v: usize| {
+            if across {
+                label_width(v) + 4
+            } else {
+                self.vertices[v].label.len() + 2
+            }
+        };
+        let ordered: Vec<Vec<Item>> = (0..depth)
+            .map(|l| order[l].iter().map(|&i| layers[l][i]).collect())
+            .collect();
+        let widths: Vec<Vec<usize>> = (0..depth)
+            .map(|l| {
+                order[l]
+                    .iter()
+                    .map(|&i| width(l, layers[l][i], max(bottoms[l][i].len(), tops[l][i].len())))
+                    .collect()
+            })
+            .collect();
+
+        // Columns.
+        let mut ordered_placements: Vec<Vec<Placement>> = ordered
+            .iter()
+            .map(|items| vec![Placement::default(); items.len()])
+            .collect();
+        let mut bands = vec![None; self.clusters.len()];
+        self.place_columns(
+            None,
+            0,
+            &Columns {
+                layers: &ordered,
+                widths: &widths,
+                dummies: &dummies,
+                room: &room.clusters,
+            },
+            &mut ordered_placements,
+            &mut bands,
+        );
+        let mut placements: Vec<Vec<Placement>> = (0..depth)
+            .map(|l| vec![Placement::default(); layers[l].len()])
+            .collect();
+        for l in 0..depth {
+            for (p, &i) in order[l].iter().enumerate() {
+                placements[l][i] = ordered_placements[l][p];
+            }
+        }
+
+        // Spread the ports of each segment along the bottom and top of the items it connects. A
+        // dummy is passed through in its first column, leaving the rest as room for a label.
+        let port = |ports: &Vec<usize>, placement: &Placement, item: Item, s: usize| {
+            let n = ports.len();
+            let j = ports.iter().position(|&other| other == s).unwrap();
+            match item {
+                Item::Dummy(_) => placement.left,
+                Item::Vertex(_) => {
+                    placement.left + (j + 1) * (placement.right - placement.left) / (n + 1)
+                }
+            }
+        };
+        let columns: Vec<(usize, usize)> = segments
+            .iter()
+            .enumerate()
+            .map(|(s, segment)| {
+                let l = segment.layer;
+                (
+                    port(
+                        &bottoms[l][segment.upper],
+                        &placements[l][segment.upper],
+                        layers[l][segment.upper],
+                        s,
+                    ),
+                    port(
+                        &tops[l + 1][segment.lower],
+                        &placements[l + 1][segment.lower],
+                        layers[l + 1][segment.lower],
+                        s,
+                    ),
+                )
+            })
+            .collect();
+
+        // The layers spanned by each cluster.
+        let mut spans: Vec<Option<(usize, usize)>> = vec![None; self.clusters.len()];
+        for (l, items) in layers.iter().enumerate() {
+            for &item in items {
+                for c in self.path(self.cluster(item, &dummies)) {
+                    spans[c] = Some(match spans[c] {
+                        Some((first, last)) => (first.min(l), last.max(l)),
+                        None => (l, l),
+                    });
+                }
+            }
+        }
+        let heights: Vec<usize> = (0..self.clusters.len()).map(|c| self.height(c)).collect();
+        let top_pad = |l: usize| {
+            (0..self.clusters.len())
+                .filter(|&c| spans[c].map(|(first, _)| first) == Some(l))
+                .map(|c| 2 * heights[c] + 1)
+                .max()
+                .unwrap_or(0)
+        };
+        let bottom_pad = |l: usize| {
+            (0..self.clusters.len())
+                .filter(|&c| spans[c].map(|(_, last)| last) == Some(l))
+                .map(|c| heights[c] + 1)
+                .max()
+                .unwrap_or(0)
+        };
+
+        // Rows, and a track in the channel below each layer for each segment leaving it.
+        let mut tracks = vec![0; segments.len()];
+        let mut band_tops = vec![0; depth];
+        let mut band_bottoms = vec![0; depth];
+        let mut rooms = vec![0; depth];
+        let mut row = 0;
+        for l in 0..depth {
+            row += top_pad(l);
+            band_tops[l] = row;
+            let tallest = (0..layers[l].len())
+                .map(|i| match layers[l][i] {
+                    Item::Vertex(v) => length(v),
+                    Item::Dummy(_) => 1,
+                })
+                .max()
+                .unwrap_or(1);
+            for (i, &item) in layers[l].iter().enumerate() {
+                placements[l][i].top = row;
+                placements[l][i].bottom = match item {
+                    Item::Vertex(v) => row + length(v) - 1,
+                    Item::Dummy(_) => row + tallest - 1,
+                };
+            }
+            band_bottoms[l] = row + tallest - 1;
+            row = band_bottoms[l] + bottom_pad(l) + 1;
+
+            let channel: Vec<usize> = (0..segments.len())
+                .filter(|&s| segments[s].layer == l)
+                .collect();
+            for (t, s) in tracks_of(&channel, &columns).into_iter().enumerate() {
+                tracks[s] = row + 1 + t;
+            }
+            row += channel.len() + 2;
+            // Across the canvas, the labels of the channel are written after its tracks.
+            if across {
+                rooms[l] = row;
+                row += channel
+                    .iter()
+                    .map(|&s| segments[s].connection)
+                    .filter(|&c| {
+                        let chain = &chains[c];
+                        chain[(chain.len() - 1) / 2].0 == l
+                    })
+                    .filter_map(|c| self.connections[c].label.as_ref())
+                    .map(|label| label.chars().count() + 1)
+                    .max()
+                    .unwrap_or(0);
+            }
+        }
+
+        let mut pen = Pen {
+            canvas: Canvas::new(),
+            across,
+            wires: HashMap::new(),
+        };
+        for (l, items) in layers.iter().enumerate() {
+            for (i, &item) in items.iter().enumerate() {
+                let p = placements[l][i];
+                match item {
+                    Item::Vertex(v) => {
+                        let ((top, left), (bottom, right)) =
+                            pen.rectangle((p.top, p.left), (p.bottom, p.right), BOX);
+                        let label = &self.vertices[v].label;
+                        let (height, width) = (bottom - top - 1, right - left - 1);
+                        for (k, line) in label.iter().enumerate() {
+                            let w = line.chars().count();
+                            pen.canvas.text(
+                                top + 1 + (height - label.len()) / 2 + k,
+                                left + 1 + (width - w) / 2,
+                                line,
+                            );
+                        }
+                    }
+                    Item::Dummy(c) => pen.along(
+                        c,
+                        p.left,
+                        band_tops[l],
+                        band_bottoms[l],
+                        self.connections[c].brush,
+                    ),
+                }
+            }
+        }
+
+        // Cluster labels go inside the top left corner, or further along the top.
+        let mut labels = Vec::new();
+        for (c, cluster) in self.clusters.iter().enumerate() {
+            if let (Some((left, right)), Some((first, last))) = (bands[c], spans[c]) {
+                let top = band_tops[first] - 1 - 2 * heights[c];
+                let bottom = band_bottoms[last] + 1 + heights[c];
+                let corners = pen.rectangle((top, left), (bottom, right), CLUSTER);
+                if let Some(ref label) = cluster.label {
+                    let ((top, left), (_, right)) = corners;
+                    let width = label.chars().count();
+                    labels.push(Label {
+                        text: label.clone(),
+                        owner: Owner::Cluster(c, corners.0, corners.1),
+                        places: (left + 2..right.saturating_sub(width))
+                            .map(|column| (top + 1, column))
+                            .collect(),
+                    });
+                }
+            }
+        }
+
+        for (s, segment) in segments.iter().enumerate() {
+            let l = segment.layer;
+            let upper = placements[l][segment.upper];
+            let lower = placements[l + 1][segment.lower];
+            let (x0, x1) = columns[s];
+            let y0 = match layers[l][segment.upper] {
+                Item::Dummy(_) => band_bottoms[l],
+                Item::Vertex(_) => upper.bottom,
+            };
+            let y1 = match layers[l + 1][segment.lower] {
+                Item::Dummy(_) => band_tops[l + 1],
+                Item::Vertex(_) => lower.top,
+            };
+            let track = tracks[s];
+            let reversed = reversed[segment.connection];
+            let chain = &chains[segment.connection];
+            let first = chain[0].0 == l;
+            let last = chain[chain.len() - 1].0 == l + 1;
+            let Connection {
+                directed,
+                backward,
+                brush,
+                ..
+            } = self.connections[segment.connection];
+            let (up, down) = if reversed {
+                (directed, backward)
+            } else {
+                (backward, directed)
+            };
+            let (up, down) = (up && first, down && last);
+            // An arrowhead takes the place of the wire's last cell, next to the box.
+            let (y0, y1) = (y0 + up as usize, y1 - down as usize);
+            let connection = segment.connection;
+            pen.along(connection, x0, y0, track, brush);
+            pen.across(connection, track, x0, x1, brush);
+            pen.along(connection, x1, track, y1, brush);
+            if up {
+                pen.arrowhead(connection, y0, x0, false);
+            }
+            if down {
+                pen.arrowhead(connection, y1, x1, true);
+            }
+            if let Some(ref label) = self.connections[connection].label {
+                if chain[(chain.len() - 1) / 2].0 == l {
+                    // Beside the wire where it turns into the next layer, or above it where it
+                    // enters the next layer, or else anywhere along the wire.
+                    let anchor = if across {
+                        (rooms[l] - 1, x1)
+                    } else {
+                        (track, max(x0, x1))
+                    };
+                    let cells = Some(anchor)
+                        .into_iter()
+                        .chain((track..=y1).map(|row| (row, x1)))
+                        .chain(range(x0, x1).map(|column| (track, column)))
+                        .chain((y0..=track).rev().map(|row| (row, x0)))
+                        .map(|(row, column)| pen.at(row, column));
+                    let width = label.chars().count();
+                    let mut places = Vec::new();
+                    for (line, column) in cells {
+                        places.push((line, column + 2));
+                        if let Some(column) = column.checked_sub(width + 1) {
+                            places.push((line, column));
+                        }
+                        if let Some(line) = line.checked_sub(1) {
+                            places.push((line, column + 1));
+                        }
+                        places.push((line + 1, column + 1));
+                    }
+                    labels.push(Label {
+                        text: label.clone(),
+                        owner: Owner::Connection(connection, (l + 1, layers[l + 1][segment.lower])),
+                        places,
+                    });
+                }
+            }
+        }
+
+        // Each label goes in the first of its places where it is read back as belonging to what
+        // it labels, or else room is made for it.
+        let rectangles = pen.canvas.rectangles();
+        let mut fits = true;
+        for label in labels {
+            let width = label.text.chars().count();
+            let place = label
+                .places
+                .iter()
+                .find(|&&place| pen.fits(&rectangles, &label, place))
+                .or_else(|| {
+                    label.places.iter().find(|&&(line, column)| {
+                        last && column > 0 && pen.canvas.is_span_free(line, column - 1, width + 2)
+                    })
+                });
+            match (place, label.owner) {
+                (Some(&(line, column)), _) => pen.canvas.text(line, column, &label.text),
+                (None, Owner::Connection(_, item)) => {
+                    *room.items.entry(item).or_insert(0) += width + 2;
+                    fits = false;
+                }
+                (None, Owner::Cluster(c, _, _)) => {
+                    room.clusters[c] += width + 2;
+                    fits = false;
+                }
+            }
+        }
+
+        (pen.canvas, fits)
+    }
+}
+
+/// The numbers from one to another, inclusive, smallest first.
+fn range(a: usize, b: usize) -> std::ops::RangeInclusive<usize> {
+    if a < b {
+        a..=b
+    } else {
+        b..=a
+    }
+}
+
+/// Draws on a canvas in the coordinates of a layout whose layers follow each other down the
+/// canvas: rows along the way that the edges run, and columns across it. When the layers follow
+/// each other across the canvas, rows and columns swap places.
+struct Pen {
+    canvas: Canvas,
+    across: bool,
+    /// The connections drawn through each cell of the canvas.
+    wires: HashMap<(usize, usize), Vec<usize>>,
+}
+
+impl Pen {
+    /// The line and column of the canvas at a row and column of the layout.
+    fn at(&self, row: usize, column: usize) -> (usize, usize) {
+        if self.across {
+            (column, row)
+        } else {
+            (row, column)
+        }
+    }
+
+    /// Draws a rectangle, and returns its top left and bottom right corners on the canvas.
+    fn rectangle(
+        &mut self,
+        (top, left): (usize, usize),
+        (bottom, right): (usize, usize),
+        brush: char,
+    ) -> ((usize, usize), (usize, usize)) {
+        let corners = (self.at(top, left), self.at(bottom, right));
+        self.canvas.rectangle(corners.0, corners.1, brush);
+        corners
+    }
+
+    /// Notes that a connection is drawn through a cell.
+    fn mark(&mut self, connection: usize, row: usize, column: usize) {
+        let cell = self.at(row, column);
+        self.wires.entry(cell).or_default().push(connection);
+    }
+
+    /// Draws a wire of a connection the way that the edges run, in the given column.
+    fn along(&mut self, connection: usize, column: usize, from: usize, to: usize, brush: char) {
+        if self.across {
+            self.canvas.horizontal(column, from, to, brush);
+        } else {
+            self.canvas.vertical(column, from, to, vertical(brush));
+        }
+        for row in range(from, to) {
+            self.mark(connection, row, column);
+        }
+    }
+
+    /// Draws a wire of a connection across the way that the edges run, in the given row.
+    fn across(&mut self, connection: usize, row: usize, from: usize, to: usize, brush: char) {
+        if self.across {
+            self.canvas.vertical(row, from, to, vertical(brush));
+        } else {
+            self.canvas.horizontal(row, from, to, brush);
+        }
+        for column in range(from, to) {
+            self.mark(connection, row, column);
+        }
+    }
+
+    /// Draws an arrowhead of a connection that points the way that the edges run, or against
+    /// it.
+    fn arrowhead(&mut self, connection: usize, row: usize, column: usize, forward: bool) {
+        self.mark(connection, row, column);
+        let (line, column) = self.at(row, column);
+        let arrowhead = match (self.across, forward) {
+            (false, true) => '▼',
+            (false, false) => '▲',
+            (true, true) => '▶',
+            (true, false) => '◀',
+        };
+        self.canvas.put(line, column, arrowhead);
+    }
+
+    /// Returns true when a label written at the given place of the canvas would be read back as
+    /// belonging to its owner: nothing is drawn in or right beside it, no other text is close
+    /// enough to run into it, and it is closer to its own wire than to any other one, or for a
+    /// cluster, inside the cluster and away from every wire.
+    fn fits(
+        &self,
+        rectangles: &[Rectangle],
+        label: &Label,
+        (line, column): (usize, usize),
+    ) -> bool {
+        let width = label.text.chars().count();
+        if column == 0 || !self.canvas.is_span_free(line, column - 1, width + 2) {
+            return false;
+        }
+        // Arrowheads are written as text but are read as the ends of wires.
+        let text = |column: usize| {
+            self.canvas.get(line, column).text().is_some()
+                && !self.wires.contains_key(&(line, column))
+        };
+        if column >= 2 && text(column - 2) || text(column + width + 1) {
+            return false;
+        }
+        // The distance to the nearest cell of each wire inside the same box, where a wire only
+        // counts on a border when it passes through it.
+        let inside = innermost(rectangles, (line, column));
+        let mut nearest: HashMap<usize, usize> = HashMap::new();
+        for l in line.saturating_sub(LABEL_DISTANCE)..=line + LABEL_DISTANCE {
+            for c in column.saturating_sub(LABEL_DISTANCE)..column + width + LABEL_DISTANCE {
+                let owners = match self.wires.get(&(l, c)) {
+                    Some(owners) => owners,
+                    None => continue,
+                };
+                let cell = self.canvas.get(l, c);
+                let crossing = cell.north.is_some()
+                    && cell.east.is_some()
+                    && cell.south.is_some()
+                    && cell.west.is_some();
+                let border = rectangles.iter().any(|r| r.is_border(l, c));
+                if innermost(rectangles, (l, c)) != inside || border && !crossing {
+                    continue;
+                }
+                let across = if c < column {
+                    column - c
+                } else {
+                    c.saturating_sub(column + width - 1)
+                };
+                let distance = max(across, line.abs_diff(l));
+                for &owner in owners {
+                    let d = nearest.entry(owner).or_insert(distance);
+                    *d = (*d).min(distance);
+                }
+            }
+        }
+        match label.owner {
+            Owner::Connection(connection, _) => match nearest.get(&connection) {
+                Some(&own) => nearest
+                    .iter()
+                    .all(|(&other, &d)| other == connection || d > own),
+                None => false,
+            },
+            Owner::Cluster(_, top_left, bottom_right) => {
+                nearest.is_empty()
+                    && inside.map(|r| ((r.top, r.left), (r.bottom, r.right)))
+                        == Some((top_left, bottom_right))
+            }
+        }
+    }
+}
+
+/// The innermost rectangle that a cell is inside of.
+fn innermost(rectangles: &[Rectangle], (line, column): (usize, usize)) -> Option<Rectangle> {
+    rectangles
+        .iter()
+        .filter(|r| r.is_inside(line, column))
+        .min_by_key(|r| (r.bottom - r.top) * (r.right - r.left))
+        .cloned()
+}
+
+/// Lays out any petgraph graph whose node weights can be displayed, using each weight as the
+/// label of a box.
+pub fn layered<G>(graph: G) -> Result<Graph, Error>
+where
+    G: IntoNodeReferences + IntoEdgeReferences + NodeIndexable + GraphProp,
+    G::NodeWeight: fmt::Display,
+{
+    let mut layout = Layout::new(graph.is_directed());
+    let mut indices = HashMap::new();
+    for node in graph.node_references() {
+        let index = layout.add_node(&node.weight().to_string(), None);
+        indices.insert(graph.to_index(node.id()), index);
+    }
+    for edge in graph.edge_references() {
+        layout.add_edge(
+            indices[&graph.to_index(edge.source())],
+            indices[&graph.to_index(edge.target())],
+            None,
+        )?;
+    }
+    layout.render()
+}
+
+/// Orders the segments of a channel from the top track down, so that no segment's vertical run
+/// overlaps the run of another segment in the same column.
+fn tracks_of(channel: &[usize], columns: &[(usize, usize)]) -> Vec<usize> {
+    let mut remaining: Vec<usize> = channel.to_vec();
+    remaining.sort_by_key(|&s| columns[s]);
+    let mut ordered = Vec::with_capacity(channel.len());
+    while !remaining.is_empty() {
+        // A segment has to wait for any segment that starts where it ends.
+        let next = remaining
+            .iter()
+            .position(|&s| {
+                !remaining
+                    .iter()
+                    .any(|&other| other != s && columns[other].0 == columns[s].1)
+            })
+            .unwrap_or(0);
+        ordered.push(remaining.remove(next));
+    }
+    ordered
+}
+
+/// Counts the pairs of segments that cross, given the position of each item within its layer.
+fn crossings(segments: &[Segment], position: &[Vec<usize>]) -> usize {
+    let mut count = 0;
+    for (i, a) in segments.iter().enumerate() {
+        for b in &segments[i + 1..] {
+            if a.layer != b.layer {
+                continue;
+            }
+            let upper = position[a.layer][a.upper] as isize - position[b.layer][b.upper] as isize;
+            let lower =
+                position[a.layer + 1][a.lower] as isize - position[b.layer + 1][b.lower] as isize;
+            if upper * lower < 0 {
+                count += 1;
+            }
+        }
+    }
+    count
+}
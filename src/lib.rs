@@ -1,11 +1,38 @@
-#![feature(proc_macro_span)]
-
 extern crate petgraph;
-extern crate proc_macro;
 
 #[cfg(test)]
 mod tests;
 
+mod canvas;
+pub mod check;
+pub mod classify;
+pub mod csv;
+pub mod d2;
+pub mod diff;
+pub mod dot;
+pub mod drawio;
+pub mod embedded;
+pub mod excalidraw;
+pub mod format;
+mod geometry;
+pub mod graphml;
+pub mod html;
+mod json;
+pub mod layout;
+pub mod logical;
+pub mod machine;
+pub mod mermaid;
+pub mod plantuml;
+pub mod raster;
+mod route;
+#[cfg(feature = "serde")]
+pub mod schema;
+pub mod style;
+pub mod svg;
+pub mod tikz;
+pub mod topology;
+mod xml;
+
 use petgraph::graphmap::UnGraphMap;
 use std::cmp::Ordering;
 #[cfg(test)]
@@ -35,15 +62,25 @@ macro_rules! tprintln {
 #[derive(Debug)]
 pub enum Error {
     TODO,
+    Syntax(LineColumn, String),
+    NoRoute,
+    /// There is no node with the given index.
+    NoNode(usize),
+    /// There is no edge with the given index.
+    NoEdge(usize),
+    /// There is no cluster with the given index.
+    NoCluster(usize),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineColumn {
     line: usize,
     column: usize,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Region {
     Center,
     North,
@@ -53,6 +90,7 @@ pub enum Region {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
     character: char,
     source: LineColumn,
@@ -61,6 +99,7 @@ pub struct Node {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Brush {
     NorthSouth(char),
     EastWest(char),
@@ -68,6 +107,15 @@ pub enum Brush {
     NorthWestSouthEast(char),
 }
 
+/// How a stroke is drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weight {
+    Light,
+    Heavy,
+    Double,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct Character {
     character: char,
@@ -82,11 +130,23 @@ struct Character {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Edge(Option<char>, Brush, Option<char>);
 
 #[derive(Debug)]
 pub struct Graph(UnGraphMap<Node, Edge>);
 
+/// A graph that was parsed at compile time, as written out by the `diagram!` macro of
+/// `dottools-macros`.
+#[derive(Debug, Copy, Clone)]
+pub struct Drawing {
+    /// The text that the graph was parsed from.
+    pub text: &'static str,
+    pub nodes: &'static [Node],
+    /// The edges between `nodes`, by index.
+    pub edges: &'static [(usize, usize, Edge)],
+}
+
 /*
 
 '─' | '━' | '│' | '┃' | '┄' | '┅' | '┆' | '┇' | '┈' | '┉' | '┊' | '┋' | '┌' | '┍' | '┎' | '┏' |
@@ -119,11 +179,115 @@ pub struct Graph(UnGraphMap<Node, Edge>);
 "
 */
 
-impl From<proc_macro::LineColumn> for LineColumn {
-    fn from(loc: proc_macro::LineColumn) -> Self {
-        let proc_macro::LineColumn { line, column } = loc;
+impl Brush {
+    /// The character that the stroke is drawn with.
+    pub fn character(&self) -> char {
+        match *self {
+            Brush::NorthSouth(c)
+            | Brush::EastWest(c)
+            | Brush::NorthEastSouthWest(c)
+            | Brush::NorthWestSouthEast(c) => c,
+        }
+    }
+
+    pub fn weight(&self) -> Weight {
+        match self.character() {
+            '━' | '┃' | '┅' | '┇' | '┉' | '┋' | '╍' | '╏' => Weight::Heavy,
+            '═' | '║' | '=' => Weight::Double,
+            _ => Weight::Light,
+        }
+    }
+
+    pub fn is_dashed(&self) -> bool {
+        matches!(
+            self.character(),
+            '┄' | '┅' | '┆' | '┇' | '┈' | '┉' | '┊' | '┋' | '╌' | '╍' | '╎' | '╏'
+        )
+    }
+
+    /// The weight and whether it is dashed, which is all that two strokes need in common to
+    /// look alike.
+    pub fn style(&self) -> (Weight, bool) {
+        (self.weight(), self.is_dashed())
+    }
+}
+
+/// The direction that an arrowhead points in, if the character is one.
+pub(crate) fn arrowhead(c: char) -> Option<Region> {
+    match c {
+        '▲' | '△' | '^' => Some(Region::North),
+        '▶' | '▷' | '►' | '>' => Some(Region::East),
+        '▼' | '▽' => Some(Region::South),
+        '◀' | '◁' | '◄' | '<' => Some(Region::West),
+        _ => None,
+    }
+}
+
+impl LineColumn {
+    pub const fn new(line: usize, column: usize) -> Self {
         LineColumn { line, column }
     }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Node {
+    pub const fn new(
+        character: char,
+        source: LineColumn,
+        visual: LineColumn,
+        region: (Region, Region),
+    ) -> Self {
+        Node {
+            character,
+            source,
+            visual,
+            region,
+        }
+    }
+
+    pub fn character(&self) -> char {
+        self.character
+    }
+
+    /// Where the character is in the text, counting characters.
+    pub fn source(&self) -> LineColumn {
+        self.source
+    }
+
+    /// Where the character is drawn, counting cells.
+    pub fn visual(&self) -> LineColumn {
+        self.visual
+    }
+
+    /// The vertical and then the horizontal region of the cell that the node is in.
+    pub fn region(&self) -> (Region, Region) {
+        self.region
+    }
+}
+
+impl Edge {
+    pub const fn new(start: Option<char>, brush: Brush, end: Option<char>) -> Self {
+        Edge(start, brush, end)
+    }
+
+    pub fn start(&self) -> Option<char> {
+        self.0
+    }
+
+    pub fn brush(&self) -> Brush {
+        self.1
+    }
+
+    pub fn end(&self) -> Option<char> {
+        self.2
+    }
 }
 
 /*
@@ -145,14 +309,12 @@ impl From<char> for Character {
         Character {
             character: c,
             north: match c {
-                '│' | '┃' | '┆' | '┇' | '┊' | '┋' | '|' => Some(c),
-                '└' | '┕' | '┘' | '┙' | '├' | '┝' | '┟' | '┢' | '┣' | '┤' | '┥' | '┧' | '┪'
-                | '┴' | '┵' | '┶' | '┷' | '┼' | '┽' | '┾' | '┿' | '╁' | '╅' | '╆' | '╈' | '╘'
-                | '╛' | '╞' | '╡' | '╧' | '╪' | '╯' | '╰' | '╵' | '╽' => {
-                    Some('│')
-                }
-                '┖' | '┗' | '┚' | '┛' | '┞' | '┠' | '┡' | '┦' | '┨' | '┩' | '┫' | '┸' | '┹'
-                | '┺' | '┻' | '╀' | '╂' | '╃' | '╄' | '╇' | '╉' | '╊' | '╋' | '╹' | '╿' => {
+                '│' | '┃' | '║' | '┆' | '┇' | '┊' | '┋' | '|' => Some(c),
+                '└' | '┕' | '┘' | '┙' | '├' | '┝' | '┟' | '┢' | '┤' | '┥' | '┧' | '┪' | '┴'
+                | '┵' | '┶' | '┷' | '┼' | '┽' | '┾' | '┿' | '╁' | '╅' | '╆' | '╈' | '╘' | '╛'
+                | '╞' | '╡' | '╧' | '╪' | '╯' | '╰' | '╵' | '╽' => Some('│'),
+                '┖' | '┗' | '┚' | '┛' | '┞' | '┠' | '┡' | '┣' | '┦' | '┨' | '┩' | '┫' | '┸'
+                | '┹' | '┺' | '┻' | '╀' | '╂' | '╃' | '╄' | '╇' | '╉' | '╊' | '╋' | '╹' | '╿' => {
                     Some('┃')
                 }
                 '╙' | '╚' | '╜' | '╝' | '╟' | '╠' | '╢' | '╣' | '╨' | '╩' | '╫' | '╬' => {
@@ -187,7 +349,7 @@ impl From<char> for Character {
                 _ => None,
             },
             south: match c {
-                '│' | '┃' | '┆' | '┇' | '┊' | '┋' | '|' => Some(c),
+                '│' | '┃' | '║' | '┆' | '┇' | '┊' | '┋' | '|' => Some(c),
                 '┌' | '┍' | '┐' | '┑' | '├' | '┝' | '┞' | '┡' | '┤' | '┥' | '┦' | '┩' | '┬'
                 | '┭' | '┮' | '┯' | '┼' | '┽' | '┾' | '┿' | '╀' | '╃' | '╄' | '╇' | '╒' | '╕'
                 | '╞' | '╡' | '╤' | '╪' | '╭' | '╮' | '╷' | '╿' => Some('│'),
@@ -230,6 +392,44 @@ impl From<char> for Character {
     }
 }
 
+/// Finds the box drawing character whose arms are exactly the given brushes, if there is one.
+pub(crate) fn junction(
+    north: Option<char>,
+    east: Option<char>,
+    south: Option<char>,
+    west: Option<char>,
+) -> Option<char> {
+    ('─'..='╿').find(|&c| {
+        let character = Character::from(c);
+        character.north == north
+            && character.east == east
+            && character.south == south
+            && character.west == west
+            && character.north_east.is_none()
+            && character.south_east.is_none()
+            && character.south_west.is_none()
+            && character.north_west.is_none()
+    })
+}
+
+/// Returns true for the box drawing characters and the solid arrowheads, which are hardly ever
+/// anything but part of a drawing.
+pub fn is_box_drawing(c: char) -> bool {
+    ('─'..='╿').contains(&c) || matches!(c, '▲' | '▶' | '▼' | '◀')
+}
+
+/// Returns true for a letter or digit. An ASCII stroke between two of them, as in `lib-core` or
+/// `X/Y`, is text.
+pub(crate) fn is_word(c: Option<char>) -> bool {
+    c.is_some_and(char::is_alphanumeric)
+}
+
+/// Returns true for the characters that take part in a drawing, i.e. those that `clean_string`
+/// preserves.
+pub(crate) fn is_drawing(c: char) -> bool {
+    ('─'..='╿').contains(&c) || matches!(c, '|' | '-' | '=' | '/' | '\\' | 'X')
+}
+
 impl Ord for LineColumn {
     fn cmp(&self, other: &Self) -> Ordering {
         match self.line.cmp(&other.line) {
@@ -672,6 +872,51 @@ impl State {
     }
 }
 
+impl Graph {
+    /// Parses `input` like `from_str`, but also keeps every other printable character as an
+    /// isolated node, so that labels and arrowheads survive a round trip through `Display`.
+    pub fn with_text(input: &str) -> Result<Self, Error> {
+        let mut graph: Graph = input.parse()?;
+        let mut source = LineColumn { line: 1, column: 0 };
+        let mut visual = LineColumn { line: 1, column: 0 };
+        for c in input.chars() {
+            if c == '\n' {
+                source.line += 1;
+                source.column = 0;
+                visual.line += 1;
+                visual.column = 0;
+                continue;
+            }
+            if !is_combining_mark(c) {
+                if !c.is_whitespace() && !is_drawing(c) {
+                    graph.add_node(Node {
+                        character: c,
+                        source,
+                        visual,
+                        region: (Region::Center, Region::Center),
+                    });
+                }
+                visual.column += 1;
+            }
+            source.column += 1;
+        }
+        Ok(graph)
+    }
+}
+
+impl From<&Drawing> for Graph {
+    fn from(drawing: &Drawing) -> Self {
+        let mut graph = UnGraphMap::new();
+        for &node in drawing.nodes {
+            graph.add_node(node);
+        }
+        for &(v, u, edge) in drawing.edges {
+            graph.add_edge(drawing.nodes[v], drawing.nodes[u], edge);
+        }
+        Graph(graph)
+    }
+}
+
 impl FromStr for Graph {
     type Err = Error;
 
@@ -782,13 +1027,7 @@ impl fmt::Display for Graph {
 pub fn clean_string(s: &str) -> String {
     let mut t = String::with_capacity(s.len());
     for c in s[..].chars() {
-        if '─' <= c && c <= '╿'
-            || match c {
-                '|' | '-' | '=' | '/' | '\\' | 'X' => true,
-                '\n' => true,
-                _ => false,
-            }
-        {
+        if is_drawing(c) || c == '\n' {
             t.push(c);
         } else if !is_combining_mark(c) {
             t.push(' ');
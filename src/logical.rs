@@ -0,0 +1,620 @@
+//! The logical content of a drawing: the boxes, the wires that connect them and their labels.
+//!
+//! A box is a closed rectangle of strokes, and its label is the text inside it. A wire is a net
+//! of connected strokes outside of the boxes' borders, which may end on the border of a box, in
+//! an arrowhead or in free space. Where two wires cross with a `┼` they are kept apart. Text
+//! close to a wire labels it, any other text outside of the boxes is kept as free text.
+//!
+//! ```
+//! use dottools::{logical::Diagram, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! let diagram = Diagram::from(&graph);
+//! assert_eq!(diagram.blocks[0].label, "A");
+//! assert_eq!(diagram.blocks[1].label, "B");
+//! let connection = &diagram.connections()[0];
+//! assert_eq!((connection.from, connection.to, connection.directed), (0, 1, true));
+//! ```
+
+use crate::canvas::{Canvas, Cell, Rectangle};
+use crate::{arrowhead, Brush, Graph, LineColumn, Region};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// How close, in cells, text has to be to a wire to label it.
+const LABEL_DISTANCE: usize = 2;
+
+/// The horizontal or vertical part of a cell.
+type Part = ((usize, usize), usize);
+
+const HORIZONTAL: usize = 0;
+const VERTICAL: usize = 1;
+
+/// A box, with the positions of its corners.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+    pub top_left: LineColumn,
+    pub bottom_right: LineColumn,
+    /// The text inside the box, with its lines joined by spaces.
+    pub label: String,
+    /// The index of the innermost box that this one is drawn inside.
+    pub parent: Option<usize>,
+    /// The brush of the box's top side.
+    pub brush: Brush,
+}
+
+/// Where a wire ends: on the border of a box, or at the last cell of a wire that ends in free
+/// space.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct End {
+    pub position: LineColumn,
+    /// The index of the box whose border the wire ends on.
+    pub block: Option<usize>,
+    /// Set when the wire ends in an arrowhead.
+    pub arrow: bool,
+}
+
+/// A net of connected strokes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wire {
+    /// The cells that the wire passes through, in reading order.
+    pub cells: Vec<LineColumn>,
+    pub ends: Vec<End>,
+    pub label: Option<String>,
+    /// The brush of the wire's first cell.
+    pub brush: Brush,
+}
+
+/// Text that neither labels a box nor a wire.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Text {
+    pub position: LineColumn,
+    pub text: String,
+}
+
+/// A wire seen as an edge between two boxes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Connection {
+    pub from: usize,
+    pub to: usize,
+    /// Set when the wire has an arrowhead at `to` and none at `from`.
+    pub directed: bool,
+    /// Set when the wire has arrowheads at both `from` and `to`.
+    pub bidirectional: bool,
+    /// The index of the wire.
+    pub wire: usize,
+}
+
+/// The boxes, wires and free text of a drawing.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagram {
+    pub blocks: Vec<Block>,
+    pub wires: Vec<Wire>,
+    pub texts: Vec<Text>,
+}
+
+fn position((line, column): (usize, usize)) -> LineColumn {
+    LineColumn {
+        line: line + 1,
+        column,
+    }
+}
+
+fn step((line, column): (usize, usize), direction: Region) -> Option<(usize, usize)> {
+    match direction {
+        Region::North => line.checked_sub(1).map(|line| (line, column)),
+        Region::East => Some((line, column + 1)),
+        Region::South => Some((line + 1, column)),
+        Region::West => column.checked_sub(1).map(|column| (line, column)),
+        Region::Center => Some((line, column)),
+    }
+}
+
+fn opposite(direction: Region) -> Region {
+    match direction {
+        Region::North => Region::South,
+        Region::East => Region::West,
+        Region::South => Region::North,
+        Region::West => Region::East,
+        Region::Center => Region::Center,
+    }
+}
+
+fn axis(direction: Region) -> usize {
+    match direction {
+        Region::East | Region::West => HORIZONTAL,
+        _ => VERTICAL,
+    }
+}
+
+fn arm(cell: &Cell, direction: Region) -> Option<char> {
+    match direction {
+        Region::North => cell.north,
+        Region::East => cell.east,
+        Region::South => cell.south,
+        Region::West => cell.west,
+        Region::Center => None,
+    }
+}
+
+/// The Chebyshev distance between two cells.
+fn distance((l0, c0): (usize, usize), (l1, c1): (usize, usize)) -> usize {
+    let line = l0.abs_diff(l1);
+    let column = c0.abs_diff(c1);
+    line.max(column)
+}
+
+fn area(rectangle: &Rectangle) -> usize {
+    (rectangle.bottom - rectangle.top) * (rectangle.right - rectangle.left)
+}
+
+struct Reader {
+    canvas: Canvas,
+    rectangles: Vec<Rectangle>,
+    width: usize,
+    /// The direction of each arrowhead.
+    arrows: HashMap<(usize, usize), Region>,
+    /// A union-find forest over the horizontal and vertical part of every cell.
+    parents: Vec<usize>,
+}
+
+impl Reader {
+    fn new(graph: &Graph) -> Self {
+        let canvas = Canvas::from_text(&graph.to_string());
+        let (height, width) = canvas.size();
+        let mut arrows = HashMap::new();
+        for line in 0..height {
+            for column in 0..width {
+                let cell = (line, column);
+                let direction = match canvas.get(line, column).text().and_then(arrowhead) {
+                    Some(direction) => direction,
+                    None => continue,
+                };
+                if canvas.leads_into(line, column, direction) {
+                    arrows.insert(cell, direction);
+                }
+            }
+        }
+        Reader {
+            rectangles: canvas.rectangles(),
+            canvas,
+            width,
+            arrows,
+            parents: (0..height * width * 2).collect(),
+        }
+    }
+
+    fn cell(&self, (line, column): (usize, usize)) -> Cell {
+        self.canvas.get(line, column)
+    }
+
+    /// The innermost box whose border the cell is on.
+    fn border(&self, (line, column): (usize, usize)) -> Option<usize> {
+        (0..self.rectangles.len())
+            .filter(|&i| self.rectangles[i].is_border(line, column))
+            .min_by_key(|&i| area(&self.rectangles[i]))
+    }
+
+    /// The innermost box that the cell is inside of.
+    fn inside(&self, (line, column): (usize, usize)) -> Option<usize> {
+        (0..self.rectangles.len())
+            .filter(|&i| self.rectangles[i].is_inside(line, column))
+            .min_by_key(|&i| area(&self.rectangles[i]))
+    }
+
+    /// Returns true for a cell of text that is not an arrowhead.
+    fn is_text(&self, (line, column): (usize, usize)) -> bool {
+        !self.arrows.contains_key(&(line, column))
+            && self.canvas.is_text(&self.rectangles, line, column)
+    }
+
+    /// Returns true when the given part of a cell belongs to a wire. A border only carries a
+    /// wire where the wire passes straight through it.
+    fn is_wire(&self, cell: (usize, usize), axis: usize) -> bool {
+        if let Some(&direction) = self.arrows.get(&cell) {
+            return self::axis(direction) == axis;
+        }
+        if self.is_text(cell) {
+            return false;
+        }
+        let (line, column) = cell;
+        let c = self.cell(cell);
+        let (before, after) = match axis {
+            HORIZONTAL => (c.west, c.east),
+            _ => (c.north, c.south),
+        };
+        if before.is_none() && after.is_none() {
+            return false;
+        }
+        self.rectangles
+            .iter()
+            .filter(|rectangle| rectangle.is_border(line, column))
+            .all(|rectangle| {
+                let side = match axis {
+                    HORIZONTAL => rectangle.left == column || rectangle.right == column,
+                    _ => rectangle.top == line || rectangle.bottom == line,
+                };
+                let corner = (rectangle.left == column || rectangle.right == column)
+                    && (rectangle.top == line || rectangle.bottom == line);
+                side && !corner && before.is_some() && after.is_some()
+            })
+    }
+
+    fn index(&self, (line, column): (usize, usize), axis: usize) -> usize {
+        (line * self.width + column) * 2 + axis
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a.max(b)] = a.min(b);
+    }
+
+    /// The directions along the axis of a part in which the wire goes on, or ends.
+    fn reaches(&self, cell: (usize, usize), axis: usize) -> Vec<Region> {
+        if let Some(&direction) = self.arrows.get(&cell) {
+            return vec![direction];
+        }
+        let c = self.cell(cell);
+        let directions = match axis {
+            HORIZONTAL => [Region::West, Region::East],
+            _ => [Region::North, Region::South],
+        };
+        directions
+            .iter()
+            .cloned()
+            .filter(|&direction| arm(&c, direction).is_some())
+            .collect()
+    }
+
+    fn read(mut self) -> Diagram {
+        let (height, width) = self.canvas.size();
+        let mut parts = Vec::new();
+        for line in 0..height {
+            for column in 0..width {
+                for &axis in &[HORIZONTAL, VERTICAL] {
+                    if self.is_wire((line, column), axis) {
+                        parts.push(((line, column), axis));
+                    }
+                }
+            }
+        }
+
+        // Join the parts of each cell, unless the cell is a crossing, and then join neighbours.
+        for &(cell, axis) in &parts {
+            let c = self.cell(cell);
+            let crossing =
+                c.north.is_some() && c.east.is_some() && c.south.is_some() && c.west.is_some();
+            if axis == VERTICAL && !crossing && self.is_wire(cell, HORIZONTAL) {
+                let (a, b) = (self.index(cell, HORIZONTAL), self.index(cell, VERTICAL));
+                self.union(a, b);
+            }
+            for direction in self.reaches(cell, axis) {
+                if let Some(next) = step(cell, direction) {
+                    if self.is_wire(next, axis) {
+                        let (a, b) = (self.index(cell, axis), self.index(next, axis));
+                        self.union(a, b);
+                    }
+                }
+            }
+        }
+
+        let mut nets: BTreeMap<usize, Vec<Part>> = BTreeMap::new();
+        for &(cell, axis) in &parts {
+            let root = self.find(self.index(cell, axis));
+            nets.entry(root).or_default().push((cell, axis));
+        }
+
+        let mut wires = Vec::new();
+        for parts in nets.values() {
+            let mut cells: Vec<(usize, usize)> = parts.iter().map(|&(cell, _)| cell).collect();
+            cells.dedup();
+            let mut ends: Vec<End> = Vec::new();
+            for &(cell, axis) in parts {
+                for direction in self.reaches(cell, axis) {
+                    let next = step(cell, direction);
+                    if next.is_some_and(|next| self.is_wire(next, axis)) {
+                        continue;
+                    }
+                    let arrow = self.arrows.contains_key(&cell);
+                    let end = match next.and_then(|next| self.border(next).map(|b| (next, b))) {
+                        Some((next, block)) => End {
+                            position: position(next),
+                            block: Some(block),
+                            arrow,
+                        },
+                        None => End {
+                            position: position(cell),
+                            block: None,
+                            arrow,
+                        },
+                    };
+                    if !ends.contains(&end) {
+                        ends.push(end);
+                    }
+                }
+            }
+            let (cell, axis) = parts[0];
+            let c = self.cell(cell);
+            let brush = match (axis, self.arrows.get(&cell)) {
+                (_, Some(&direction)) => {
+                    let behind = step(cell, opposite(direction)).unwrap();
+                    let brush = arm(&self.cell(behind), direction).unwrap();
+                    if axis == HORIZONTAL {
+                        Brush::EastWest(brush)
+                    } else {
+                        Brush::NorthSouth(brush)
+                    }
+                }
+                (HORIZONTAL, None) => Brush::EastWest(c.east.or(c.west).unwrap()),
+                (_, None) => Brush::NorthSouth(c.south.or(c.north).unwrap()),
+            };
+            wires.push((
+                cells,
+                Wire {
+                    cells: Vec::new(),
+                    ends,
+                    label: None,
+                    brush,
+                },
+            ));
+        }
+        wires.sort_by_key(|(cells, _)| cells[0]);
+
+        let mut blocks: Vec<Block> = self
+            .rectangles
+            .iter()
+            .map(|rectangle| {
+                let top_left = (rectangle.top, rectangle.left);
+                Block {
+                    top_left: position(top_left),
+                    bottom_right: position((rectangle.bottom, rectangle.right)),
+                    label: String::new(),
+                    parent: self.inside(top_left),
+                    // A box with no inside has no top side but its corners.
+                    brush: Brush::EastWest(
+                        self.cell((rectangle.top, rectangle.left + 1))
+                            .east
+                            .or(self.cell(top_left).east)
+                            .unwrap(),
+                    ),
+                }
+            })
+            .collect();
+
+        // Gather the text into runs of words separated by single spaces.
+        let mut runs: Vec<Vec<(usize, usize)>> = Vec::new();
+        for line in 0..height {
+            for column in 0..width {
+                let cell = (line, column);
+                if !self.is_text(cell) {
+                    continue;
+                }
+                match runs.last_mut() {
+                    Some(run)
+                        if run[run.len() - 1].0 == line && run[run.len() - 1].1 + 2 >= column =>
+                    {
+                        run.push(cell)
+                    }
+                    _ => runs.push(vec![cell]),
+                }
+            }
+        }
+
+        let mut texts = Vec::new();
+        for run in runs {
+            let mut text = String::new();
+            for (i, &cell) in run.iter().enumerate() {
+                if i > 0 && run[i - 1].1 + 1 < cell.1 {
+                    text.push(' ');
+                }
+                text.push(self.cell(cell).character());
+            }
+            // Text labels a wire that runs through the same box.
+            let inside = self.inside(run[0]);
+            let near = wires
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (cells, _))| {
+                    run.iter()
+                        .flat_map(|&a| {
+                            cells
+                                .iter()
+                                .filter(|&&b| self.inside(b) == inside)
+                                .map(move |&b| distance(a, b))
+                        })
+                        .min()
+                        .map(|d| (d, i))
+                })
+                .min();
+            match (near, inside) {
+                (Some((d, wire)), _) if d <= LABEL_DISTANCE => {
+                    let label = &mut wires[wire].1.label;
+                    match label {
+                        Some(label) => {
+                            label.push(' ');
+                            label.push_str(&text);
+                        }
+                        None => *label = Some(text),
+                    }
+                }
+                (_, Some(block)) => {
+                    let label = &mut blocks[block].label;
+                    if !label.is_empty() {
+                        label.push(' ');
+                    }
+                    label.push_str(&text);
+                }
+                _ => texts.push(Text {
+                    position: position(run[0]),
+                    text,
+                }),
+            }
+        }
+
+        Diagram {
+            blocks,
+            wires: wires
+                .into_iter()
+                .map(|(cells, wire)| Wire {
+                    cells: cells.into_iter().map(position).collect(),
+                    ..wire
+                })
+                .collect(),
+            texts,
+        }
+    }
+}
+
+impl From<&Graph> for Diagram {
+    fn from(graph: &Graph) -> Self {
+        Reader::new(graph).read()
+    }
+}
+
+impl Wire {
+    /// The pairs of ends that the wire leads between, of those that `keep` accepts, and whether
+    /// each pair is directed: like `Diagram::connections`, from each plain end to each arrowhead,
+    /// or failing that from the first end to each of the others.
+    pub(crate) fn pairs<F>(&self, keep: F) -> Vec<(&End, &End, bool)>
+    where
+        F: Fn(&End) -> bool,
+    {
+        let ends: Vec<&End> = self.ends.iter().filter(|&end| keep(end)).collect();
+        let (targets, sources): (Vec<&End>, Vec<&End>) = ends.iter().partition(|end| end.arrow);
+        if !sources.is_empty() && !targets.is_empty() {
+            sources
+                .iter()
+                .flat_map(|&from| targets.iter().map(move |&to| (from, to, true)))
+                .collect()
+        } else {
+            ends.iter()
+                .skip(1)
+                .map(|&to| (ends[0], to, false))
+                .collect()
+        }
+    }
+
+    /// The cell of the wire that is next to one of its ends.
+    fn cell_at(&self, end: &End) -> Option<(usize, usize)> {
+        let (line, column) = (end.position.line - 1, end.position.column);
+        let cells: Vec<(usize, usize)> = match end.block {
+            None => vec![(line, column)],
+            Some(_) => [Region::North, Region::East, Region::South, Region::West]
+                .iter()
+                .filter_map(|&direction| step((line, column), direction))
+                .collect(),
+        };
+        cells
+            .into_iter()
+            .find(|&cell| self.cells.contains(&position(cell)))
+    }
+
+    /// The way that the wire runs between two of its ends: the cells next to each end, and the
+    /// cells in between where the wire turns. Returns nothing when the ends are not connected.
+    pub fn path(&self, from: &End, to: &End) -> Vec<LineColumn> {
+        let (start, goal) = match (self.cell_at(from), self.cell_at(to)) {
+            (Some(start), Some(goal)) => (start, goal),
+            _ => return Vec::new(),
+        };
+        let cells: HashSet<(usize, usize)> = self
+            .cells
+            .iter()
+            .map(|cell| (cell.line - 1, cell.column))
+            .collect();
+        let mut previous = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        previous.insert(start, start);
+        while let Some(cell) = queue.pop_front() {
+            if cell == goal {
+                break;
+            }
+            for &direction in &[Region::North, Region::East, Region::South, Region::West] {
+                if let Some(next) = step(cell, direction) {
+                    if cells.contains(&next) && !previous.contains_key(&next) {
+                        previous.insert(next, cell);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        if !previous.contains_key(&goal) {
+            return Vec::new();
+        }
+        let mut cells = vec![goal];
+        while cells[cells.len() - 1] != start {
+            cells.push(previous[&cells[cells.len() - 1]]);
+        }
+        cells.reverse();
+        let mut path: Vec<(usize, usize)> = Vec::new();
+        for cell in cells {
+            if path.len() >= 2 {
+                let (a, b) = (path[path.len() - 2], path[path.len() - 1]);
+                if (a.0 == b.0 && b.0 == cell.0) || (a.1 == b.1 && b.1 == cell.1) {
+                    path.pop();
+                }
+            }
+            path.push(cell);
+        }
+        path.into_iter().map(position).collect()
+    }
+}
+
+impl Diagram {
+    /// The boxes that each wire connects. A wire with arrowheads leads from each of its plain
+    /// ends to each of its arrowheads; a wire without any, or with nothing but arrowheads,
+    /// connects its first box to each of the others, both ways when both ends are arrowheads.
+    pub fn connections(&self) -> Vec<Connection> {
+        let mut connections = Vec::new();
+        for (i, wire) in self.wires.iter().enumerate() {
+            for (from, to, directed) in wire.pairs(|end| end.block.is_some()) {
+                let bidirectional = from.arrow && to.arrow;
+                if let (Some(from), Some(to)) = (from.block, to.block) {
+                    connections.push(Connection {
+                        from,
+                        to,
+                        directed,
+                        bidirectional,
+                        wire: i,
+                    });
+                }
+            }
+        }
+        connections
+    }
+
+    /// The innermost box that the position is on or inside of.
+    pub fn block_at(&self, position: LineColumn) -> Option<usize> {
+        (0..self.blocks.len())
+            .filter(|&i| {
+                let Block {
+                    top_left,
+                    bottom_right,
+                    ..
+                } = self.blocks[i];
+                (top_left.line..=bottom_right.line).contains(&position.line)
+                    && (top_left.column..=bottom_right.column).contains(&position.column)
+            })
+            .min_by_key(|&i| {
+                let Block {
+                    top_left,
+                    bottom_right,
+                    ..
+                } = self.blocks[i];
+                (bottom_right.line - top_left.line) * (bottom_right.column - top_left.column)
+            })
+    }
+}
@@ -0,0 +1,257 @@
+//! A drawing read as a finite state machine, and the Rust code that implements it.
+//!
+//! Each box is a state and each arrow between two boxes is a transition, labelled with the
+//! events that take it, separated by commas. The box whose label contains `(start)` is the
+//! initial state; the marker is not part of the state's name.
+//!
+//! ```
+//! use dottools::machine::Machine;
+//!
+//! let machine: Machine = "
+//! ┌──────────────┐  open  ┌──────┐
+//! │ Closed       ├───────▶│ Open │
+//! │ (start)      │◀───────┤      │
+//! └──────────────┘  close └──────┘"
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(machine.states, ["Closed", "Open"]);
+//! assert_eq!(machine.initial, 0);
+//! assert_eq!(machine.events, ["open", "close"]);
+//! assert!(machine.to_rust().contains("(State::Closed, Event::Open) => Some(State::Open),"));
+//! ```
+
+use crate::logical::Diagram;
+use crate::{Error, Graph, LineColumn};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// The marker of the initial state.
+const START: &str = "(start)";
+
+/// A change of state on an event.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transition {
+    pub from: usize,
+    pub event: usize,
+    pub to: usize,
+}
+
+/// The states and events of a machine, by name, and the transitions between the states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Machine {
+    /// The labels of the boxes, without the start marker.
+    pub states: Vec<String>,
+    /// The index of the initial state.
+    pub initial: usize,
+    /// The events, in the order they are first seen.
+    pub events: Vec<String>,
+    pub transitions: Vec<Transition>,
+}
+
+/// A name as an upper camel case identifier, e.g. `waiting for ack` as `WaitingForAck`.
+fn identifier(name: &str) -> String {
+    let mut identifier = String::new();
+    for word in name.split(|c: char| !c.is_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            identifier.extend(first.to_uppercase());
+            identifier.extend(chars);
+        }
+    }
+    if identifier.starts_with(|c: char| c.is_numeric()) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+/// Checks that each name makes a distinct identifier, and not `Self`, the one keyword in
+/// upper camel case.
+fn identifiers(names: &[String], positions: &[LineColumn], kind: &str) -> Result<(), Error> {
+    let mut seen = HashSet::new();
+    for (name, &position) in names.iter().zip(positions) {
+        let identifier = identifier(name);
+        if identifier.is_empty() {
+            return Err(Error::Syntax(
+                position,
+                format!("{} `{}` has no name", kind, name),
+            ));
+        }
+        if identifier == "Self" {
+            return Err(Error::Syntax(
+                position,
+                format!(
+                    "{} `{}` is named `Self`, which is reserved in Rust",
+                    kind, name
+                ),
+            ));
+        }
+        if !seen.insert(identifier.clone()) {
+            return Err(Error::Syntax(
+                position,
+                format!("{} `{}` is named `{}` again", kind, name, identifier),
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl Machine {
+    /// Reads the states and transitions of a diagram. It is an error for a wire between two
+    /// boxes to have no direction or no label, for an event to lead from one state to two
+    /// others, or for there not to be exactly one initial state.
+    pub fn from_diagram(diagram: &Diagram) -> Result<Self, Error> {
+        let mut states = Vec::new();
+        let mut initial = None;
+        for (i, block) in diagram.blocks.iter().enumerate() {
+            let label = &block.label;
+            if label.contains(START) {
+                if initial.is_some() {
+                    return Err(Error::Syntax(
+                        block.top_left,
+                        format!("`{}` marks a second initial state", label),
+                    ));
+                }
+                initial = Some(i);
+            }
+            let name = label.replace(START, " ");
+            let name: Vec<&str> = name.split_whitespace().collect();
+            states.push(name.join(" "));
+        }
+        let initial = initial.ok_or_else(|| {
+            Error::Syntax(
+                LineColumn { line: 1, column: 0 },
+                format!("no state is marked `{}`", START),
+            )
+        })?;
+        let positions: Vec<LineColumn> = diagram.blocks.iter().map(|b| b.top_left).collect();
+        identifiers(&states, &positions, "state")?;
+
+        let mut events: Vec<String> = Vec::new();
+        let mut event_positions = Vec::new();
+        let mut transitions: Vec<Transition> = Vec::new();
+        for connection in diagram.connections() {
+            let wire = &diagram.wires[connection.wire];
+            let position = wire.cells[0];
+            if !connection.directed {
+                return Err(Error::Syntax(
+                    position,
+                    format!(
+                        "the wire between `{}` and `{}` has no arrowhead",
+                        states[connection.from], states[connection.to]
+                    ),
+                ));
+            }
+            let label = wire.label.as_ref().ok_or_else(|| {
+                Error::Syntax(
+                    position,
+                    format!(
+                        "the arrow from `{}` to `{}` has no event",
+                        states[connection.from], states[connection.to]
+                    ),
+                )
+            })?;
+            for name in label
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+            {
+                let event = match events.iter().position(|event| event == name) {
+                    Some(event) => event,
+                    None => {
+                        events.push(name.to_string());
+                        event_positions.push(position);
+                        events.len() - 1
+                    }
+                };
+                let transition = Transition {
+                    from: connection.from,
+                    event,
+                    to: connection.to,
+                };
+                match transitions
+                    .iter()
+                    .find(|t| t.from == transition.from && t.event == transition.event)
+                {
+                    Some(t) if t.to == transition.to => {}
+                    Some(t) => {
+                        return Err(Error::Syntax(
+                            position,
+                            format!(
+                                "`{}` leads from `{}` to both `{}` and `{}`",
+                                name, states[t.from], states[t.to], states[transition.to]
+                            ),
+                        ))
+                    }
+                    None => transitions.push(transition),
+                }
+            }
+        }
+        identifiers(&events, &event_positions, "event")?;
+
+        Ok(Machine {
+            states,
+            initial,
+            events,
+            transitions,
+        })
+    }
+
+    /// Rust source for an `enum State`, whose `INITIAL` is the initial state, an `enum Event`,
+    /// and a `transition` function that returns the state an event leads to, if any.
+    pub fn to_rust(&self) -> String {
+        let states: Vec<String> = self.states.iter().map(|name| identifier(name)).collect();
+        let events: Vec<String> = self.events.iter().map(|name| identifier(name)).collect();
+        let mut rust = String::new();
+
+        rust.push_str("#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]\npub enum State {\n");
+        for state in &states {
+            writeln!(rust, "    {},", state).unwrap();
+        }
+        rust.push_str("}\n\nimpl State {\n");
+        writeln!(
+            rust,
+            "    pub const INITIAL: State = State::{};",
+            states[self.initial]
+        )
+        .unwrap();
+        rust.push_str("}\n\n");
+
+        rust.push_str("#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]\npub enum Event {\n");
+        for event in &events {
+            writeln!(rust, "    {},", event).unwrap();
+        }
+        rust.push_str("}\n\n");
+
+        rust.push_str("pub fn transition(state: State, event: Event) -> Option<State> {\n");
+        if events.is_empty() {
+            rust.push_str("    let _ = state;\n    match event {}\n}\n");
+            return rust;
+        }
+        rust.push_str("    match (state, event) {\n");
+        for transition in &self.transitions {
+            writeln!(
+                rust,
+                "        (State::{}, Event::{}) => Some(State::{}),",
+                states[transition.from], events[transition.event], states[transition.to]
+            )
+            .unwrap();
+        }
+        if self.transitions.len() < states.len() * events.len() {
+            rust.push_str("        _ => None,\n");
+        }
+        rust.push_str("    }\n}\n");
+        rust
+    }
+}
+
+impl FromStr for Machine {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let graph = Graph::with_text(input)?;
+        Machine::from_diagram(&Diagram::from(&graph))
+    }
+}
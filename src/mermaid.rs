@@ -0,0 +1,678 @@
+//! Mermaid flowcharts.
+//!
+//! The boxes and wires of a drawing can be written out as a flowchart, and a flowchart can be
+//! read and laid out as a drawing. Only the parts of the language that matter to a box drawing
+//! are read: nodes and their labels, links and their labels, and subgraphs. Every shape is drawn
+//! as a box, and styling statements are parsed and ignored.
+//!
+//! ```
+//! use dottools::{logical::Diagram, mermaid, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! assert_eq!(
+//!     mermaid::render(&Diagram::from(&graph)),
+//!     "flowchart LR\n    n0[\"A\"]\n    n1[\"B\"]\n    n0 --> n1\n"
+//! );
+//! ```
+
+use crate::layout::{Direction, Layout};
+use crate::logical::Diagram;
+use crate::{Error, LineColumn, Weight};
+use std::fmt::Write;
+use std::str::FromStr;
+
+const INDENT: &str = "    ";
+
+/// Escapes text for use inside double quotes.
+fn escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// Guesses the direction of the flowchart: along the axis that most of its connections run, and
+/// the way along it that most of the directed ones do.
+fn direction(diagram: &Diagram) -> &'static str {
+    let centre = |block: usize| {
+        let block = &diagram.blocks[block];
+        (
+            (block.top_left.line + block.bottom_right.line) as isize,
+            (block.top_left.column + block.bottom_right.column) as isize,
+        )
+    };
+    let (mut down, mut right) = (0, 0);
+    let (mut vertical, mut horizontal) = (0, 0);
+    for connection in diagram.connections() {
+        let ((l0, c0), (l1, c1)) = (centre(connection.from), centre(connection.to));
+        // Cells are about twice as high as they are wide.
+        let (line, column) = (2 * (l1 - l0), c1 - c0);
+        vertical += line.abs();
+        horizontal += column.abs();
+        if connection.directed {
+            down += line;
+            right += column;
+        }
+    }
+    if vertical >= horizontal {
+        if down < 0 {
+            "BT"
+        } else {
+            "TD"
+        }
+    } else if right < 0 {
+        "RL"
+    } else {
+        "LR"
+    }
+}
+
+fn block(diagram: &Diagram, out: &mut String, parent: Option<usize>, depth: usize) {
+    for (i, block) in diagram.blocks.iter().enumerate() {
+        if block.parent != parent {
+            continue;
+        }
+        let indent = INDENT.repeat(depth);
+        let label = if block.label.is_empty() {
+            " ".to_string()
+        } else {
+            escape(&block.label)
+        };
+        if diagram.blocks.iter().any(|child| child.parent == Some(i)) {
+            writeln!(out, "{}subgraph n{} [\"{}\"]", indent, i, label).unwrap();
+            self::block(diagram, out, Some(i), depth + 1);
+            writeln!(out, "{}end", indent).unwrap();
+        } else {
+            writeln!(out, "{}n{}[\"{}\"]", indent, i, label).unwrap();
+        }
+    }
+}
+
+/// Writes the boxes and wires of the diagram as a flowchart. Boxes that contain other boxes
+/// become subgraphs.
+pub fn render(diagram: &Diagram) -> String {
+    let mut out = String::new();
+    writeln!(out, "flowchart {}", direction(diagram)).unwrap();
+    block(diagram, &mut out, None, 1);
+    for connection in diagram.connections() {
+        let wire = &diagram.wires[connection.wire];
+        let link = match (
+            wire.brush.weight(),
+            wire.brush.is_dashed(),
+            connection.directed || connection.bidirectional,
+        ) {
+            (_, true, true) => "-.->",
+            (_, true, false) => "-.-",
+            (Weight::Heavy, _, true) | (Weight::Double, _, true) => "==>",
+            (Weight::Heavy, _, false) | (Weight::Double, _, false) => "===",
+            (_, _, true) => "-->",
+            (_, _, false) => "---",
+        };
+        let link = if connection.bidirectional {
+            format!("<{}", link)
+        } else {
+            link.to_string()
+        };
+        write!(out, "{}n{} {}", INDENT, connection.from, link).unwrap();
+        if let Some(label) = &wire.label {
+            write!(out, "|\"{}\"|", escape(label)).unwrap();
+        }
+        writeln!(out, " n{}", connection.to).unwrap();
+    }
+    out
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shape {
+    /// `id[text]`
+    Rectangle,
+    /// `id(text)`
+    Round,
+    /// `id([text])`
+    Stadium,
+    /// `id[[text]]`
+    Subroutine,
+    /// `id[(text)]`
+    Cylinder,
+    /// `id((text))`
+    Circle,
+    /// `id(((text)))`
+    DoubleCircle,
+    /// `id>text]`
+    Asymmetric,
+    /// `id{text}`
+    Rhombus,
+    /// `id{{text}}`
+    Hexagon,
+    /// `id[/text/]`
+    Parallelogram,
+    /// `id[\text\]`
+    ParallelogramAlt,
+    /// `id[/text\]`
+    Trapezoid,
+    /// `id[\text/]`
+    TrapezoidAlt,
+}
+
+/// How a link is drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stroke {
+    /// `---`
+    Normal,
+    /// `===`
+    Thick,
+    /// `-.-`
+    Dotted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Node {
+    pub id: String,
+    pub label: String,
+    pub shape: Shape,
+    /// Index of the innermost subgraph that the node was first mentioned in, if any.
+    pub subgraph: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edge {
+    /// Index of the node that the link starts from.
+    pub from: usize,
+    /// Index of the node that the link leads to.
+    pub to: usize,
+    pub label: Option<String>,
+    /// Set when the link ends in an arrowhead at `to`.
+    pub directed: bool,
+    /// Set when the link also ends in an arrowhead at `from`, as in `<-->`.
+    pub backward: bool,
+    pub stroke: Stroke,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Subgraph {
+    pub id: String,
+    pub label: Option<String>,
+    /// Index of the enclosing subgraph.
+    pub parent: Option<usize>,
+}
+
+/// A flowchart read from Mermaid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Flowchart {
+    /// The direction that the flowchart declares, such as `TD` or `LR`.
+    pub direction: String,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub subgraphs: Vec<Subgraph>,
+}
+
+/// Statements that only style the flowchart.
+const IGNORED: &[&str] = &[
+    "direction",
+    "classDef",
+    "class",
+    "style",
+    "linkStyle",
+    "click",
+];
+
+/// A link, before the nodes at its ends are known.
+struct Link {
+    label: Option<String>,
+    directed: bool,
+    backward: bool,
+    stroke: Stroke,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    location: LineColumn,
+    flowchart: Flowchart,
+    /// The subgraphs that are open, innermost last.
+    open: Vec<usize>,
+}
+
+impl Parser {
+    fn error<T>(&self, message: &str) -> Result<T, Error> {
+        Err(Error::Syntax(self.location, message.to_string()))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.position + i) == Some(&c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        if c == '\n' {
+            self.location.line += 1;
+            self.location.column = 0;
+        } else {
+            self.location.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| c == ' ' || c == '\t' || c == '\r')
+        {
+            self.bump();
+        }
+    }
+
+    fn is_end_of_statement(&self) -> bool {
+        match self.peek() {
+            None | Some('\n') | Some(';') => true,
+            _ => self.starts_with("%%"),
+        }
+    }
+
+    /// Consumes the rest of the statement and returns it.
+    fn rest(&mut self) -> String {
+        let mut rest = String::new();
+        while !self.is_end_of_statement() {
+            rest.extend(self.bump());
+        }
+        rest
+    }
+
+    fn identifier(&mut self) -> String {
+        let mut id = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '_' {
+                id.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        id
+    }
+
+    fn peek_identifier(&self) -> String {
+        self.chars[self.position..]
+            .iter()
+            .take_while(|c| c.is_alphanumeric() || **c == '_')
+            .collect()
+    }
+
+    /// Reads text up to, and consuming, the given closing sequence.
+    fn text(&mut self, close: &[&'static str]) -> Result<(String, &'static str), Error> {
+        self.skip_spaces();
+        let mut text = String::new();
+        let quoted = self.peek() == Some('"');
+        if quoted {
+            self.bump();
+            loop {
+                match self.bump() {
+                    Some('"') => break,
+                    Some(c) => text.push(c),
+                    None => return self.error("unterminated string"),
+                }
+            }
+            self.skip_spaces();
+        }
+        loop {
+            if let Some(&close) = close.iter().find(|close| self.starts_with(close)) {
+                for _ in close.chars() {
+                    self.bump();
+                }
+                return Ok((label(&text), close));
+            }
+            match self.peek() {
+                Some(c) if !quoted && c != '\n' => {
+                    text.push(c);
+                    self.bump();
+                }
+                _ => return self.error(&format!("expected `{}`", close[0])),
+            }
+        }
+    }
+
+    /// Reads the shape and label that may follow a node's id.
+    fn shape(&mut self) -> Result<Option<(Shape, String)>, Error> {
+        let opening: &[(&str, Shape, &[&'static str])] = &[
+            ("(((", Shape::DoubleCircle, &[")))"]),
+            ("((", Shape::Circle, &["))"]),
+            ("([", Shape::Stadium, &["])"]),
+            ("(", Shape::Round, &[")"]),
+            ("[[", Shape::Subroutine, &["]]"]),
+            ("[(", Shape::Cylinder, &[")]"]),
+            ("[/", Shape::Parallelogram, &["/]", "\\]"]),
+            ("[\\", Shape::ParallelogramAlt, &["\\]", "/]"]),
+            ("[", Shape::Rectangle, &["]"]),
+            ("{{", Shape::Hexagon, &["}}"]),
+            ("{", Shape::Rhombus, &["}"]),
+            (">", Shape::Asymmetric, &["]"]),
+        ];
+        let (open, shape, close) = match opening.iter().find(|(open, _, _)| self.starts_with(open))
+        {
+            Some(&opening) => opening,
+            None => return Ok(None),
+        };
+        for _ in open.chars() {
+            self.bump();
+        }
+        let (label, closed) = self.text(close)?;
+        let shape = match (shape, closed) {
+            (Shape::Parallelogram, "\\]") => Shape::Trapezoid,
+            (Shape::ParallelogramAlt, "/]") => Shape::TrapezoidAlt,
+            (shape, _) => shape,
+        };
+        Ok(Some((shape, label)))
+    }
+
+    /// Reads a reference to a node, declaring the node if it is new, and returns its index.
+    fn node(&mut self) -> Result<usize, Error> {
+        self.skip_spaces();
+        let id = self.identifier();
+        if id.is_empty() {
+            return self.error("expected a node");
+        }
+        let shape = self.shape()?;
+        if self.starts_with(":::") {
+            self.bump();
+            self.bump();
+            self.bump();
+            self.identifier();
+        }
+        let index = match self.flowchart.nodes.iter().position(|node| node.id == id) {
+            Some(index) => {
+                // A node that is mentioned in a subgraph belongs to it.
+                let node = &mut self.flowchart.nodes[index];
+                if node.subgraph.is_none() {
+                    node.subgraph = self.open.last().cloned();
+                }
+                index
+            }
+            None => {
+                self.flowchart.nodes.push(Node {
+                    label: id.clone(),
+                    id,
+                    shape: Shape::Rectangle,
+                    subgraph: self.open.last().cloned(),
+                });
+                self.flowchart.nodes.len() - 1
+            }
+        };
+        if let Some((shape, label)) = shape {
+            let node = &mut self.flowchart.nodes[index];
+            node.shape = shape;
+            node.label = label;
+        }
+        Ok(index)
+    }
+
+    /// Reads one or more nodes joined by `&`.
+    fn nodes(&mut self) -> Result<Vec<usize>, Error> {
+        let mut nodes = vec![self.node()?];
+        loop {
+            self.skip_spaces();
+            if self.peek() != Some('&') {
+                return Ok(nodes);
+            }
+            self.bump();
+            nodes.push(self.node()?);
+        }
+    }
+
+    fn arrow(&mut self) -> String {
+        let mut arrow = String::new();
+        while let Some(c) = self.peek() {
+            if "-=.<>".contains(c) {
+                arrow.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        arrow
+    }
+
+    /// Reads a link, along with its label and whether it ends in an arrowhead at either end, if
+    /// the statement goes on with one.
+    fn link(&mut self) -> Result<Option<Link>, Error> {
+        self.skip_spaces();
+        let location = self.location;
+        let mut arrow = self.arrow();
+        if arrow.is_empty() {
+            return Ok(None);
+        }
+        let mut label = None;
+        // A label inside the link, as in `-- text -->`.
+        let close: &[&'static str] = match arrow.as_str() {
+            "--" | "<--" => &["-->", "---"],
+            "-." | "<-." => &[".->", ".-"],
+            "==" | "<==" => &["==>", "==="],
+            _ => &[],
+        };
+        if !close.is_empty() {
+            let (text, close) = self
+                .text(close)
+                .map_err(|_| Error::Syntax(location, format!("unterminated link `{}`", arrow)))?;
+            label = Some(text);
+            arrow.push_str(close);
+            arrow.push_str(&self.arrow());
+        }
+        let valid = {
+            let body = arrow.trim_start_matches('<').trim_end_matches('>');
+            body.len() >= 2
+                && (body.chars().all(|c| c == '-')
+                    || body.chars().all(|c| c == '=')
+                    || (body.starts_with('-')
+                        && body.ends_with('-')
+                        && body[1..body.len() - 1].chars().all(|c| c == '.')))
+        };
+        if !valid {
+            return Err(Error::Syntax(location, format!("unknown link `{}`", arrow)));
+        }
+        self.skip_spaces();
+        if self.peek() == Some('|') {
+            self.bump();
+            let (text, _) = self.text(&["|"])?;
+            label = Some(text);
+        }
+        let stroke = if arrow.contains('.') {
+            Stroke::Dotted
+        } else if arrow.contains('=') {
+            Stroke::Thick
+        } else {
+            Stroke::Normal
+        };
+        Ok(Some(Link {
+            label: label.filter(|label| !label.is_empty()),
+            directed: arrow.ends_with('>'),
+            backward: arrow.starts_with('<'),
+            stroke,
+        }))
+    }
+
+    fn statement(&mut self) -> Result<(), Error> {
+        let keyword = self.peek_identifier();
+        match keyword.as_str() {
+            "subgraph" => {
+                self.identifier();
+                let rest = self.rest();
+                let rest = rest.trim();
+                let (id, label) = match (rest.find('['), rest.ends_with(']')) {
+                    (Some(open), true) => (
+                        rest[..open].trim().to_string(),
+                        label(rest[open + 1..rest.len() - 1].trim().trim_matches('"')),
+                    ),
+                    _ => (
+                        rest.trim_matches('"').to_string(),
+                        label(rest.trim_matches('"')),
+                    ),
+                };
+                let id = if id.is_empty() {
+                    format!("subGraph{}", self.flowchart.subgraphs.len())
+                } else {
+                    id
+                };
+                self.flowchart.subgraphs.push(Subgraph {
+                    id,
+                    label: Some(label).filter(|label| !label.is_empty()),
+                    parent: self.open.last().cloned(),
+                });
+                self.open.push(self.flowchart.subgraphs.len() - 1);
+            }
+            "end" => {
+                if self.open.pop().is_none() {
+                    return self.error("`end` without `subgraph`");
+                }
+                self.identifier();
+            }
+            keyword if IGNORED.contains(&keyword) => {
+                self.rest();
+            }
+            _ => {
+                let mut from = self.nodes()?;
+                while let Some(link) = self.link()? {
+                    let to = self.nodes()?;
+                    for &f in &from {
+                        for &t in &to {
+                            self.flowchart.edges.push(Edge {
+                                from: f,
+                                to: t,
+                                label: link.label.clone(),
+                                directed: link.directed,
+                                backward: link.backward,
+                                stroke: link.stroke,
+                            });
+                        }
+                    }
+                    from = to;
+                }
+            }
+        }
+        self.skip_spaces();
+        if !self.is_end_of_statement() {
+            return self.error("expected the end of the statement");
+        }
+        Ok(())
+    }
+
+    /// Skips blank lines, comments and statement separators.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+            if self.starts_with("%%") {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+            }
+            match self.peek() {
+                Some('\n') | Some(';') => {
+                    self.bump();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn flowchart(mut self) -> Result<Flowchart, Error> {
+        self.skip_blank();
+        match self.identifier().as_str() {
+            "flowchart" | "graph" => {}
+            _ => return self.error("expected `flowchart` or `graph`"),
+        }
+        self.skip_spaces();
+        let direction = self.identifier();
+        self.flowchart.direction = match direction.as_str() {
+            "" => "TD".to_string(),
+            "TB" | "TD" | "BT" | "LR" | "RL" => direction,
+            _ => return self.error("expected a direction"),
+        };
+        loop {
+            self.skip_blank();
+            if self.peek().is_none() {
+                break;
+            }
+            self.statement()?;
+        }
+        if !self.open.is_empty() {
+            return self.error("expected `end`");
+        }
+        Ok(self.flowchart)
+    }
+}
+
+/// Turns Mermaid's line breaks into new lines.
+fn label(text: &str) -> String {
+    text.replace("<br/>", "\n")
+        .replace("<br />", "\n")
+        .replace("<br>", "\n")
+        .trim()
+        .to_string()
+}
+
+impl FromStr for Flowchart {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Parser {
+            chars: input.chars().collect(),
+            position: 0,
+            location: LineColumn { line: 1, column: 0 },
+            flowchart: Flowchart {
+                direction: String::new(),
+                nodes: Vec::new(),
+                edges: Vec::new(),
+                subgraphs: Vec::new(),
+            },
+            open: Vec::new(),
+        }
+        .flowchart()
+    }
+}
+
+impl Flowchart {
+    /// Lays out the flowchart as labelled boxes connected by orthogonal wires, in the direction
+    /// that it declares, with subgraphs drawn as dashed boxes around their members and dotted
+    /// links drawn with dashed brushes.
+    pub fn to_graph(&self) -> Result<crate::Graph, Error> {
+        let mut layout = Layout::new(true);
+        layout.set_direction(match self.direction.as_str() {
+            "BT" => Direction::Up,
+            "LR" => Direction::Right,
+            "RL" => Direction::Left,
+            _ => Direction::Down,
+        });
+        for subgraph in &self.subgraphs {
+            let label = subgraph
+                .label
+                .clone()
+                .unwrap_or_else(|| subgraph.id.clone());
+            layout.add_cluster(Some(label), subgraph.parent)?;
+        }
+        for node in &self.nodes {
+            layout.add_node(&node.label, node.subgraph);
+        }
+        for edge in &self.edges {
+            let index = layout.add_edge(edge.from, edge.to, edge.label.clone())?;
+            layout.set_directed(index, edge.directed)?;
+            layout.set_backward(index, edge.backward)?;
+            layout.set_brush(
+                index,
+                match edge.stroke {
+                    Stroke::Normal => '─',
+                    Stroke::Thick => '━',
+                    Stroke::Dotted => '┄',
+                },
+            )?;
+        }
+        layout.render()
+    }
+}
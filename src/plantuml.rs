@@ -0,0 +1,75 @@
+//! PlantUML component diagrams.
+//!
+//! ```
+//! use dottools::{logical::Diagram, plantuml, Graph};
+//!
+//! let graph = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! assert_eq!(
+//!     plantuml::render(&Diagram::from(&graph)),
+//!     "@startuml\ncomponent \"A\" as n0\ncomponent \"B\" as n1\nn0 --> n1\n@enduml\n"
+//! );
+//! ```
+
+use crate::logical::Diagram;
+use std::fmt::Write;
+
+const INDENT: &str = "  ";
+
+/// Escapes a name or a label: double quotes as an entity, which PlantUML reads in any text, and
+/// line breaks and backslashes with a backslash.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("&#34;"),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text))
+}
+
+fn block(diagram: &Diagram, out: &mut String, parent: Option<usize>, depth: usize) {
+    for (i, block) in diagram.blocks.iter().enumerate() {
+        if block.parent != parent {
+            continue;
+        }
+        let indent = INDENT.repeat(depth);
+        let label = quote(&block.label);
+        if diagram.blocks.iter().any(|child| child.parent == Some(i)) {
+            writeln!(out, "{}rectangle {} as n{} {{", indent, label, i).unwrap();
+            self::block(diagram, out, Some(i), depth + 1);
+            writeln!(out, "{}}}", indent).unwrap();
+        } else {
+            writeln!(out, "{}component {} as n{}", indent, label, i).unwrap();
+        }
+    }
+}
+
+/// Writes the boxes and wires of the diagram as components connected by links. Boxes that
+/// contain other boxes become rectangles around them, and dashed wires become dotted links.
+pub fn render(diagram: &Diagram) -> String {
+    let mut out = String::from("@startuml\n");
+    block(diagram, &mut out, None, 0);
+    for connection in diagram.connections() {
+        let wire = &diagram.wires[connection.wire];
+        let link = match (wire.brush.is_dashed(), connection.directed) {
+            (true, true) => "..>",
+            (true, false) => "..",
+            (false, true) => "-->",
+            (false, false) => "--",
+        };
+        write!(out, "n{} {} n{}", connection.from, link, connection.to).unwrap();
+        if let Some(label) = &wire.label {
+            write!(out, " : {}", escape(label)).unwrap();
+        }
+        out.push('\n');
+    }
+    out.push_str("@enduml\n");
+    out
+}
@@ -0,0 +1,396 @@
+//! Rendering a drawing as a raster image, without any fonts or libraries from the system.
+//!
+//! Strokes are drawn through the centres of their cells like the vector exports: heavy brushes
+//! twice as thick, double brushes as two parallel strokes and dashed brushes with gaps.
+//! Arrowheads are filled triangles and text is drawn with an embedded 5×7 bitmap font. The image
+//! can be written as a binary PPM or as an uncompressed PNG.
+//!
+//! ```
+//! use dottools::{raster, Graph};
+//!
+//! let graph = Graph::with_text("──▶ done").unwrap();
+//! let image = raster::render(&graph, &raster::Options::default());
+//! assert_eq!((image.width, image.height), (96, 24));
+//! assert!(image.to_png().starts_with(b"\x89PNG\r\n\x1a\n"));
+//! ```
+
+use crate::geometry::{Arrow, Geometry, Stroke};
+use crate::{Graph, Weight};
+
+/// The size of a character cell, in pixels at a scale of one.
+const CELL_WIDTH: usize = 6;
+const CELL_HEIGHT: usize = 12;
+
+/// The glyphs of the printable ASCII characters, from `' '` to `'~'`. Each glyph is five columns
+/// from left to right, with the top row in the lowest bit.
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x5f, 0x00, 0x00],
+    [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7f, 0x14, 0x7f, 0x14],
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12],
+    [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x55, 0x22, 0x50],
+    [0x00, 0x05, 0x03, 0x00, 0x00],
+    [0x00, 0x1c, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1c, 0x00],
+    [0x08, 0x2a, 0x1c, 0x2a, 0x08],
+    [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00],
+    [0x08, 0x08, 0x08, 0x08, 0x08],
+    [0x00, 0x60, 0x60, 0x00, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02],
+    [0x3e, 0x51, 0x49, 0x45, 0x3e],
+    [0x00, 0x42, 0x7f, 0x40, 0x00],
+    [0x42, 0x61, 0x51, 0x49, 0x46],
+    [0x21, 0x41, 0x45, 0x4b, 0x31],
+    [0x18, 0x14, 0x12, 0x7f, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39],
+    [0x3c, 0x4a, 0x49, 0x49, 0x30],
+    [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36],
+    [0x06, 0x49, 0x49, 0x29, 0x1e],
+    [0x00, 0x36, 0x36, 0x00, 0x00],
+    [0x00, 0x56, 0x36, 0x00, 0x00],
+    [0x08, 0x14, 0x22, 0x41, 0x00],
+    [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08],
+    [0x02, 0x01, 0x51, 0x09, 0x06],
+    [0x32, 0x49, 0x79, 0x41, 0x3e],
+    [0x7e, 0x11, 0x11, 0x11, 0x7e],
+    [0x7f, 0x49, 0x49, 0x49, 0x36],
+    [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c],
+    [0x7f, 0x49, 0x49, 0x49, 0x41],
+    [0x7f, 0x09, 0x09, 0x01, 0x01],
+    [0x3e, 0x41, 0x41, 0x51, 0x32],
+    [0x7f, 0x08, 0x08, 0x08, 0x7f],
+    [0x00, 0x41, 0x7f, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3f, 0x01],
+    [0x7f, 0x08, 0x14, 0x22, 0x41],
+    [0x7f, 0x40, 0x40, 0x40, 0x40],
+    [0x7f, 0x02, 0x04, 0x02, 0x7f],
+    [0x7f, 0x04, 0x08, 0x10, 0x7f],
+    [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06],
+    [0x3e, 0x41, 0x51, 0x21, 0x5e],
+    [0x7f, 0x09, 0x19, 0x29, 0x46],
+    [0x46, 0x49, 0x49, 0x49, 0x31],
+    [0x01, 0x01, 0x7f, 0x01, 0x01],
+    [0x3f, 0x40, 0x40, 0x40, 0x3f],
+    [0x1f, 0x20, 0x40, 0x20, 0x1f],
+    [0x7f, 0x20, 0x18, 0x20, 0x7f],
+    [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x03, 0x04, 0x78, 0x04, 0x03],
+    [0x61, 0x51, 0x49, 0x45, 0x43],
+    [0x00, 0x7f, 0x41, 0x41, 0x00],
+    [0x02, 0x04, 0x08, 0x10, 0x20],
+    [0x00, 0x41, 0x41, 0x7f, 0x00],
+    [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40],
+    [0x00, 0x01, 0x02, 0x04, 0x00],
+    [0x20, 0x54, 0x54, 0x54, 0x78],
+    [0x7f, 0x48, 0x44, 0x44, 0x38],
+    [0x38, 0x44, 0x44, 0x44, 0x20],
+    [0x38, 0x44, 0x44, 0x48, 0x7f],
+    [0x38, 0x54, 0x54, 0x54, 0x18],
+    [0x08, 0x7e, 0x09, 0x01, 0x02],
+    [0x08, 0x54, 0x54, 0x54, 0x3c],
+    [0x7f, 0x08, 0x04, 0x04, 0x78],
+    [0x00, 0x44, 0x7d, 0x40, 0x00],
+    [0x20, 0x40, 0x44, 0x3d, 0x00],
+    [0x7f, 0x10, 0x28, 0x44, 0x00],
+    [0x00, 0x41, 0x7f, 0x40, 0x00],
+    [0x7c, 0x04, 0x18, 0x04, 0x78],
+    [0x7c, 0x08, 0x04, 0x04, 0x78],
+    [0x38, 0x44, 0x44, 0x44, 0x38],
+    [0x7c, 0x14, 0x14, 0x14, 0x08],
+    [0x08, 0x14, 0x14, 0x18, 0x7c],
+    [0x7c, 0x08, 0x04, 0x04, 0x08],
+    [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20],
+    [0x3c, 0x40, 0x40, 0x20, 0x7c],
+    [0x1c, 0x20, 0x40, 0x20, 0x1c],
+    [0x3c, 0x40, 0x30, 0x40, 0x3c],
+    [0x44, 0x28, 0x10, 0x28, 0x44],
+    [0x0c, 0x50, 0x50, 0x50, 0x3c],
+    [0x44, 0x64, 0x54, 0x4c, 0x44],
+    [0x00, 0x08, 0x36, 0x41, 0x00],
+    [0x00, 0x00, 0x7f, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00],
+    [0x08, 0x04, 0x08, 0x10, 0x08],
+];
+
+/// The glyph drawn for characters that the font doesn't have: a hollow box.
+const MISSING: [u8; 5] = [0x7f, 0x41, 0x41, 0x41, 0x7f];
+
+/// How a drawing is rasterised.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// The number of pixels to each pixel of the font; a character cell is six by twelve of
+    /// them.
+    pub scale: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { scale: 2 }
+    }
+}
+
+/// A greyscale image, black on white.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// The grey level of each pixel, row by row from the top, where zero is black.
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![0xff; width * height],
+        }
+    }
+
+    /// The grey level of the pixel at `x` from the left and `y` from the top.
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    fn set(&mut self, x: isize, y: isize) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.pixels[y as usize * self.width + x as usize] = 0;
+        }
+    }
+
+    /// Blacks out a square of `size` pixels, centred on a point.
+    fn stamp(&mut self, (x, y): (f64, f64), size: f64) {
+        let half = size / 2.0;
+        let (left, top) = ((x - half).floor() as isize, (y - half).floor() as isize);
+        let size = size.round().max(1.0) as isize;
+        for dy in 0..size {
+            for dx in 0..size {
+                self.set(left + dx, top + dy);
+            }
+        }
+    }
+
+    /// Draws a line, optionally dashed with dashes and gaps a little over twice its width. The
+    /// dashes are laid out from the origin rather than from the start of the line, so that lines
+    /// which continue one another keep to the same pattern.
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, dashed: bool) {
+        let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+        let steps = (length * 2.0).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let t = step as f64 / steps as f64;
+            let point = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+            if dashed && ((point.0 + point.1) / (2.0 * width + 2.0)) as usize % 2 == 1 {
+                continue;
+            }
+            self.stamp(point, width);
+        }
+    }
+
+    /// Fills a triangle.
+    fn triangle(&mut self, a: (f64, f64), b: (f64, f64), c: (f64, f64)) {
+        let side = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+            (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+        };
+        let left = a.0.min(b.0).min(c.0).floor() as isize;
+        let right = a.0.max(b.0).max(c.0).ceil() as isize;
+        let top = a.1.min(b.1).min(c.1).floor() as isize;
+        let bottom = a.1.max(b.1).max(c.1).ceil() as isize;
+        for y in top..bottom {
+            for x in left..right {
+                let p = (x as f64 + 0.5, y as f64 + 0.5);
+                let (ab, bc, ca) = (side(a, b, p), side(b, c, p), side(c, a, p));
+                if (ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0) {
+                    self.set(x, y);
+                }
+            }
+        }
+    }
+
+    /// Writes the image as a binary PPM (`P6`).
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for &grey in &self.pixels {
+            out.extend_from_slice(&[grey, grey, grey]);
+        }
+        out
+    }
+
+    /// Writes the image as an 8-bit greyscale PNG, stored without compression.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // Eight bits of grey, deflate, adaptive filtering and no interlacing.
+        header.extend_from_slice(&[8, 0, 0, 0, 0]);
+        chunk(&mut out, b"IHDR", &header);
+
+        // Each row starts with its filter type, which is none.
+        let mut raw = Vec::with_capacity((self.width + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+        let mut data = vec![0x78, 0x01];
+        let mut blocks = raw.chunks(0xffff).peekable();
+        if blocks.peek().is_none() {
+            data.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+        }
+        while let Some(block) = blocks.next() {
+            let length = block.len() as u16;
+            data.push(if blocks.peek().is_none() { 1 } else { 0 });
+            data.extend_from_slice(&length.to_le_bytes());
+            data.extend_from_slice(&(!length).to_le_bytes());
+            data.extend_from_slice(block);
+        }
+        data.extend_from_slice(&adler32(&raw).to_be_bytes());
+        chunk(&mut out, b"IDAT", &data);
+
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+struct Painter {
+    image: Image,
+    scale: f64,
+}
+
+impl Painter {
+    fn point(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            x * (CELL_WIDTH as f64) * self.scale,
+            y * (CELL_HEIGHT as f64) * self.scale,
+        )
+    }
+
+    fn stroke(&mut self, stroke: &Stroke) {
+        let (from, to) = (self.point(stroke.from), self.point(stroke.to));
+        let dash = stroke.brush.is_dashed();
+        match stroke.brush.weight() {
+            Weight::Light => self.image.line(from, to, self.scale, dash),
+            Weight::Heavy => self.image.line(from, to, 2.0 * self.scale, dash),
+            Weight::Double => {
+                // Offset each side by a stroke width, measured across the stroke.
+                let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+                let (dx, dy) = (
+                    (from.1 - to.1) / length * self.scale,
+                    (to.0 - from.0) / length * self.scale,
+                );
+                for &side in &[1.0, -1.0] {
+                    let (dx, dy) = (dx * side, dy * side);
+                    self.image.line(
+                        (from.0 + dx, from.1 + dy),
+                        (to.0 + dx, to.1 + dy),
+                        self.scale,
+                        dash,
+                    );
+                }
+            }
+        }
+    }
+
+    fn arrow(&mut self, arrow: &Arrow) {
+        let (from, to) = arrow.points();
+        let (from, to) = (self.point(from), self.point(to));
+        let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+        // The base is as wide as most of a cell.
+        let half = 0.4 * CELL_WIDTH as f64 * self.scale;
+        let (dx, dy) = (
+            (from.1 - to.1) / length * half,
+            (to.0 - from.0) / length * half,
+        );
+        self.image
+            .triangle((from.0 + dx, from.1 + dy), (from.0 - dx, from.1 - dy), to);
+    }
+
+    fn character(&mut self, line: usize, column: usize, c: char) {
+        let glyph = match c {
+            ' '..='~' => &FONT[c as usize - ' ' as usize],
+            _ => &MISSING,
+        };
+        let scale = self.scale as isize;
+        // Centre the five by seven glyph in the six by twelve cell.
+        let left = (column * CELL_WIDTH) as isize * scale;
+        let top = ((line - 1) * CELL_HEIGHT + 2) as isize * scale;
+        for (x, bits) in glyph.iter().enumerate() {
+            for y in 0..7 {
+                if bits >> y & 1 == 1 {
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.image
+                                .set(left + x as isize * scale + dx, top + y * scale + dy);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rasterises the drawing.
+pub fn render(graph: &Graph, options: &Options) -> Image {
+    let geometry = Geometry::from_graph(graph);
+    let (lines, columns) = geometry.size;
+    let scale = options.scale.max(1);
+    let mut painter = Painter {
+        image: Image::new(columns * CELL_WIDTH * scale, lines * CELL_HEIGHT * scale),
+        scale: scale as f64,
+    };
+    for stroke in &geometry.strokes {
+        painter.stroke(stroke);
+    }
+    for arrow in &geometry.arrows {
+        painter.arrow(arrow);
+    }
+    for label in &geometry.labels {
+        for (i, c) in label.text.chars().enumerate() {
+            painter.character(label.line, label.column + i, c);
+        }
+    }
+    painter.image
+}
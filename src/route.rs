@@ -0,0 +1,371 @@
+//! Routing new wires through an existing drawing.
+
+use crate::canvas::{Canvas, Rectangle};
+use crate::{Error, Graph, LineColumn};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// The cost of each cell that a wire passes through.
+const STEP: usize = 1;
+/// The additional cost of a bend.
+const BEND: usize = 8;
+/// The additional cost of crossing another wire.
+const CROSSING: usize = 16;
+/// How far beyond the existing drawing a wire may go.
+const MARGIN: usize = 2;
+
+const NORTH: usize = 0;
+const EAST: usize = 1;
+const SOUTH: usize = 2;
+const WEST: usize = 3;
+
+fn step((line, column): (usize, usize), direction: usize) -> Option<(usize, usize)> {
+    match direction {
+        NORTH => line.checked_sub(1).map(|line| (line, column)),
+        EAST => Some((line, column + 1)),
+        SOUTH => Some((line + 1, column)),
+        _ => column.checked_sub(1).map(|column| (line, column)),
+    }
+}
+
+fn arrowhead(direction: usize) -> char {
+    match direction {
+        NORTH => '▲',
+        EAST => '▶',
+        SOUTH => '▼',
+        _ => '◀',
+    }
+}
+
+/// One end of a wire: either a cell on the border of a box, facing away from the box, or a free
+/// cell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct End {
+    cell: (usize, usize),
+    /// The direction that leads away from the box, for a port.
+    outward: Option<usize>,
+}
+
+struct Router {
+    canvas: Canvas,
+    rectangles: Vec<Rectangle>,
+    /// Whether each box has other boxes inside of it, so that wires may pass through it.
+    containers: Vec<bool>,
+    height: usize,
+    width: usize,
+}
+
+impl Router {
+    fn is_border(&self, (line, column): (usize, usize)) -> bool {
+        self.rectangles
+            .iter()
+            .any(|rectangle| rectangle.is_border(line, column))
+    }
+
+    /// Returns true when the cell is inside a box that has no other boxes inside of it.
+    fn is_inside(&self, (line, column): (usize, usize)) -> bool {
+        self.rectangles
+            .iter()
+            .zip(&self.containers)
+            .any(|(rectangle, &container)| !container && rectangle.is_inside(line, column))
+    }
+
+    /// Returns true when a wire heading in `direction` may cross the border of the boxes that
+    /// the cell is on, which it may do straight through a side of a box that contains others.
+    fn is_crossable(&self, (line, column): (usize, usize), direction: usize) -> bool {
+        self.rectangles
+            .iter()
+            .zip(&self.containers)
+            .filter(|(rectangle, _)| rectangle.is_border(line, column))
+            .all(|(rectangle, &container)| {
+                let horizontal = rectangle.top == line || rectangle.bottom == line;
+                let vertical = rectangle.left == column || rectangle.right == column;
+                container
+                    && match direction {
+                        NORTH | SOUTH => horizontal && !vertical,
+                        _ => vertical && !horizontal,
+                    }
+            })
+    }
+
+    /// The ports on the straight sides of a box, which a wire can leave from or arrive at.
+    fn ports(&self, rectangle: &Rectangle) -> Vec<End> {
+        let mut ports = Vec::new();
+        let Rectangle {
+            top,
+            left,
+            bottom,
+            right,
+        } = *rectangle;
+        for column in left + 1..right {
+            ports.push(End {
+                cell: (top, column),
+                outward: Some(NORTH),
+            });
+            ports.push(End {
+                cell: (bottom, column),
+                outward: Some(SOUTH),
+            });
+        }
+        for line in top + 1..bottom {
+            ports.push(End {
+                cell: (line, left),
+                outward: Some(WEST),
+            });
+            ports.push(End {
+                cell: (line, right),
+                outward: Some(EAST),
+            });
+        }
+        ports
+            .into_iter()
+            .filter(|port| self.is_plain_side(port))
+            .collect()
+    }
+
+    /// Returns true when the port is a plain stretch of border, without another wire attached.
+    fn is_plain_side(&self, port: &End) -> bool {
+        let (line, column) = port.cell;
+        let cell = self.canvas.get(line, column);
+        cell.text().is_none()
+            && match port.outward {
+                Some(NORTH) | Some(SOUTH) => {
+                    cell.north.is_none()
+                        && cell.south.is_none()
+                        && cell.east.is_some()
+                        && cell.west.is_some()
+                }
+                _ => {
+                    cell.east.is_none()
+                        && cell.west.is_none()
+                        && cell.north.is_some()
+                        && cell.south.is_some()
+                }
+            }
+    }
+
+    /// Works out what the given position refers to: every port of the box it is inside, the
+    /// port it is on, or else the free cell itself.
+    fn ends(&self, position: LineColumn) -> Result<Vec<End>, Error> {
+        let line = position.line.checked_sub(1).ok_or(Error::NoRoute)?;
+        let cell = (line, position.column);
+        let (line, column) = cell;
+        if let Some(rectangle) = self
+            .rectangles
+            .iter()
+            .filter(|rectangle| rectangle.is_inside(line, column))
+            .min_by_key(|r| (r.bottom - r.top) * (r.right - r.left))
+        {
+            return Ok(self.ports(rectangle));
+        }
+        if let Some(rectangle) = self
+            .rectangles
+            .iter()
+            .find(|rectangle| rectangle.is_border(line, column))
+        {
+            return Ok(self
+                .ports(rectangle)
+                .into_iter()
+                .filter(|port| port.cell == cell)
+                .collect());
+        }
+        if self.canvas.get(line, column).is_empty() {
+            Ok(vec![End {
+                cell,
+                outward: None,
+            }])
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Returns true when a wire heading in `direction` may pass through the cell, and whether
+    /// doing so crosses another wire.
+    fn passable(&self, cell: (usize, usize), direction: usize) -> Option<bool> {
+        let (line, column) = cell;
+        if line >= self.height || column >= self.width {
+            return None;
+        }
+        if self.is_border(cell) {
+            return if self.is_crossable(cell, direction) {
+                Some(true)
+            } else {
+                None
+            };
+        }
+        if self.is_inside(cell) {
+            return None;
+        }
+        let c = self.canvas.get(line, column);
+        if c.is_empty() {
+            // A free cell that an existing stroke points into is left for that stroke.
+            let pointed_at = [NORTH, EAST, SOUTH, WEST].iter().any(|&d| {
+                step(cell, d).is_some_and(|(l, c)| {
+                    let neighbour = self.canvas.get(l, c);
+                    match d {
+                        NORTH => neighbour.south.is_some(),
+                        EAST => neighbour.west.is_some(),
+                        SOUTH => neighbour.north.is_some(),
+                        _ => neighbour.east.is_some(),
+                    }
+                })
+            });
+            return if pointed_at { None } else { Some(false) };
+        }
+        if c.text().is_some() {
+            return None;
+        }
+        let vertical =
+            c.north.is_some() && c.south.is_some() && c.east.is_none() && c.west.is_none();
+        let horizontal =
+            c.east.is_some() && c.west.is_some() && c.north.is_none() && c.south.is_none();
+        match direction {
+            EAST | WEST if vertical => Some(true),
+            NORTH | SOUTH if horizontal => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Finds the cheapest path from any of the starts to any of the goals, returning its cells
+    /// and the direction it arrives in.
+    fn search(
+        &self,
+        starts: &[End],
+        goals: &[End],
+        arrow: bool,
+    ) -> Option<(Vec<(usize, usize)>, usize)> {
+        type State = ((usize, usize), usize);
+        let mut costs: HashMap<State, usize> = HashMap::new();
+        let mut previous: HashMap<State, State> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        for start in starts {
+            let directions = match start.outward {
+                Some(direction) => vec![direction],
+                None => vec![NORTH, EAST, SOUTH, WEST],
+            };
+            for direction in directions {
+                costs.insert((start.cell, direction), 0);
+                heap.push(Reverse((0, start.cell, direction)));
+            }
+        }
+        while let Some(Reverse((cost, cell, direction))) = heap.pop() {
+            if costs.get(&(cell, direction)).is_some_and(|&c| c < cost) {
+                continue;
+            }
+            let state = (cell, direction);
+            if let Some(goal) = goals.iter().find(|goal| self.arrives(goal, state, arrow)) {
+                let mut cells = vec![cell];
+                let mut state = state;
+                while let Some(&p) = previous.get(&state) {
+                    if p.0 != state.0 {
+                        cells.push(p.0);
+                    }
+                    state = p;
+                }
+                cells.reverse();
+                if goal.outward.is_some() && !arrow {
+                    cells.push(goal.cell);
+                }
+                return Some((cells, direction));
+            }
+            // Ports and crossings have to be passed straight through.
+            let straight = self.canvas.get(cell.0, cell.1).is_stroke();
+            for next in &[NORTH, EAST, SOUTH, WEST] {
+                let next = *next;
+                if next == (direction + 2) % 4 || (straight && next != direction) {
+                    continue;
+                }
+                let to = match step(cell, next) {
+                    Some(to) => to,
+                    None => continue,
+                };
+                let crossing = match self.passable(to, next) {
+                    Some(crossing) => crossing,
+                    None => continue,
+                };
+                let cost = cost
+                    + STEP
+                    + if next != direction { BEND } else { 0 }
+                    + if crossing { CROSSING } else { 0 };
+                if costs.get(&(to, next)).is_none_or(|&c| cost < c) {
+                    costs.insert((to, next), cost);
+                    previous.insert((to, next), state);
+                    heap.push(Reverse((cost, to, next)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns true when the wire has reached the goal: either the free cell itself, or the
+    /// cell just outside a port heading into the box.
+    fn arrives(&self, goal: &End, (cell, direction): ((usize, usize), usize), arrow: bool) -> bool {
+        match goal.outward {
+            None => cell == goal.cell,
+            Some(outward) => {
+                direction == (outward + 2) % 4
+                    && step(cell, direction) == Some(goal.cell)
+                    && !(arrow && self.canvas.get(cell.0, cell.1).is_stroke())
+            }
+        }
+    }
+}
+
+impl Graph {
+    /// Draws a new wire between two positions of the drawing, avoiding boxes and text and
+    /// keeping bends and crossings to a minimum. A position inside a box lets the wire attach to
+    /// any side of it, one on a box's border makes it attach there, and any other position must
+    /// be a free cell where the wire ends. When `arrow` is set, the wire ends in an arrowhead.
+    ///
+    /// The graph should have been read with `Graph::with_text` so that the wire avoids labels.
+    /// It is parsed again afterwards, so source positions become visual ones.
+    pub fn route(&mut self, from: LineColumn, to: LineColumn, arrow: bool) -> Result<(), Error> {
+        let canvas = Canvas::from_text(&self.to_string());
+        let (height, width) = canvas.size();
+        let rectangles = canvas.rectangles();
+        let containers = rectangles
+            .iter()
+            .map(|outer| {
+                rectangles
+                    .iter()
+                    .any(|inner| outer.is_inside(inner.top, inner.left))
+            })
+            .collect();
+        let router = Router {
+            rectangles,
+            containers,
+            canvas,
+            height: height + MARGIN,
+            width: width + MARGIN,
+        };
+        let starts = router.ends(from)?;
+        let goals = router.ends(to)?;
+        let (cells, direction) = router
+            .search(&starts, &goals, arrow)
+            .ok_or(Error::NoRoute)?;
+
+        // Keep only the cells where the wire bends.
+        let mut points: Vec<(usize, usize)> = Vec::with_capacity(cells.len());
+        for &cell in &cells {
+            if points.len() >= 2 {
+                let (a, b) = (points[points.len() - 2], points[points.len() - 1]);
+                if (a.0 == b.0 && b.0 == cell.0) || (a.1 == b.1 && b.1 == cell.1) {
+                    points.pop();
+                }
+            }
+            points.push(cell);
+        }
+        let mut canvas = router.canvas;
+        if points.len() == 1 {
+            let (line, column) = points[0];
+            canvas.horizontal(line, column, column, '─');
+        }
+        canvas.polyline(&points, '─');
+        if arrow {
+            let (line, column) = cells[cells.len() - 1];
+            canvas.put(line, column, arrowhead(direction));
+        }
+        *self = canvas.to_graph()?;
+        Ok(())
+    }
+}
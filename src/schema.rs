@@ -0,0 +1,103 @@
+//! Serializing a whole [`Graph`](../struct.Graph.html), with the `serde` feature.
+//!
+//! The other public data types derive `Serialize` and `Deserialize` directly. A graph is written
+//! as a versioned document, described by the JSON schema in [`SCHEMA`](constant.SCHEMA.html):
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "nodes": [
+//!     {"character": "─", "source": {"line": 1, "column": 0},
+//!      "visual": {"line": 1, "column": 0}, "region": ["Center", "West"]},
+//!     {"character": "─", "source": {"line": 1, "column": 0},
+//!      "visual": {"line": 1, "column": 0}, "region": ["Center", "East"]}
+//!   ],
+//!   "edges": [
+//!     {"from": 0, "to": 1, "edge": [null, {"EastWest": "─"}, null]}
+//!   ]
+//! }
+//! ```
+//!
+//! Nodes are written in order, and edges refer to them by index, so that the same drawing is
+//! always written the same way. Documents of any other version are refused.
+
+use crate::{Edge, Graph, Node};
+use petgraph::graphmap::UnGraphMap;
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The version of the documents that are written, and the only one that is read.
+pub const VERSION: u32 = 1;
+
+/// The JSON schema of a document.
+pub const SCHEMA: &str = include_str!("../schema/graph-v1.json");
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    version: u32,
+    nodes: Vec<Node>,
+    edges: Vec<Stroke>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Stroke {
+    from: usize,
+    to: usize,
+    edge: Edge,
+}
+
+impl Serialize for Graph {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut nodes: Vec<Node> = self.nodes().collect();
+        nodes.sort();
+        let index: HashMap<Node, usize> = nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+        let mut edges: Vec<Stroke> = self
+            .all_edges()
+            .map(|(v, u, &edge)| {
+                let (v, u) = (index[&v], index[&u]);
+                Stroke {
+                    from: v.min(u),
+                    to: v.max(u),
+                    edge,
+                }
+            })
+            .collect();
+        edges.sort_by_key(|stroke| (stroke.from, stroke.to));
+        Document {
+            version: VERSION,
+            nodes,
+            edges,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Graph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let document = Document::deserialize(deserializer)?;
+        if document.version != VERSION {
+            return Err(de::Error::custom(format!(
+                "unsupported version {}, expected {}",
+                document.version, VERSION
+            )));
+        }
+        let mut graph = UnGraphMap::new();
+        for &node in &document.nodes {
+            graph.add_node(node);
+        }
+        for stroke in &document.edges {
+            let node =
+                |i: usize| {
+                    document.nodes.get(i).cloned().ok_or_else(|| {
+                        de::Error::custom(format!("edge refers to missing node {}", i))
+                    })
+                };
+            graph.add_edge(node(stroke.from)?, node(stroke.to)?, stroke.edge);
+        }
+        Ok(Graph(graph))
+    }
+}
@@ -0,0 +1,222 @@
+//! Redrawing the strokes of a drawing with another brush.
+//!
+//! Each stroke keeps its arms and whether it is dashed, but takes the weight of the style.
+//! Double lines cannot be dashed, and ASCII has only `-`, `|` and `+`, so dashes are lost in
+//! those styles. Strokes drawn in ASCII are read back with `+` as a junction of whichever of its
+//! neighbours lead into it.
+//!
+//! ```
+//! use dottools::style::{restyle, Style};
+//!
+//! let light = "┌───┐\n│ A ├──▶\n└───┘";
+//! assert_eq!(restyle(light, Style::Heavy), "┏━━━┓\n┃ A ┣━━▶\n┗━━━┛");
+//! assert_eq!(restyle(light, Style::Ascii), "+---+\n| A +-->\n+---+");
+//! assert_eq!(restyle(&restyle(light, Style::Ascii), Style::Light), light);
+//! ```
+
+use crate::canvas::{Canvas, Cell};
+use crate::{is_word, junction};
+use std::str::FromStr;
+use unicode_normalization::char::is_combining_mark;
+
+/// The brushes that strokes can be redrawn with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Style {
+    Ascii,
+    Light,
+    Heavy,
+    Double,
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "ascii" => Ok(Style::Ascii),
+            "light" => Ok(Style::Light),
+            "heavy" => Ok(Style::Heavy),
+            "double" => Ok(Style::Double),
+            _ => Err(format!("unknown style `{}`", name)),
+        }
+    }
+}
+
+/// Returns true for the dashed brushes.
+fn is_dashed(brush: char) -> bool {
+    matches!(
+        brush,
+        '┄' | '┅' | '┆' | '┇' | '┈' | '┉' | '┊' | '┋' | '╌' | '╍' | '╎' | '╏'
+    )
+}
+
+impl Style {
+    /// The brush of an arm in this style, given the brush it had.
+    fn brush(self, brush: char, vertical: bool) -> char {
+        let dashed = is_dashed(brush);
+        match (self, vertical, dashed) {
+            (Style::Ascii, false, _) => '-',
+            (Style::Ascii, true, _) => '|',
+            (Style::Light, false, false) => '─',
+            (Style::Light, true, false) => '│',
+            (Style::Light, false, true) => '┄',
+            (Style::Light, true, true) => '┆',
+            (Style::Heavy, false, false) => '━',
+            (Style::Heavy, true, false) => '┃',
+            (Style::Heavy, false, true) => '┅',
+            (Style::Heavy, true, true) => '┇',
+            (Style::Double, false, _) => '═',
+            (Style::Double, true, _) => '║',
+        }
+    }
+
+    /// The character with the given arms in this style.
+    fn stroke(self, original: Option<char>, arms: [Option<char>; 4]) -> char {
+        let [north, east, south, west] = arms;
+        let brush = |arm: Option<char>, vertical| arm.map(|arm| self.brush(arm, vertical));
+        let arms = (
+            brush(north, true),
+            brush(east, false),
+            brush(south, true),
+            brush(west, false),
+        );
+        if self == Style::Ascii {
+            return match arms {
+                (None, _, None, _) => '-',
+                (_, None, _, None) => '|',
+                _ => '+',
+            };
+        }
+        // A character that already has these arms is kept, so that rounded corners stay round.
+        if let Some(c) = original {
+            let character = crate::Character::from(c);
+            if (
+                character.north,
+                character.east,
+                character.south,
+                character.west,
+            ) == arms
+            {
+                return c;
+            }
+        }
+        let (north, east, south, west) = arms;
+        junction(north, east, south, west).unwrap_or_else(|| match (east, west) {
+            // Double lines have no stubs, which are drawn as whole lines instead.
+            (None, None) => self.brush('│', true),
+            _ => self.brush('─', false),
+        })
+    }
+}
+
+/// The arms of a `+`, which are those of its neighbours that lead into it: strokes with an arm
+/// towards it, other `+`s, and arrowheads that point away from it.
+fn plus(canvas: &Canvas, glyphs: &[Vec<char>], line: usize, column: usize) -> [Option<char>; 4] {
+    let arm = |line: Option<usize>,
+               column: Option<usize>,
+               into: fn(&Cell) -> Option<char>,
+               arrowheads: [char; 2]| {
+        let (line, column) = (line?, column?);
+        let glyph = glyphs.get(line).and_then(|glyphs| glyphs.get(column));
+        match glyph {
+            Some('+') => Some('-'),
+            Some(c) if arrowheads.contains(c) => Some('-'),
+            _ => into(&canvas.get(line, column)),
+        }
+    };
+    [
+        arm(
+            line.checked_sub(1),
+            Some(column),
+            |cell| cell.south,
+            ['▲', '^'],
+        ),
+        arm(Some(line), Some(column + 1), |cell| cell.west, ['▶', '>']),
+        arm(Some(line + 1), Some(column), |cell| cell.north, ['▼', 'v']),
+        arm(
+            Some(line),
+            column.checked_sub(1),
+            |cell| cell.east,
+            ['◀', '<'],
+        ),
+    ]
+}
+
+/// Redraws every stroke, junction and arrowhead in the given style, and leaves text alone.
+pub fn restyle(text: &str, style: Style) -> String {
+    let canvas = Canvas::from_text(text);
+    let glyphs: Vec<Vec<char>> = text
+        .lines()
+        .map(|line| line.chars().filter(|&c| !is_combining_mark(c)).collect())
+        .collect();
+    let glyph = |line: usize, column: Option<usize>| -> Option<char> {
+        glyphs.get(line)?.get(column?).cloned()
+    };
+    let mut out = String::with_capacity(text.len());
+    for (line, text) in text.split('\n').enumerate() {
+        if line > 0 {
+            out.push('\n');
+        }
+        let mut column = 0;
+        for c in text.chars() {
+            if is_combining_mark(c) {
+                out.push(c);
+                continue;
+            }
+            let cell = canvas.get(line, column);
+            let (before, after) = match c {
+                '-' | '=' | '|' => (
+                    glyph(line, column.checked_sub(1)),
+                    glyph(line, Some(column + 1)),
+                ),
+                _ => (None, None),
+            };
+            // Whether the neighbour at a position leads into this cell, along the given arm.
+            let leads = |line: Option<usize>,
+                         column: Option<usize>,
+                         into: fn(&Cell) -> Option<char>| {
+                match (line, column) {
+                    (Some(line), Some(column)) => {
+                        glyph(line, Some(column)) == Some('+')
+                            || into(&canvas.get(line, column)).is_some()
+                    }
+                    _ => false,
+                }
+            };
+            out.push(match c {
+                _ if is_word(before) && is_word(after) => c,
+                '+' => match plus(&canvas, &glyphs, line, column) {
+                    [None, None, None, None] => c,
+                    arms => style.stroke(None, arms),
+                },
+                _ if cell.is_stroke() => {
+                    style.stroke(Some(c), [cell.north, cell.east, cell.south, cell.west])
+                }
+                '▲' | '^' if leads(Some(line + 1), Some(column), |cell| cell.north) => {
+                    ascii('▲', '^', style)
+                }
+                '▶' | '>' if leads(Some(line), column.checked_sub(1), |cell| cell.east) => {
+                    ascii('▶', '>', style)
+                }
+                '▼' | 'v' if leads(line.checked_sub(1), Some(column), |cell| cell.south) => {
+                    ascii('▼', 'v', style)
+                }
+                '◀' | '<' if leads(Some(line), Some(column + 1), |cell| cell.west) => {
+                    ascii('◀', '<', style)
+                }
+                _ => c,
+            });
+            column += 1;
+        }
+    }
+    out
+}
+
+/// One of an arrowhead's two forms, depending on the style.
+fn ascii(arrowhead: char, ascii: char, style: Style) -> char {
+    match style {
+        Style::Ascii => ascii,
+        _ => arrowhead,
+    }
+}
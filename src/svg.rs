@@ -0,0 +1,167 @@
+//! Rendering a drawing as SVG.
+//!
+//! Every brush becomes a stroke through the centres of its cells: heavy brushes are drawn with a
+//! thicker stroke, double brushes as two parallel strokes and dashed brushes with a dash pattern.
+//! Text becomes `<text>` elements and arrowheads become markers at the end of a short stroke.
+//!
+//! ```
+//! use dottools::{svg, Graph};
+//!
+//! let graph = Graph::with_text("──▶ done").unwrap();
+//! let svg = svg::render(&graph, &svg::Options::default());
+//! assert!(svg.contains("marker-end=\"url(#arrow)\""));
+//! assert!(svg.contains(">done</text>"));
+//! ```
+
+use crate::geometry::{Geometry, Stroke};
+use crate::xml::escape;
+use crate::{Graph, Weight};
+use std::fmt::Write;
+
+/// How a drawing is laid out in SVG user units.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// The width of a character cell.
+    pub cell_width: f64,
+    /// The height of a character cell.
+    pub cell_height: f64,
+    /// The width of a light stroke; heavy strokes are twice as wide.
+    pub stroke_width: f64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            cell_width: 8.0,
+            cell_height: 16.0,
+            stroke_width: 1.0,
+        }
+    }
+}
+
+struct Writer<'a> {
+    options: &'a Options,
+    out: String,
+}
+
+impl<'a> Writer<'a> {
+    fn scale(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (x * self.options.cell_width, y * self.options.cell_height)
+    }
+
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), attributes: &str) {
+        let ((x1, y1), (x2, y2)) = (self.scale(from), self.scale(to));
+        writeln!(
+            self.out,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+            x1, y1, x2, y2, attributes
+        )
+        .unwrap();
+    }
+
+    fn stroke(&mut self, stroke: &Stroke) {
+        let Options {
+            cell_width,
+            cell_height,
+            stroke_width,
+        } = *self.options;
+        let mut attributes = String::new();
+        if stroke.brush.weight() == Weight::Heavy {
+            write!(attributes, r#" stroke-width="{}""#, stroke_width * 2.0).unwrap();
+        }
+        if stroke.brush.is_dashed() {
+            let ((x1, y1), (x2, y2)) = (stroke.from, stroke.to);
+            let dash = if x1 == x2 {
+                cell_height / 4.0
+            } else if y1 == y2 {
+                cell_width / 4.0
+            } else {
+                (cell_width + cell_height) / 8.0
+            };
+            write!(attributes, r#" stroke-dasharray="{} {}""#, dash, dash).unwrap();
+        }
+        if stroke.brush.weight() == Weight::Double {
+            // Offset each side by a stroke width, measured across the stroke.
+            let ((x1, y1), (x2, y2)) = (self.scale(stroke.from), self.scale(stroke.to));
+            let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            let (dx, dy) = (
+                (y1 - y2) / length * stroke_width,
+                (x2 - x1) / length * stroke_width,
+            );
+            let (dx, dy) = (dx / cell_width, dy / cell_height);
+            let ((x1, y1), (x2, y2)) = (stroke.from, stroke.to);
+            self.line((x1 + dx, y1 + dy), (x2 + dx, y2 + dy), &attributes);
+            self.line((x1 - dx, y1 - dy), (x2 - dx, y2 - dy), &attributes);
+        } else {
+            self.line(stroke.from, stroke.to, &attributes);
+        }
+    }
+}
+
+/// Renders the drawing as a standalone SVG document.
+pub fn render(graph: &Graph, options: &Options) -> String {
+    let geometry = Geometry::from_graph(graph);
+    let (height, width) = geometry.size;
+    let (width, height) = (
+        width as f64 * options.cell_width,
+        height as f64 * options.cell_height,
+    );
+    let mut writer = Writer {
+        options,
+        out: String::new(),
+    };
+    writeln!(
+        writer.out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+        width, height
+    )
+    .unwrap();
+    if !geometry.arrows.is_empty() {
+        writer.out.push_str(concat!(
+            "<defs>\n",
+            r#"<marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" "#,
+            r#"markerWidth="6" markerHeight="6" orient="auto">"#,
+            "\n",
+            r#"<path d="M 0 0 L 10 5 L 0 10 z"/>"#,
+            "\n</marker>\n</defs>\n",
+        ));
+    }
+    writeln!(
+        writer.out,
+        r#"<g fill="none" stroke="black" stroke-width="{}" stroke-linecap="square">"#,
+        options.stroke_width
+    )
+    .unwrap();
+    for stroke in &geometry.strokes {
+        writer.stroke(stroke);
+    }
+    for arrow in &geometry.arrows {
+        let (from, to) = arrow.points();
+        writer.line(from, to, r#" marker-end="url(#arrow)""#);
+    }
+    writer.out.push_str("</g>\n");
+    if !geometry.labels.is_empty() {
+        writeln!(
+            writer.out,
+            r#"<g font-family="monospace" font-size="{}" dominant-baseline="central">"#,
+            options.cell_height * 0.75
+        )
+        .unwrap();
+        for label in &geometry.labels {
+            let (x, y) = writer.scale((label.column as f64, label.line as f64 - 0.5));
+            writeln!(
+                writer.out,
+                r#"<text x="{}" y="{}" textLength="{}">{}</text>"#,
+                x,
+                y,
+                label.text.chars().count() as f64 * options.cell_width,
+                escape(&label.text)
+            )
+            .unwrap();
+        }
+        writer.out.push_str("</g>\n");
+    }
+    writer.out.push_str("</svg>\n");
+    writer.out
+}
@@ -0,0 +1,47 @@
+use crate::classify::{confidence, THRESHOLD};
+
+#[test]
+fn drawings() {
+    for drawing in &[
+        "┌─┐\n└─┘",
+        "┌───┬───┐\n│ a │ b │\n└───┴───┘",
+        "   │\n───┼───\n   │",
+        "A ──▶ B",
+        "┌──\n└─┘",
+        "+---+    +---+\n| A |--->| B |\n+---+    +---+",
+    ] {
+        assert!(confidence(drawing) >= THRESHOLD, "{}", drawing);
+    }
+}
+
+#[test]
+fn text() {
+    for text in &[
+        "",
+        "Plain prose, with a well-known hyphen.",
+        "| a | b |\n|---|---|\n| 1 | 2 |",
+        "a = b - c;\nif a == b || c != d {}",
+        "println!(\"────────\");",
+        "fn f(x: i32) -> i32 {\n    x - 1 // ────\n}",
+        "Some words ─ in prose, and ─ some more.",
+        "─── Helpers ───",
+        "──────────",
+    ] {
+        assert!(confidence(text) < THRESHOLD, "{}", text);
+    }
+}
+
+#[test]
+fn evidence() {
+    // Each box, and wires that reach them, add to the confidence.
+    let line = confidence("──────");
+    let boxes = confidence("┌─┐  ┌─┐\n└─┘  └─┘");
+    let wired = confidence("┌─┐  ┌─┐\n└┬┘  └┬┘\n └────┘");
+    assert!(
+        line < boxes && boxes < wired,
+        "{} {} {}",
+        line,
+        boxes,
+        wired
+    );
+}
@@ -0,0 +1,30 @@
+use crate::csv::{adjacency, edges};
+use crate::logical::Diagram;
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn quoting_and_undirected() {
+    let g = Graph::with_text(
+        "┌───┐     ┌──────┐
+│ A ├─────┤ x, y │
+└─┬─┘ \"q\" └──────┘
+  │
+┏━┷━┓
+┃ B ┃
+┗━━━┛",
+    )
+    .unwrap();
+    let diagram = Diagram::from(&g);
+    assert_eq!(
+        edges(&diagram),
+        r#"source,target,source_label,target_label,directed,label,brush,dashed
+n0,n1,A,"x, y",false,"""q""",light,false
+n0,n2,A,B,false,,light,false
+"#
+    );
+    assert_eq!(
+        adjacency(&diagram),
+        ",n0,n1,n2\nn0,0,1,1\nn1,1,0,0\nn2,1,0,0\n"
+    );
+}
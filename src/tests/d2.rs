@@ -0,0 +1,34 @@
+use crate::d2::render;
+use crate::logical::Diagram;
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn nesting_and_labels() {
+    let g = Graph::with_text(
+        "┌┄┄┄┄┄┄┄┄┄┄┄┐
+┆ outer     ┆
+┆ ┌───────┐ ┆
+┆ │ inner │ ┆
+┆ └───┬───┘ ┆
+┆     ┆ go  ┆
+└┄┄┄┄┄┼┄┄┄┄┄┘
+      ┆
+      ▼
+┌───────────┐    ┌───┐
+│ say \"hi\"  ├────┤ Y │
+└───────────┘    └───┘",
+    )
+    .unwrap();
+    assert_eq!(
+        render(&Diagram::from(&g)),
+        r#"n0: "outer" {
+  n1: "inner"
+}
+n2: "say \"hi\""
+n3: "Y"
+n0.n1 -> n2: "go" {style.stroke-dash: 3}
+n2 -- n3
+"#
+    );
+}
@@ -0,0 +1,123 @@
+use crate::diff::{diff, side_by_side, Change, Link};
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+fn changes(old: &str, new: &str) -> Vec<String> {
+    let (old, new) = (
+        Graph::with_text(old).unwrap(),
+        Graph::with_text(new).unwrap(),
+    );
+    diff(&old, &new).iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn cosmetic() {
+    assert_eq!(
+        changes(
+            "┌───┐    ┌───┐
+│ A ├───▶│ B │
+└───┘    └───┘",
+            "┌───┐
+│ A ├──┐
+└───┘  │  ┏━━━┓
+       └─▶┃ B ┃
+          ┗━━━┛",
+        ),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn boxes_and_connections() {
+    let old = Graph::with_text(
+        "┌───┐    ┌───┐    ┌───┐
+│ A ├───▶│ B ├────┤ C │
+└───┘ go └───┘    └───┘",
+    )
+    .unwrap();
+    let new = Graph::with_text(
+        "┌───┐    ┌───┐    ┌───┐
+│ A ├───▶│ D │    │ C │
+└─┬─┘ up └───┘    └───┘
+  │
+┌─┴─┐
+│ E │
+└───┘",
+    )
+    .unwrap();
+    assert_eq!(
+        diff(&old, &new),
+        vec![
+            Change::RenamedBox {
+                old: 1,
+                new: 1,
+                from: "B".to_string(),
+                to: "D".to_string(),
+            },
+            Change::AddedBox {
+                new: 3,
+                label: "E".to_string(),
+            },
+            Change::Relabelled {
+                old: 0,
+                new: 0,
+                link: Link {
+                    from: "A".to_string(),
+                    to: "D".to_string(),
+                    directed: true,
+                },
+                from: Some("go".to_string()),
+                to: Some("up".to_string()),
+            },
+            Change::Disconnected {
+                old: 1,
+                link: Link {
+                    from: "B".to_string(),
+                    to: "C".to_string(),
+                    directed: false,
+                },
+            },
+            Change::Connected {
+                new: 1,
+                link: Link {
+                    from: "A".to_string(),
+                    to: "E".to_string(),
+                    directed: false,
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn rerouted() {
+    assert_eq!(
+        changes(
+            "┌───┐  ┌───┐
+│ A ├─▶│ B │
+└───┘  └───┘",
+            "┌───┐  ┌───┐
+│ A │  │ B │
+└─┬─┘  └───┘
+  │      ▲
+  └──────┘",
+        ),
+        vec![r#"rerouted "A" -> "B""#]
+    );
+}
+
+#[test]
+fn side_by_side_view() {
+    let old = Graph::with_text("┌───┐\n│ A │\n└───┘").unwrap();
+    let new = Graph::with_text("┌───┐\n│ A │\n└───┘\n┌───┐\n│ B │\n└───┘").unwrap();
+    assert_eq!(
+        side_by_side(&old, &new),
+        "┌───┐ │ ┌───┐
+│ A │ │ │ A │
+└───┘ │ └───┘
+      │ \x1b[7m┌\x1b[0m\x1b[7m─\x1b[0m\x1b[7m─\x1b[0m\x1b[7m─\x1b[0m\x1b[7m┐\x1b[0m
+      │ \x1b[7m│\x1b[0m \x1b[7mB\x1b[0m \x1b[7m│\x1b[0m
+      │ \x1b[7m└\x1b[0m\x1b[7m─\x1b[0m\x1b[7m─\x1b[0m\x1b[7m─\x1b[0m\x1b[7m┘\x1b[0m
+"
+    );
+}
@@ -0,0 +1,319 @@
+use crate::dot::{Cluster, Edge, Graph, Node};
+use crate::layout::Direction;
+use crate::logical::Diagram;
+use crate::tests::layout::{read_back, ReadBack};
+use crate::{Error, LineColumn};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn parse() {
+    let input = r#"
+        /* A small example */
+        strict digraph "G" {
+            node [shape=box];
+            a [label="Alpha"];
+            a -> b -> c [label="next"]; // a chain
+            subgraph cluster_0 {
+                label = "Inner";
+                d:n -> e;
+            }
+            c -> { d e }
+        }
+    "#;
+    let node = |id: &str, label: &str, cluster| Node {
+        id: id.to_string(),
+        label: label.to_string(),
+        cluster,
+    };
+    let edge = |from, to, label: Option<&str>| Edge {
+        from,
+        to,
+        label: label.map(String::from),
+    };
+    assert_eq!(
+        input.parse::<Graph>().unwrap(),
+        Graph {
+            strict: true,
+            directed: true,
+            id: Some("G".to_string()),
+            direction: Direction::Down,
+            nodes: vec![
+                node("a", "Alpha", None),
+                node("b", "b", None),
+                node("c", "c", None),
+                node("d", "d", Some(0)),
+                node("e", "e", Some(0)),
+            ],
+            edges: vec![
+                edge(0, 1, Some("next")),
+                edge(1, 2, Some("next")),
+                edge(3, 4, None),
+                edge(2, 3, None),
+                edge(2, 4, None),
+            ],
+            clusters: vec![Cluster {
+                id: "cluster_0".to_string(),
+                label: Some("Inner".to_string()),
+                parent: None,
+            }],
+        }
+    );
+}
+
+#[test]
+fn escaped_labels() {
+    let g = r#"graph { "a\nb"; c [label="one\ltwo\l"] }"#.parse::<Graph>().unwrap();
+    assert_eq!(g.directed, false);
+    assert_eq!(g.nodes[0].label, "a\nb");
+    assert_eq!(g.nodes[1].label, "one\ntwo");
+}
+
+#[test]
+fn html_labels() {
+    let g = "digraph { a [label=<<b>Hi</b> &amp; <i>bye</i>>]; b [label=<one<br/>two>] }"
+        .parse::<Graph>()
+        .unwrap();
+    assert_eq!(g.nodes[0].label, "Hi & bye");
+    assert_eq!(g.nodes[1].label, "one\ntwo");
+}
+
+#[test]
+fn syntax_error() {
+    match "digraph {\n  a -> ;\n}".parse::<Graph>() {
+        Err(Error::Syntax(location, _)) => {
+            assert_eq!(location, LineColumn { line: 2, column: 7 })
+        }
+        result => panic!("unexpected {:?}", result),
+    }
+}
+
+#[test]
+fn edge_operators() {
+    let message = |input: &str| match input.parse::<Graph>() {
+        Err(Error::Syntax(location, message)) => (location, message),
+        result => panic!("unexpected {:?}", result),
+    };
+    assert_eq!(
+        message("graph {\n  a -> b\n}"),
+        (
+            LineColumn { line: 2, column: 4 },
+            "`->` in a graph, which only has `--`".to_string()
+        )
+    );
+    assert_eq!(
+        message("digraph { a -> b -- c }"),
+        (
+            LineColumn {
+                line: 1,
+                column: 17
+            },
+            "`--` in a digraph, which only has `->`".to_string()
+        )
+    );
+}
+
+#[test]
+fn rankdir() {
+    let direction = |input: &str| input.parse::<Graph>().unwrap().direction;
+    assert_eq!(direction("digraph { a -> b }"), Direction::Down);
+    assert_eq!(
+        direction("digraph { rankdir=LR; a -> b }"),
+        Direction::Right
+    );
+    assert_eq!(direction("digraph { graph [rankdir=BT] }"), Direction::Up);
+    assert_eq!(direction("digraph { rankdir = \"RL\" }"), Direction::Left);
+    // Only the graph itself has a direction.
+    assert_eq!(
+        direction("digraph { subgraph cluster_0 { rankdir=LR } }"),
+        Direction::Down
+    );
+    let g = "digraph { rankdir=LR; a -> b }".parse::<Graph>().unwrap();
+    assert_eq!(
+        g.to_graph().unwrap().to_string(),
+        "┌───┐   ┌───┐
+│   │   │   │
+│ a ├──▶│ b │
+│   │   │   │
+└───┘   └───┘"
+    );
+}
+
+#[test]
+fn labels_with_strokes() {
+    let g = r#"digraph { a [label="lib-core"]; b [label="X/Y|Z"]; a -> b [label="a=b"] }"#
+        .parse::<Graph>()
+        .unwrap();
+    let diagram = Diagram::from(&g.to_graph().unwrap());
+    let labels: Vec<&str> = diagram.blocks.iter().map(|b| b.label.as_str()).collect();
+    assert_eq!(labels, ["lib-core", "X/Y|Z"]);
+    assert_eq!(diagram.wires[0].label, Some("a=b".to_string()));
+}
+
+#[test]
+fn render_directed() {
+    let g = "digraph { a -> b }".parse::<Graph>().unwrap();
+    assert_eq!(
+        g.to_graph().unwrap().to_string(),
+        "┌───┐
+│ a │
+└─┬─┘
+  │
+  │
+  ▼
+┌───┐
+│ b │
+└───┘"
+    );
+}
+
+#[test]
+fn render_undirected() {
+    let g = "graph { a -- b; a -- c }".parse::<Graph>().unwrap();
+    assert_eq!(
+        g.to_graph().unwrap().to_string(),
+        "  ┌─────┐
+  │  a  │
+  └─┬─┬─┘
+    │ │
+  ┌─┘ │
+  │   └──┐
+  │      │
+┌─┴─┐  ┌─┴─┐
+│ b │  │ c │
+└───┘  └───┘"
+    );
+}
+
+#[test]
+fn render_cycle() {
+    let g = "digraph { a -> b -> a }".parse::<Graph>().unwrap();
+    assert_eq!(
+        g.to_graph().unwrap().to_string(),
+        "┌─────┐
+│  a  │
+└─┬───┘
+  │ ▲
+  │ │
+  │ │
+  ▼ │
+┌───┴─┐
+│  b  │
+└─────┘"
+    );
+}
+
+#[test]
+fn render() {
+    let g = crate::Graph::with_text(
+        "┌┄┄┄┄┄┄┄┄┄┄┄┐
+┆ outer     ┆
+┆ ┌───────┐ ┆
+┆ │ inner │ ┆
+┆ └───┬───┘ ┆
+┆     ┆ go  ┆
+└┄┄┄┄┄┼┄┄┄┄┄┘
+      ┆
+      ▼
+┌───────────┐    ┌───┐
+│ say \"hi\"  ├━━━━┥ Y │
+└───────────┘    └───┘",
+    )
+    .unwrap();
+    assert_eq!(
+        crate::dot::render(&crate::logical::Diagram::from(&g)),
+        r#"digraph {
+  node [shape=box];
+  subgraph cluster_n0 {
+    label="outer";
+    n1 [label="inner"];
+  }
+  n2 [label="say \"hi\""];
+  n3 [label="Y"];
+  n1 -> n2 [label="go", style=dashed];
+  n2 -> n3 [dir=none, penwidth=2];
+}
+"#
+    );
+}
+
+#[test]
+fn render_cluster_ends() {
+    let g = crate::Graph::with_text(
+        "┌───────────┐
+│ outer     │
+│ ┌───────┐ │    ┌───┐
+│ │ inner │ ├───▶│ Z │
+│ └───────┘ │    └───┘
+└───────────┘",
+    )
+    .unwrap();
+    assert_eq!(
+        crate::dot::render(&crate::logical::Diagram::from(&g)),
+        r#"digraph {
+  compound=true;
+  node [shape=box];
+  subgraph cluster_n0 {
+    label="outer";
+    n0 [shape=point, style=invis];
+    n1 [label="inner"];
+  }
+  n2 [label="Z"];
+  n0 -> n2 [ltail=cluster_n0];
+}
+"#
+    );
+}
+
+#[test]
+fn labels_read_back() {
+    let label = |from: &str, to: &str, label: &str| {
+        (from.to_string(), to.to_string(), Some(label.to_string()))
+    };
+    for input in &[
+        "digraph { a -> b [label=yes]; a -> c [label=no]; a -> d [label=maybe] }",
+        "digraph { rankdir=LR; a -> b [label=yes]; a -> c [label=no]; a -> d [label=maybe] }",
+        "digraph { rankdir=BT; a -> b [label=yes]; a -> c [label=no]; a -> d [label=maybe] }",
+    ] {
+        let ReadBack {
+            connections, texts, ..
+        } = read_back(&input.parse::<Graph>().unwrap().to_graph().unwrap());
+        assert_eq!(
+            connections,
+            vec![
+                label("a", "b", "yes"),
+                label("a", "c", "no"),
+                label("a", "d", "maybe")
+            ],
+            "{}",
+            input
+        );
+        assert!(texts.is_empty(), "{}", input);
+    }
+    // A long edge is labelled halfway along, where it passes another layer.
+    let graph = "digraph { a -> b -> c; a -> c [label=skip] }".parse::<Graph>();
+    let connections = read_back(&graph.unwrap().to_graph().unwrap()).connections;
+    assert!(connections.contains(&label("a", "c", "skip")));
+}
+
+#[test]
+fn cluster_labels_read_back() {
+    let graph = "digraph {
+        subgraph cluster_0 { label=Outer; subgraph cluster_1 { label=Inner; x -> y } }
+        a -> x [label=in]
+    }"
+    .parse::<Graph>();
+    let ReadBack {
+        blocks,
+        connections,
+        texts,
+    } = read_back(&graph.unwrap().to_graph().unwrap());
+    assert_eq!(blocks, vec!["Inner", "Outer", "a", "x", "y"]);
+    assert_eq!(
+        connections,
+        vec![
+            ("a".to_string(), "x".to_string(), Some("in".to_string())),
+            ("x".to_string(), "y".to_string(), None)
+        ]
+    );
+    assert!(texts.is_empty());
+}
@@ -0,0 +1,184 @@
+use crate::drawio::{render, Edge, Model, Vertex};
+use crate::logical::Diagram;
+use crate::{Error, Graph, LineColumn};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn export() {
+    let g = Graph::with_text(
+        "┌───┐
+│ A ├─┐
+└───┘ │
+      ▼
+    ┌───┐
+    │ B │
+    └───┘",
+    )
+    .unwrap();
+    assert_eq!(
+        render(&Diagram::from(&g)),
+        r#"<mxfile host="dottools">
+  <diagram id="dottools" name="Page-1">
+    <mxGraphModel grid="1" gridSize="10">
+      <root>
+        <mxCell id="0"/>
+        <mxCell id="1" parent="0"/>
+        <mxCell id="n0" value="A" style="rounded=0;whiteSpace=wrap;html=1;" vertex="1" parent="1">
+          <mxGeometry x="5" y="10" width="40" height="40" as="geometry"/>
+        </mxCell>
+        <mxCell id="n1" value="B" style="rounded=0;whiteSpace=wrap;html=1;" vertex="1" parent="1">
+          <mxGeometry x="45" y="90" width="40" height="40" as="geometry"/>
+        </mxCell>
+        <mxCell id="w0" value="" style="edgeStyle=orthogonalEdgeStyle;rounded=0;html=1;endArrow=classic;exitX=1;exitY=0.5;entryX=0.5;entryY=0;" edge="1" parent="1" source="n0" target="n1">
+          <mxGeometry relative="1" as="geometry">
+            <Array as="points">
+              <mxPoint x="65" y="30"/>
+            </Array>
+          </mxGeometry>
+        </mxCell>
+      </root>
+    </mxGraphModel>
+  </diagram>
+</mxfile>
+"#
+    );
+}
+
+#[test]
+fn import() {
+    let model: Model = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mxfile host="app.diagrams.net">
+  <diagram id="x" name="Page-1">
+    <mxGraphModel dx="800" dy="600" grid="1" gridSize="10">
+      <root>
+        <mxCell id="0" />
+        <mxCell id="1" parent="0" />
+        <mxCell id="a" value="&lt;b&gt;Web&lt;/b&gt;" style="rounded=0;html=1;" vertex="1" parent="1">
+          <mxGeometry x="40" y="40" width="80" height="60" as="geometry" />
+        </mxCell>
+        <object label="DB" id="b">
+          <mxCell style="rounded=0;html=1;dashed=1;" vertex="1" parent="1">
+            <mxGeometry x="200" y="40" width="60" height="60" as="geometry" />
+          </mxCell>
+        </object>
+        <mxCell id="e" value="sql" style="edgeStyle=orthogonalEdgeStyle;endArrow=none;" edge="1" parent="1" source="a" target="b">
+          <mxGeometry relative="1" as="geometry" />
+        </mxCell>
+      </root>
+    </mxGraphModel>
+  </diagram>
+</mxfile>"#
+        .parse()
+        .unwrap();
+    assert_eq!(
+        model.vertices,
+        vec![
+            Vertex {
+                id: "a".to_string(),
+                label: "Web".to_string(),
+                x: 40.0,
+                y: 40.0,
+                width: 80.0,
+                height: 60.0,
+                parent: None,
+                text: false,
+                dashed: false,
+            },
+            Vertex {
+                id: "b".to_string(),
+                label: "DB".to_string(),
+                x: 200.0,
+                y: 40.0,
+                width: 60.0,
+                height: 60.0,
+                parent: None,
+                text: false,
+                dashed: true,
+            },
+        ]
+    );
+    assert_eq!(
+        model.edges,
+        vec![Edge {
+            id: "e".to_string(),
+            label: Some("sql".to_string()),
+            source: Some(0),
+            target: Some(1),
+            arrow: false,
+        }]
+    );
+    assert_eq!(
+        model.to_graph().unwrap().to_string(),
+        "┌───────┐   sql ┌┄┄┄┄┄┐
+│  Web  ├───────┤ DB  ┆
+│       │       ┆     ┆
+└───────┘       └┄┄┄┄┄┘"
+    );
+}
+
+#[test]
+fn unroutable() {
+    // B is walled in by text, so only the connector to C is drawn.
+    let model: Model = r#"<mxfile><diagram><mxGraphModel><root>
+<mxCell id="a" value="A" vertex="1" parent="1"><mxGeometry x="0" y="0" width="60" height="60"/></mxCell>
+<mxCell id="t" value="xxxxxxxxxxx" style="text;" vertex="1" parent="1"><mxGeometry x="80" y="0" width="110" height="20"/></mxCell>
+<mxCell id="u" value="xxxxxxxxxxx" style="text;" vertex="1" parent="1"><mxGeometry x="80" y="100" width="110" height="20"/></mxCell>
+<mxCell id="v" value="x&#xa;x&#xa;x&#xa;x" style="text;" vertex="1" parent="1"><mxGeometry x="80" y="20" width="10" height="80"/></mxCell>
+<mxCell id="w" value="x&#xa;x&#xa;x&#xa;x" style="text;" vertex="1" parent="1"><mxGeometry x="180" y="20" width="10" height="80"/></mxCell>
+<mxCell id="b" value="B" vertex="1" parent="1"><mxGeometry x="100" y="20" width="60" height="60"/></mxCell>
+<mxCell id="c" value="C" vertex="1" parent="1"><mxGeometry x="0" y="120" width="60" height="60"/></mxCell>
+<mxCell id="e" edge="1" source="a" target="b"><mxGeometry relative="1"/></mxCell>
+<mxCell id="f" edge="1" source="a" target="c"><mxGeometry relative="1"/></mxCell>
+</root></mxGraphModel></diagram></mxfile>"#
+        .parse()
+        .unwrap();
+    assert_eq!(
+        model.to_graph().unwrap().to_string(),
+        "┌─────┐ xxxxxxxxxxx
+│  A  │ x ┌─────┐ x
+│     │ x │  B  │ x
+└┬────┘ x │     │ x
+ │      x └─────┘ x
+ ▼      xxxxxxxxxxx
+┌─────┐
+│  C  │
+│     │
+└─────┘"
+    );
+}
+
+#[test]
+fn empty_cells() {
+    // The geometry after an empty cell is not the cell's.
+    let model: Model = r#"<mxfile><diagram><mxGraphModel><root>
+<mxCell id="a" value="A" vertex="1" parent="1"/>
+<mxGeometry x="40" y="40" width="80" height="60" as="geometry"/>
+</root></mxGraphModel></diagram></mxfile>"#
+        .parse()
+        .unwrap();
+    assert_eq!((model.vertices[0].x, model.vertices[0].width), (0.0, 0.0));
+}
+
+#[test]
+fn compressed() {
+    let input = "<mxfile>\n<diagram id=\"x\">7VhNj5swEP01</diagram>\n</mxfile>";
+    match input.parse::<Model>() {
+        Err(Error::Syntax(location, _)) => assert_eq!(location, LineColumn::new(2, 16)),
+        result => panic!("expected a syntax error, got {:?}", result),
+    }
+}
+
+#[test]
+fn inside_of_itself() {
+    let input = r#"<mxfile><diagram><mxGraphModel><root>
+<mxCell id="a" value="A" vertex="1" parent="b"/>
+<mxCell id="b" value="B" vertex="1" parent="a"/>
+</root></mxGraphModel></diagram></mxfile>"#;
+    match input.parse::<Model>() {
+        Err(Error::Syntax(location, message)) => {
+            assert_eq!(location, LineColumn::new(2, 0));
+            assert_eq!(message, "`a` is inside of itself");
+        }
+        result => panic!("expected a syntax error, got {:?}", result),
+    }
+}
@@ -0,0 +1,159 @@
+use crate::embedded::{drawings, replace, Embedded, Kind};
+use crate::style::{restyle, Style};
+use crate::{Brush, LineColumn};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn markdown() {
+    let document = "# Notes\n\n  ```text\n  ┌─┐\n   └┘\n  ```\n\n```rust\n// ┌─┐\n```\n";
+    assert_eq!(
+        drawings(document, Kind::Markdown),
+        [Embedded {
+            line: 3,
+            lines: vec![("  ", "┌─┐"), ("  ", " └┘")],
+        }]
+    );
+}
+
+#[test]
+fn source() {
+    let source = "/// ┌─┐\n/// └─┘\n// ┌─┐\n    # ─┐\n";
+    let found = drawings(source, Kind::Source);
+    assert_eq!(
+        found
+            .iter()
+            .map(|drawing| (drawing.line, drawing.text()))
+            .collect::<Vec<_>>(),
+        [
+            (0, "┌─┐\n└─┘".to_string()),
+            (2, "┌─┐".to_string()),
+            (3, "─┐".to_string())
+        ]
+    );
+    assert_eq!(found[2].lines[0].0, "    # ");
+}
+
+#[test]
+fn indented() {
+    let document = "Text\n    ┌─┐\n\n    ┌─┐\n\n      └┘\n\n- Item\n\n    ```\n    └┘\n    ```\n";
+    assert_eq!(
+        drawings(document, Kind::Markdown),
+        [
+            Embedded {
+                line: 3,
+                lines: vec![("    ", "┌─┐"), ("", ""), ("    ", "  └┘")],
+            },
+            Embedded {
+                line: 10,
+                lines: vec![("    ", "└┘")],
+            }
+        ]
+    );
+}
+
+#[test]
+fn graph() {
+    let document = "# Notes\n\n    ┌─┐\n    └─┘ A\n";
+    let drawing = &drawings(document, Kind::Markdown)[0];
+    let graph = drawing.graph().unwrap();
+    let mut nodes: Vec<(char, usize, usize)> = graph
+        .nodes()
+        .map(|node| {
+            (
+                node.character(),
+                node.source().line(),
+                node.source().column(),
+            )
+        })
+        .collect();
+    nodes.sort();
+    assert_eq!(
+        nodes,
+        [
+            ('A', 4, 8),
+            ('┌', 3, 4),
+            ('┐', 3, 6),
+            ('└', 4, 4),
+            ('┘', 4, 6)
+        ]
+    );
+    assert_eq!(graph.edge_count(), 4);
+    assert_eq!(drawing.visual(LineColumn::new(2, 1)), LineColumn::new(4, 5));
+}
+
+#[test]
+fn comments() {
+    let source = "//// ┌─┐\n//// └─┘\n-- ─┐\n/*\n * ┌─┐\n * └─┘ */\n";
+    let found = drawings(source, Kind::Source);
+    assert_eq!(
+        found,
+        [
+            Embedded {
+                line: 0,
+                lines: vec![("//// ", "┌─┐"), ("//// ", "└─┘")],
+            },
+            Embedded {
+                line: 2,
+                lines: vec![("-- ", "─┐")],
+            },
+            Embedded {
+                line: 4,
+                lines: vec![(" * ", "┌─┐"), (" * ", "└─┘")],
+            }
+        ]
+    );
+
+    // No part of a marker is read as a stroke, and positions are those in the source.
+    for drawing in &found {
+        let graph = drawing.graph().unwrap();
+        for (v, u, edge) in graph.all_edges() {
+            assert!(
+                matches!(edge.brush(), Brush::NorthSouth(_) | Brush::EastWest(_)),
+                "{:?}",
+                edge
+            );
+            for node in &[v, u] {
+                let line = source.lines().nth(node.source().line() - 1).unwrap();
+                let c = line.chars().nth(node.source().column()).unwrap();
+                assert_eq!(c, node.character());
+            }
+        }
+    }
+
+    assert_eq!(
+        replace(source, Kind::Source, |drawing| drawing.replace('─', "━")),
+        "//// ┌━┐\n//// └━┘\n-- ━┐\n/*\n * ┌━┐\n * └━┘ */\n"
+    );
+}
+
+#[test]
+fn block_comments() {
+    let source = "/* ┌───┐\n * │ A ├──▶\n * └───┘ */\n";
+    let found = drawings(source, Kind::Source);
+    assert_eq!(
+        found,
+        [Embedded {
+            line: 0,
+            lines: vec![("/* ", "┌───┐"), (" * ", "│ A ├──▶"), (" * ", "└───┘")],
+        }]
+    );
+    assert_eq!(
+        replace(source, Kind::Source, |drawing| restyle(
+            drawing,
+            Style::Heavy
+        )),
+        "/* ┏━━━┓\n * ┃ A ┣━━▶\n * ┗━━━┛ */\n"
+    );
+}
+
+#[test]
+fn code() {
+    let document = "```\nprintln!(\"────────\");\n```\n\n    let rule = \"──\";\n";
+    assert_eq!(drawings(document, Kind::Markdown), []);
+    assert_eq!(
+        drawings("// ┌┐\n// └┘ is a box\n# ───", Kind::Source).len(),
+        1
+    );
+    // Rules are not drawings.
+    assert_eq!(drawings("// ─── Helpers ───\n", Kind::Source), []);
+}
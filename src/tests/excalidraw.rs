@@ -0,0 +1,75 @@
+use crate::excalidraw::render;
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+/// The elements of a scene, one per line, with the fields common to every element removed.
+fn elements(scene: &str) -> Vec<String> {
+    let common = concat!(
+        r##""angle":0,"strokeColor":"#1e1e1e","backgroundColor":"transparent","##,
+        r#""fillStyle":"solid","#,
+    );
+    let state = r#""groupIds":[],"frameId":null,"roundness":null,"#;
+    let lines: Vec<&str> = scene.lines().collect();
+    assert_eq!(
+        lines[0],
+        r#"{"type":"excalidraw","version":2,"source":"dottools","elements":["#
+    );
+    assert_eq!(
+        lines[lines.len() - 1],
+        r##"],"appState":{"viewBackgroundColor":"#ffffff","gridSize":null},"files":{}}"##
+    );
+    lines[1..lines.len() - 1]
+        .iter()
+        .map(|line| {
+            line.trim_end_matches(',')
+                .replace(common, "")
+                .replace(state, "")
+                .replace(r#""roughness":1,"opacity":100,"#, "")
+                .replace(r#","updated":1,"link":null,"locked":false"#, "")
+        })
+        .collect()
+}
+
+#[test]
+fn arrow_between_boxes() {
+    let g = Graph::with_text(
+        "┏━━━┓    ┌───┐
+┃ A ┠┄┄┄▶│ B │
+┗━━━┛    └───┘",
+    )
+    .unwrap();
+    assert_eq!(
+        elements(&render(&g)),
+        vec![
+            r#"{"id":"b0","type":"rectangle","x":5,"y":10,"width":40,"height":40,"strokeWidth":2,"strokeStyle":"solid","seed":1,"version":1,"versionNonce":1,"isDeleted":false,"boundElements":[{"id":"b0-label","type":"text"},{"id":"a0","type":"arrow"}]}"#,
+            r#"{"id":"b0-label","type":"text","x":20,"y":20,"width":10,"height":20,"strokeWidth":1,"strokeStyle":"solid","seed":2,"version":1,"versionNonce":2,"isDeleted":false,"boundElements":null,"text":"A","fontSize":16,"fontFamily":3,"textAlign":"center","verticalAlign":"middle","containerId":"b0","originalText":"A","lineHeight":1.25}"#,
+            r#"{"id":"b1","type":"rectangle","x":95,"y":10,"width":40,"height":40,"strokeWidth":1,"strokeStyle":"solid","seed":3,"version":1,"versionNonce":3,"isDeleted":false,"boundElements":[{"id":"b1-label","type":"text"},{"id":"a0","type":"arrow"}]}"#,
+            r#"{"id":"b1-label","type":"text","x":110,"y":20,"width":10,"height":20,"strokeWidth":1,"strokeStyle":"solid","seed":4,"version":1,"versionNonce":4,"isDeleted":false,"boundElements":null,"text":"B","fontSize":16,"fontFamily":3,"textAlign":"center","verticalAlign":"middle","containerId":"b1","originalText":"B","lineHeight":1.25}"#,
+            r#"{"id":"a0","type":"arrow","x":50,"y":30,"width":40,"height":0,"strokeWidth":1,"strokeStyle":"dashed","seed":5,"version":1,"versionNonce":5,"isDeleted":false,"boundElements":null,"points":[[0,0],[40,0]],"startBinding":{"elementId":"b0","focus":0,"gap":1},"endBinding":{"elementId":"b1","focus":0,"gap":1},"startArrowhead":null,"endArrowhead":"arrow"}"#,
+        ]
+    );
+}
+
+#[test]
+fn line_and_text() {
+    let g = Graph::with_text(
+        "┌───┐
+│ A ├──┐      note
+└───┘  │
+     ┌─┴─┐
+     │ B │
+     └───┘",
+    )
+    .unwrap();
+    assert_eq!(
+        elements(&render(&g)),
+        vec![
+            r#"{"id":"b0","type":"rectangle","x":5,"y":10,"width":40,"height":40,"strokeWidth":1,"strokeStyle":"solid","seed":1,"version":1,"versionNonce":1,"isDeleted":false,"boundElements":[{"id":"b0-label","type":"text"}]}"#,
+            r#"{"id":"b0-label","type":"text","x":20,"y":20,"width":10,"height":20,"strokeWidth":1,"strokeStyle":"solid","seed":2,"version":1,"versionNonce":2,"isDeleted":false,"boundElements":null,"text":"A","fontSize":16,"fontFamily":3,"textAlign":"center","verticalAlign":"middle","containerId":"b0","originalText":"A","lineHeight":1.25}"#,
+            r#"{"id":"b1","type":"rectangle","x":55,"y":70,"width":40,"height":40,"strokeWidth":1,"strokeStyle":"solid","seed":3,"version":1,"versionNonce":3,"isDeleted":false,"boundElements":[{"id":"b1-label","type":"text"}]}"#,
+            r#"{"id":"b1-label","type":"text","x":70,"y":80,"width":10,"height":20,"strokeWidth":1,"strokeStyle":"solid","seed":4,"version":1,"versionNonce":4,"isDeleted":false,"boundElements":null,"text":"B","fontSize":16,"fontFamily":3,"textAlign":"center","verticalAlign":"middle","containerId":"b1","originalText":"B","lineHeight":1.25}"#,
+            r#"{"id":"l0","type":"line","x":50,"y":30,"width":25,"height":30,"strokeWidth":1,"strokeStyle":"solid","seed":5,"version":1,"versionNonce":5,"isDeleted":false,"boundElements":null,"points":[[0,0],[25,0],[25,30]],"startBinding":null,"endBinding":null,"startArrowhead":null,"endArrowhead":null}"#,
+            r#"{"id":"t0","type":"text","x":140,"y":20,"width":40,"height":20,"strokeWidth":1,"strokeStyle":"solid","seed":6,"version":1,"versionNonce":6,"isDeleted":false,"boundElements":null,"text":"note","fontSize":16,"fontFamily":3,"textAlign":"center","verticalAlign":"middle","containerId":null,"originalText":"note","lineHeight":1.25}"#,
+        ]
+    );
+}
@@ -0,0 +1,121 @@
+use crate::format::{format, format_markdown, format_source, Options};
+use crate::style::Style;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn junctions() {
+    let options = Options::default();
+    assert_eq!(
+        format(
+            "┌───┬────\n│ A │   │\n├───┼───┤\n│   │ B │\n└───────┘",
+            &options
+        ),
+        "┌───┬───┐\n│ A │   │\n├───┼───┤\n│   │ B │\n└───┴───┘"
+    );
+    assert_eq!(format("  │\n──│──▶\n  │", &options), "  │\n──┼──▶\n  │");
+    // Text next to a stroke is not joined.
+    assert_eq!(format("A──B", &options), "A──B");
+}
+
+#[test]
+fn ascii() {
+    let options = Options::default();
+    let drawing = "+------+\n| lib  |---> x\n+------+";
+    assert_eq!(format(drawing, &options), drawing);
+    // A box drawn with box drawing is not joined to an ASCII stroke.
+    assert_eq!(
+        format("┌───┐\n│ A │---> x\n└───┘", &options),
+        "┌───┐\n│ A │---> x\n└───┘"
+    );
+}
+
+#[test]
+fn style_and_whitespace() {
+    let options = Options {
+        style: Some(Style::Heavy),
+    };
+    assert_eq!(
+        format("┌──┐   \n│  ├──▶ \n└──┘", &options),
+        "┏━━┓\n┃  ┣━━▶\n┗━━┛"
+    );
+}
+
+#[test]
+fn markdown() {
+    let document = "# Title  
+
+Some text,  with   spacing.
+
+```text
+┌───── 
+│ A  │
+└────┘   
+```
+
+```rust
+let s = \"┌──── \";
+```
+
+   ~~~
+   ──│
+   ~~~
+";
+    assert_eq!(
+        format_markdown(document, &Options::default()),
+        "# Title  
+
+Some text,  with   spacing.
+
+```text
+┌────┐
+│ A  │
+└────┘
+```
+
+```rust
+let s = \"┌──── \";
+```
+
+   ~~~
+   ───
+   ~~~
+"
+    );
+}
+
+#[test]
+fn source() {
+    let source = "/// Docs about
+/// ┌───┐ go ┌────
+/// │ A ├───▶│ B │
+/// └───┘    └───┘
+fn f() {
+    // ──│  
+    let x = y - z; # not a comment ┄
+}
+
+/*
+ * ┌──
+ * └─┘
+ */
+# ──│
+";
+    assert_eq!(
+        format_source(source, &Options::default()),
+        "/// Docs about
+/// ┌───┐ go ┌───┐
+/// │ A ├───▶│ B │
+/// └───┘    └───┘
+fn f() {
+    // ───
+    let x = y - z; # not a comment ┄
+}
+
+/*
+ * ┌─┐
+ * └─┘
+ */
+# ───
+"
+    );
+}
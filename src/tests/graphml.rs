@@ -0,0 +1,110 @@
+use crate::graphml::{render, render_diagram};
+use crate::logical::Diagram;
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn raw() {
+    let g = Graph::with_text("┄┄").unwrap();
+    assert_eq!(
+        render(&g),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="line" for="node" attr.name="line" attr.type="int"/>
+  <key id="column" for="node" attr.name="column" attr.type="int"/>
+  <key id="glyph" for="all" attr.name="glyph" attr.type="string"/>
+  <key id="region" for="node" attr.name="region" attr.type="string"/>
+  <key id="brush" for="edge" attr.name="brush" attr.type="string"/>
+  <key id="dashed" for="edge" attr.name="dashed" attr.type="boolean"/>
+  <key id="direction" for="edge" attr.name="direction" attr.type="string"/>
+  <graph id="G" edgedefault="undirected">
+    <node id="n0">
+      <data key="line">1</data>
+      <data key="column">0</data>
+      <data key="glyph">┄</data>
+      <data key="region">center west</data>
+    </node>
+    <node id="n1">
+      <data key="line">1</data>
+      <data key="column">1</data>
+      <data key="glyph">┄</data>
+      <data key="region">center east</data>
+    </node>
+    <edge id="e0" source="n0" target="n1">
+      <data key="glyph">┄</data>
+      <data key="brush">light</data>
+      <data key="dashed">true</data>
+      <data key="direction">east-west</data>
+    </edge>
+  </graph>
+</graphml>
+"#
+    );
+}
+
+#[test]
+fn logical() {
+    let g = Graph::with_text(
+        "┌───┐     ┌──────┐
+│ A ├─────┤ x, y │
+└─┬─┘ \"q\" └──────┘
+  │
+┏━┷━┓
+┃ B ┃
+┗━━━┛",
+    )
+    .unwrap();
+    assert_eq!(
+        render_diagram(&Diagram::from(&g)),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="label" for="all" attr.name="label" attr.type="string"/>
+  <key id="line" for="node" attr.name="line" attr.type="int"/>
+  <key id="column" for="node" attr.name="column" attr.type="int"/>
+  <key id="width" for="node" attr.name="width" attr.type="int"/>
+  <key id="height" for="node" attr.name="height" attr.type="int"/>
+  <key id="parent" for="node" attr.name="parent" attr.type="string"/>
+  <key id="brush" for="all" attr.name="brush" attr.type="string"/>
+  <key id="dashed" for="all" attr.name="dashed" attr.type="boolean"/>
+  <graph id="G" edgedefault="undirected">
+    <node id="n0">
+      <data key="label">A</data>
+      <data key="line">1</data>
+      <data key="column">0</data>
+      <data key="width">5</data>
+      <data key="height">3</data>
+      <data key="brush">light</data>
+      <data key="dashed">false</data>
+    </node>
+    <node id="n1">
+      <data key="label">x, y</data>
+      <data key="line">1</data>
+      <data key="column">10</data>
+      <data key="width">8</data>
+      <data key="height">3</data>
+      <data key="brush">light</data>
+      <data key="dashed">false</data>
+    </node>
+    <node id="n2">
+      <data key="label">B</data>
+      <data key="line">5</data>
+      <data key="column">0</data>
+      <data key="width">5</data>
+      <data key="height">3</data>
+      <data key="brush">heavy</data>
+      <data key="dashed">false</data>
+    </node>
+    <edge id="e0" source="n0" target="n1" directed="false">
+      <data key="label">&quot;q&quot;</data>
+      <data key="brush">light</data>
+      <data key="dashed">false</data>
+    </edge>
+    <edge id="e1" source="n0" target="n2" directed="false">
+      <data key="brush">light</data>
+      <data key="dashed">false</data>
+    </edge>
+  </graph>
+</graphml>
+"#
+    );
+}
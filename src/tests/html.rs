@@ -0,0 +1,19 @@
+use crate::html::render;
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn spans_and_stylesheet() {
+    let g = Graph::with_text("┌─┐\n│&├─▶ x\n└─┘").unwrap();
+    assert_eq!(
+        render(&g),
+        r#"<style>
+.dottools span[data-wire]:hover { cursor: pointer; }
+.dottools:has(span[data-wire="0"]:hover) span[data-wire="0"] { background-color: #ffe58f; }
+</style>
+<pre class="dottools"><span data-position="1:0" data-box="&amp;">┌</span><span data-position="1:1" data-box="&amp;">─</span><span data-position="1:2" data-box="&amp;">┐</span>
+<span data-position="2:0" data-box="&amp;">│</span><span data-position="2:1" data-box="&amp;">&amp;</span><span data-position="2:2" data-box="&amp;">├</span><span data-position="2:3" data-wire="0">─</span><span data-position="2:4" data-wire="0">▶</span> <span data-position="2:6">x</span>
+<span data-position="3:0" data-box="&amp;">└</span><span data-position="3:1" data-box="&amp;">─</span><span data-position="3:2" data-box="&amp;">┘</span></pre>
+"#
+    );
+}
@@ -0,0 +1,161 @@
+use crate::layout::{layered, Layout};
+use crate::logical::Diagram;
+use crate::Error;
+use petgraph::Graph;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn uncrossed() {
+    let mut g = Graph::<&str, ()>::new();
+    let a = g.add_node("a");
+    let b = g.add_node("b");
+    let c = g.add_node("c");
+    let d = g.add_node("d");
+    g.add_edge(a, d, ());
+    g.add_edge(b, c, ());
+    assert_eq!(
+        layered(&g).unwrap().to_string(),
+        "┌───┐  ┌───┐
+│ a │  │ b │
+└─┬─┘  └─┬─┘
+  │      │
+  │      │
+  │      │
+  ▼      ▼
+┌───┐  ┌───┐
+│ d │  │ c │
+└───┘  └───┘"
+    );
+}
+
+#[test]
+fn long_edges() {
+    let mut g = petgraph::graph::UnGraph::<String, ()>::new_undirected();
+    let n: Vec<_> = (0..6).map(|i| g.add_node(format!("n{}", i))).collect();
+    for &(x, y) in &[(0, 3), (0, 5), (1, 4), (2, 3), (1, 5), (3, 4)] {
+        g.add_edge(n[x], n[y], ());
+    }
+    assert_eq!(
+        layered(&g).unwrap().to_string(),
+        "┌─────┐  ┌─────┐  ┌────┐
+│ n0  │  │ n1  │  │ n2 │
+└─┬─┬─┘  └─┬─┬─┘  └─┬──┘
+  │ │      │ │      │
+  │ │ ┌────┘ │      │
+  │ │ │      │ ┌────┘
+  │ │ │      └─┼────┐
+  │ └─┼──────┐ │    │
+  └─┐ │      │ │    │
+    │ │      │ │    │
+  ┌─┴─┴─┐  ┌─┴─┴─┐  │
+  │ n5  │  │ n3  │  │
+  └─────┘  └──┬──┘  │
+              │     │
+          ┌───┘     │
+          │ ┌───────┘
+          │ │
+        ┌─┴─┴─┐
+        │ n4  │
+        └─────┘"
+    );
+}
+
+#[test]
+fn clusters_and_labels() {
+    let mut layout = Layout::new(true);
+    let core = layout.add_cluster(Some("core".to_string()), None).unwrap();
+    let api = layout.add_node("api", None);
+    let db = layout.add_node("db", Some(core));
+    let cache = layout.add_node("cache", Some(core));
+    layout.add_edge(api, db, Some("sql".to_string())).unwrap();
+    layout.add_edge(api, cache, None).unwrap();
+    assert_eq!(
+        layout.render().unwrap().to_string(),
+        "┌─────┐
+│ api │
+└─┬─┬─┘
+  │ │
+  └─┼───────┐
+    └───────┼─────────┐
+            │         │
+        ┌┄┄┄┼┄┄┄┄┄┄┄┄┄┼┄┄┄┄┄┐
+        ┆   │  core   │     ┆
+        ┆   ▼ sql     ▼     ┆
+        ┆ ┌────┐  ┌───────┐ ┆
+        ┆ │ db │  │ cache │ ┆
+        ┆ └────┘  └───────┘ ┆
+        ┆                   ┆
+        └┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┄┘"
+    );
+}
+
+#[test]
+fn edge_to_missing_node() {
+    let mut layout = Layout::new(false);
+    let a = layout.add_node("a", None);
+    match layout.add_edge(a, 1, None) {
+        Err(Error::NoNode(1)) => {}
+        result => panic!("unexpected {:?}", result),
+    }
+    assert_eq!(layout.render().unwrap().to_string(), "┌───┐\n│ a │\n└───┘");
+}
+
+#[test]
+fn missing_edges_and_clusters() {
+    let mut layout = Layout::new(true);
+    let a = layout.add_node("a", None);
+    let b = layout.add_node("b", None);
+    let edge = layout.add_edge(a, b, None).unwrap();
+    layout.set_directed(edge, false).unwrap();
+    match layout.set_backward(edge + 1, true) {
+        Err(Error::NoEdge(1)) => {}
+        result => panic!("unexpected {:?}", result),
+    }
+    match layout.set_brush(edge + 1, '┄') {
+        Err(Error::NoEdge(1)) => {}
+        result => panic!("unexpected {:?}", result),
+    }
+    // A cluster cannot be put in itself or in one that comes after it.
+    match layout.add_cluster(None, Some(0)) {
+        Err(Error::NoCluster(0)) => {}
+        result => panic!("unexpected {:?}", result),
+    }
+    let outer = layout.add_cluster(None, None).unwrap();
+    assert_eq!(layout.add_cluster(None, Some(outer)).unwrap(), 1);
+    assert_eq!(
+        layout.render().unwrap().to_string(),
+        "┌───┐\n│ a │\n└─┬─┘\n  │\n  │\n  │\n┌─┴─┐\n│ b │\n└───┘"
+    );
+}
+
+/// The labels of the boxes and wires of a drawing as they are read back, sorted, along with any
+/// text that labels neither.
+pub(super) struct ReadBack {
+    pub(super) blocks: Vec<String>,
+    pub(super) connections: Vec<(String, String, Option<String>)>,
+    pub(super) texts: Vec<String>,
+}
+
+pub(super) fn read_back(graph: &crate::Graph) -> ReadBack {
+    let diagram = Diagram::from(graph);
+    let mut blocks: Vec<String> = diagram.blocks.iter().map(|b| b.label.clone()).collect();
+    blocks.sort();
+    let mut connections: Vec<_> = diagram
+        .connections()
+        .iter()
+        .map(|c| {
+            (
+                diagram.blocks[c.from].label.clone(),
+                diagram.blocks[c.to].label.clone(),
+                diagram.wires[c.wire].label.clone(),
+            )
+        })
+        .collect();
+    connections.sort();
+    let texts = diagram.texts.iter().map(|t| t.text.clone()).collect();
+    ReadBack {
+        blocks,
+        connections,
+        texts,
+    }
+}
@@ -0,0 +1,166 @@
+use crate::logical::{Block, Connection, Diagram, End};
+use crate::{Brush, Graph, LineColumn};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn boxes_and_arrow() {
+    let g = Graph::with_text(
+        "┌─────┐    ┌───┐
+│ one ├───▶│ 2 │
+└─────┘    └───┘",
+    )
+    .unwrap();
+    let diagram = Diagram::from(&g);
+    assert_eq!(
+        diagram.blocks,
+        vec![
+            Block {
+                top_left: LineColumn::new(1, 0),
+                bottom_right: LineColumn::new(3, 6),
+                label: "one".to_string(),
+                parent: None,
+                brush: Brush::EastWest('─'),
+            },
+            Block {
+                top_left: LineColumn::new(1, 11),
+                bottom_right: LineColumn::new(3, 15),
+                label: "2".to_string(),
+                parent: None,
+                brush: Brush::EastWest('─'),
+            },
+        ]
+    );
+    assert_eq!(diagram.wires.len(), 1);
+    assert_eq!(
+        diagram.wires[0].ends,
+        vec![
+            End {
+                position: LineColumn::new(2, 6),
+                block: Some(0),
+                arrow: false,
+            },
+            End {
+                position: LineColumn::new(2, 11),
+                block: Some(1),
+                arrow: true,
+            },
+        ]
+    );
+    assert_eq!(
+        diagram.connections(),
+        vec![Connection {
+            from: 0,
+            to: 1,
+            directed: true,
+            bidirectional: false,
+            wire: 0,
+        }]
+    );
+}
+
+#[test]
+fn nesting_and_labels() {
+    let g = Graph::with_text(
+        "┌┄┄┄┄┄┄┄┄┄┄┄┐
+┆ outer     ┆
+┆ ┌───────┐ ┆
+┆ │ inner │ ┆
+┆ └───┬───┘ ┆
+┆     │ go  ┆
+└┄┄┄┄┄┼┄┄┄┄┄┘
+      │
+      ▼   note",
+    )
+    .unwrap();
+    let diagram = Diagram::from(&g);
+    let labels: Vec<_> = diagram
+        .blocks
+        .iter()
+        .map(|block| (block.label.as_str(), block.parent, block.brush))
+        .collect();
+    assert_eq!(
+        labels,
+        vec![
+            ("outer", None, Brush::EastWest('┄')),
+            ("inner", Some(0), Brush::EastWest('─')),
+        ]
+    );
+    assert_eq!(diagram.wires.len(), 1);
+    assert_eq!(diagram.wires[0].label, Some("go".to_string()));
+    assert_eq!(
+        diagram.wires[0].ends,
+        vec![
+            End {
+                position: LineColumn::new(5, 6),
+                block: Some(1),
+                arrow: false,
+            },
+            End {
+                position: LineColumn::new(9, 6),
+                block: None,
+                arrow: true,
+            },
+        ]
+    );
+    assert_eq!(diagram.texts.len(), 1);
+    assert_eq!(diagram.texts[0].text, "note");
+}
+
+#[test]
+fn crossing_wires() {
+    let g = Graph::with_text("  │\n──┼──\n  │").unwrap();
+    let diagram = Diagram::from(&g);
+    assert_eq!(diagram.wires.len(), 2);
+    assert_eq!(diagram.wires[0].cells.len(), 3);
+    assert_eq!(diagram.wires[1].cells.len(), 5);
+    assert!(diagram.connections().is_empty());
+}
+
+#[test]
+fn words_with_strokes() {
+    let g = Graph::with_text(
+        "┌──────────┐     ┌─────┐
+│ lib-core ├────▶│ X/Y │
+└──────────┘ a=b └─────┘
+ one|two",
+    )
+    .unwrap();
+    let diagram = Diagram::from(&g);
+    let labels: Vec<&str> = diagram.blocks.iter().map(|b| b.label.as_str()).collect();
+    assert_eq!(labels, ["lib-core", "X/Y"]);
+    assert_eq!(diagram.wires.len(), 1);
+    assert_eq!(diagram.wires[0].label, Some("a=b".to_string()));
+    assert_eq!(diagram.texts[0].text, "one|two");
+}
+
+#[test]
+fn letters_and_strokes_inside_boxes() {
+    let g = Graph::with_text(
+        "┌────┐ ┌──────────────┐
+│ X  │ │ Привет ─ мир │
+│x=1 │ │ A─B  c │ d   │
+└────┘ └──────────────┘",
+    )
+    .unwrap();
+    let diagram = Diagram::from(&g);
+    let labels: Vec<&str> = diagram.blocks.iter().map(|b| b.label.as_str()).collect();
+    assert_eq!(labels, ["X x=1", "Привет ─ мир A─B c │ d"]);
+    assert!(diagram.wires.is_empty());
+    assert!(diagram.texts.is_empty());
+}
+
+#[test]
+fn box_without_inside() {
+    let g = Graph::with_text("┌┐\n└┘").unwrap();
+    let diagram = Diagram::from(&g);
+    assert_eq!(
+        diagram.blocks,
+        vec![Block {
+            top_left: LineColumn::new(1, 0),
+            bottom_right: LineColumn::new(2, 1),
+            label: String::new(),
+            parent: None,
+            brush: Brush::EastWest('─'),
+        }]
+    );
+}
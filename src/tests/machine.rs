@@ -0,0 +1,102 @@
+use crate::machine::{Machine, Transition};
+use crate::Error;
+use pretty_assertions::assert_eq;
+
+const DOOR: &str = "
+               ┌─────────┐  open   ┌──────┐
+               │ closed  ├────────▶│ open │
+               │ (start) │◀────────┤      │
+               └───────┬─┘  close  └──────┘
+                 ▲     │
+    unlock, kick │     │ lock
+                 │     ▼
+               ┌─┴───────┐
+               │ locked  │
+               └─────────┘";
+
+#[test]
+fn extraction() {
+    let machine: Machine = DOOR.parse().unwrap();
+    assert_eq!(machine.states, ["closed", "open", "locked"]);
+    assert_eq!(machine.initial, 0);
+    assert_eq!(machine.events, ["open", "close", "unlock", "kick", "lock"]);
+    let mut transitions: Vec<(usize, usize, usize)> = machine
+        .transitions
+        .iter()
+        .map(|&Transition { from, event, to }| (from, event, to))
+        .collect();
+    transitions.sort();
+    assert_eq!(
+        transitions,
+        [(0, 0, 1), (0, 4, 2), (1, 1, 0), (2, 2, 0), (2, 3, 0)]
+    );
+}
+
+#[test]
+fn rust() {
+    let machine: Machine = "
+┌─────────────────┐ ack ┌──────┐
+│ waiting for ack ├────▶│ done │
+│ (start)         │     └──────┘
+└─────────────────┘"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        machine.to_rust(),
+        "#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum State {
+    WaitingForAck,
+    Done,
+}
+
+impl State {
+    pub const INITIAL: State = State::WaitingForAck;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Event {
+    Ack,
+}
+
+pub fn transition(state: State, event: Event) -> Option<State> {
+    match (state, event) {
+        (State::WaitingForAck, Event::Ack) => Some(State::Done),
+        _ => None,
+    }
+}
+"
+    );
+}
+
+fn message(text: &str) -> String {
+    match text.parse::<Machine>() {
+        Err(Error::Syntax(_, message)) => message,
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        message("┌───┐ go ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘"),
+        "no state is marked `(start)`"
+    );
+    assert_eq!(
+        message("┌─────────┐    ┌───┐\n│ A       ├────┤ B │\n│ (start) │    └───┘\n└─────────┘"),
+        "the wire between `A` and `B` has no arrowhead"
+    );
+    assert_eq!(
+        message("┌─────────┐    ┌───┐\n│ A       ├───▶│ B │\n│ (start) │    └───┘\n└─────────┘"),
+        "the arrow from `A` to `B` has no event"
+    );
+    assert_eq!(
+        message(
+            "┌─────────┐ go ┌───┐\n│ A       ├───▶│ B │\n│ (start) │    └───┘\n│         │ go ┌───┐\n│         ├───▶│ C │\n└─────────┘    └───┘"
+        ),
+        "`go` leads from `A` to both `B` and `C`"
+    );
+    assert_eq!(
+        message("┌─────────┐ go ┌──────┐\n│ A       ├───▶│ self │\n│ (start) │    └──────┘\n└─────────┘"),
+        "state `self` is named `Self`, which is reserved in Rust"
+    );
+}
@@ -0,0 +1,289 @@
+use crate::logical::Diagram;
+use crate::mermaid::{render, Edge, Flowchart, Node, Shape, Stroke, Subgraph};
+use crate::tests::layout::{read_back, ReadBack};
+use crate::{Error, Graph, LineColumn};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn subgraphs_and_labels() {
+    let g = Graph::with_text(
+        "┌┄┄┄┄┄┄┄┄┄┄┄┐
+┆ outer     ┆
+┆ ┌───────┐ ┆
+┆ │ inner │ ┆
+┆ └───┬───┘ ┆
+┆     ┆ go  ┆
+└┄┄┄┄┄┼┄┄┄┄┄┘
+      ┆
+      ▼
+┌───────────┐
+│ say \"hi\"  │
+└───────────┘",
+    )
+    .unwrap();
+    assert_eq!(
+        render(&Diagram::from(&g)),
+        r#"flowchart TD
+    subgraph n0 ["outer"]
+        n1["inner"]
+    end
+    n2["say #quot;hi#quot;"]
+    n1 -.->|"go"| n2
+"#
+    );
+}
+
+#[test]
+fn undirected_right_to_left() {
+    let g = Graph::with_text(
+        "┌───┐    ┌───┐    ┌───┐
+│ A │◀━━━┥ B ├────┤ C │
+└───┘    └───┘    └───┘",
+    )
+    .unwrap();
+    assert_eq!(
+        render(&Diagram::from(&g)),
+        r#"flowchart RL
+    n0["A"]
+    n1["B"]
+    n2["C"]
+    n1 ==> n0
+    n1 --- n2
+"#
+    );
+}
+
+#[test]
+fn both_ways_and_undirected() {
+    let g = Graph::with_text(
+        "┌───┐   ┌───┐   ┌───┐
+│ A │◀─▶│ B ├───┤ C │
+└───┘   └───┘   └───┘",
+    )
+    .unwrap();
+    let diagram = Diagram::from(&g);
+    assert!(diagram.connections()[0].bidirectional);
+    assert_eq!(
+        render(&diagram),
+        r#"flowchart LR
+    n0["A"]
+    n1["B"]
+    n2["C"]
+    n0 <--> n1
+    n1 --- n2
+"#
+    );
+}
+
+#[test]
+fn parse() {
+    let flowchart: Flowchart = "%% a comment
+flowchart LR
+    a[\"Start\"] -->|go| b{Choose}
+    subgraph s [Inner]
+        b -. maybe .-> c([\"Stop\"]) & d[(Store)]
+    end
+    c === a; style a fill:#f9f"
+        .parse()
+        .unwrap();
+    assert_eq!(flowchart.direction, "LR");
+    let node = |id: &str, label: &str, shape, subgraph| Node {
+        id: id.to_string(),
+        label: label.to_string(),
+        shape,
+        subgraph,
+    };
+    assert_eq!(
+        flowchart.nodes,
+        vec![
+            node("a", "Start", Shape::Rectangle, None),
+            node("b", "Choose", Shape::Rhombus, Some(0)),
+            node("c", "Stop", Shape::Stadium, Some(0)),
+            node("d", "Store", Shape::Cylinder, Some(0)),
+        ]
+    );
+    let edge = |from, to, label: Option<&str>, directed, stroke| Edge {
+        from,
+        to,
+        label: label.map(str::to_string),
+        directed,
+        backward: false,
+        stroke,
+    };
+    assert_eq!(
+        flowchart.edges,
+        vec![
+            edge(0, 1, Some("go"), true, Stroke::Normal),
+            edge(1, 2, Some("maybe"), true, Stroke::Dotted),
+            edge(1, 3, Some("maybe"), true, Stroke::Dotted),
+            edge(2, 0, None, false, Stroke::Thick),
+        ]
+    );
+    assert_eq!(
+        flowchart.subgraphs,
+        vec![Subgraph {
+            id: "s".to_string(),
+            label: Some("Inner".to_string()),
+            parent: None,
+        }]
+    );
+}
+
+#[test]
+fn syntax_error() {
+    match "flowchart TD\n    a --o b".parse::<Flowchart>() {
+        Err(Error::Syntax(location, _)) => assert_eq!(location, LineColumn::new(2, 6)),
+        result => panic!("expected a syntax error, got {:?}", result),
+    }
+}
+
+#[test]
+fn render_dotted() {
+    let flowchart: Flowchart = "graph TD\n    a -.-> b\n    a --> c".parse().unwrap();
+    assert_eq!(
+        flowchart.to_graph().unwrap().to_string(),
+        "  ┌─────┐
+  │  a  │
+  └─┬─┬─┘
+    ┆ │
+  ┌┄┘ │
+  ┆   └──┐
+  ▼      ▼
+┌───┐  ┌───┐
+│ b │  │ c │
+└───┘  └───┘"
+    );
+}
+
+#[test]
+fn render_directions() {
+    let flowchart: Flowchart = "graph LR\n    a <--> b".parse().unwrap();
+    assert!(flowchart.edges[0].directed && flowchart.edges[0].backward);
+    let render = |direction: &str| {
+        format!("flowchart {}\n    a --> b\n    b <--> c", direction)
+            .parse::<Flowchart>()
+            .unwrap()
+            .to_graph()
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(
+        render("TD"),
+        "┌───┐
+│ a │
+└─┬─┘
+  │
+  │
+  ▼
+┌───┐
+│ b │
+└───┘
+  ▲
+  │
+  ▼
+┌───┐
+│ c │
+└───┘"
+    );
+    assert_eq!(
+        render("BT"),
+        "┌───┐
+│ c │
+└───┘
+  ▲
+  │
+  ▼
+┌───┐
+│ b │
+└───┘
+  ▲
+  │
+  │
+┌─┴─┐
+│ a │
+└───┘"
+    );
+    assert_eq!(
+        render("LR"),
+        "┌───┐   ┌───┐   ┌───┐
+│   │   │   │   │   │
+│ a ├──▶│ b │◀─▶│ c │
+│   │   │   │   │   │
+└───┘   └───┘   └───┘"
+    );
+    assert_eq!(
+        render("RL"),
+        "┌───┐   ┌───┐   ┌───┐
+│   │   │   │   │   │
+│ c │◀─▶│ b │◀──┤ a │
+│   │   │   │   │   │
+└───┘   └───┘   └───┘"
+    );
+}
+
+/// The flowchart laid out, then read back from the drawing.
+fn laid_out(input: &str) -> ReadBack {
+    read_back(&input.parse::<Flowchart>().unwrap().to_graph().unwrap())
+}
+
+#[test]
+fn labels_read_back() {
+    let label = |from: &str, to: &str, label: Option<&str>| {
+        (from.to_string(), to.to_string(), label.map(String::from))
+    };
+    for direction in &["TD", "BT", "LR", "RL"] {
+        let ReadBack {
+            connections, texts, ..
+        } = laid_out(&format!(
+            "flowchart {}\n  A[Start] --> B{{Ok?}}\n  B -->|Yes| C[Go]\n  B -.->|No| D[Stop]",
+            direction
+        ));
+        assert_eq!(
+            connections,
+            vec![
+                label("Ok?", "Go", Some("Yes")),
+                label("Ok?", "Stop", Some("No")),
+                label("Start", "Ok?", None)
+            ],
+            "{}",
+            direction
+        );
+        assert!(texts.is_empty(), "{}", direction);
+    }
+    let ReadBack { connections, .. } =
+        laid_out("flowchart TD\n  A -- text --> B\n  A -. dotted .-> C\n  A == thick ==> D");
+    assert_eq!(
+        connections,
+        vec![
+            label("A", "B", Some("text")),
+            label("A", "C", Some("dotted")),
+            label("A", "D", Some("thick"))
+        ]
+    );
+}
+
+#[test]
+fn subgraph_labels_read_back() {
+    // The wire into the subgraph passes where its label would first go.
+    let input = "flowchart TD
+  a --> b1
+  subgraph two
+    b1 --> b2
+  end";
+    let ReadBack { blocks, texts, .. } = laid_out(input);
+    assert_eq!(blocks, vec!["a", "b1", "b2", "two"]);
+    assert!(texts.is_empty());
+    let graph = input.parse::<Flowchart>().unwrap().to_graph().unwrap();
+    assert_eq!(
+        render(&Diagram::from(&graph)),
+        r#"flowchart TD
+    n0["a"]
+    subgraph n1 ["two"]
+        n2["b1"]
+        n3["b2"]
+    end
+    n0 --> n2
+    n2 --> n3
+"#
+    );
+}
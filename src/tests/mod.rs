@@ -1,4 +1,29 @@
 extern crate base64;
 
+mod classify;
+mod csv;
+mod d2;
+mod diff;
+mod dot;
+mod drawio;
+mod embedded;
+mod excalidraw;
+mod format;
+mod graphml;
+mod html;
+mod layout;
+mod logical;
+mod machine;
+mod mermaid;
 mod parse;
+mod plantuml;
+mod raster;
+mod route;
+#[cfg(feature = "serde")]
+mod schema;
 mod state;
+mod style;
+mod svg;
+mod text;
+mod tikz;
+mod topology;
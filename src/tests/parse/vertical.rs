@@ -117,3 +117,75 @@ parse!(
         )
     ]
 );
+
+parse!(
+    heavy_tee,
+    "┃\n┣━",
+    [
+        (
+            Node {
+                character: '┃',
+                source: LineColumn { line: 1, column: 0 },
+                visual: LineColumn { line: 1, column: 0 },
+                region: (Region::North, Region::Center),
+            },
+            Node {
+                character: '┣',
+                source: LineColumn { line: 2, column: 0 },
+                visual: LineColumn { line: 2, column: 0 },
+                region: (Region::Center, Region::Center),
+            },
+            &Edge(None, Brush::NorthSouth('┃'), None),
+        ),
+        (
+            Node {
+                character: '┣',
+                source: LineColumn { line: 2, column: 0 },
+                visual: LineColumn { line: 2, column: 0 },
+                region: (Region::Center, Region::Center),
+            },
+            Node {
+                character: '┣',
+                source: LineColumn { line: 2, column: 0 },
+                visual: LineColumn { line: 2, column: 0 },
+                region: (Region::South, Region::Center),
+            },
+            &Edge(None, Brush::NorthSouth('┃'), None),
+        ),
+        (
+            Node {
+                character: '┣',
+                source: LineColumn { line: 2, column: 0 },
+                visual: LineColumn { line: 2, column: 0 },
+                region: (Region::Center, Region::Center),
+            },
+            Node {
+                character: '━',
+                source: LineColumn { line: 2, column: 1 },
+                visual: LineColumn { line: 2, column: 1 },
+                region: (Region::Center, Region::East),
+            },
+            &Edge(None, Brush::EastWest('━'), None),
+        )
+    ]
+);
+
+parse!(
+    double,
+    "║",
+    [(
+        Node {
+            character: '║',
+            source: LineColumn { line: 1, column: 0 },
+            visual: LineColumn { line: 1, column: 0 },
+            region: (Region::North, Region::Center),
+        },
+        Node {
+            character: '║',
+            source: LineColumn { line: 1, column: 0 },
+            visual: LineColumn { line: 1, column: 0 },
+            region: (Region::South, Region::Center),
+        },
+        &Edge(None, Brush::NorthSouth('║'), None),
+    )]
+);
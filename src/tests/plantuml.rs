@@ -0,0 +1,60 @@
+use crate::logical::Diagram;
+use crate::plantuml::render;
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn nesting_and_labels() {
+    let g = Graph::with_text(
+        "┌┄┄┄┄┄┄┄┄┄┄┄┐
+┆ outer     ┆
+┆ ┌───────┐ ┆
+┆ │ inner │ ┆
+┆ └───┬───┘ ┆
+┆     ┆ go  ┆
+└┄┄┄┄┄┼┄┄┄┄┄┘
+      ┆
+      ▼
+┌───────────┐    ┌───┐
+│ say \"hi\"  ├────┤ Y │
+└───────────┘    └───┘",
+    )
+    .unwrap();
+    assert_eq!(
+        render(&Diagram::from(&g)),
+        "@startuml
+rectangle \"outer\" as n0 {
+  component \"inner\" as n1
+}
+component \"say &#34;hi&#34;\" as n2
+component \"Y\" as n3
+n1 ..> n2 : go
+n2 -- n3
+@enduml
+"
+    );
+}
+
+#[test]
+fn escaped_labels() {
+    let g = Graph::with_text(
+        "┌────────┐
+│ a\\b: c │
+└───┬────┘
+    │ x: \"y\"
+    ▼
+┌────────┐
+│ B      │
+└────────┘",
+    )
+    .unwrap();
+    assert_eq!(
+        render(&Diagram::from(&g)),
+        "@startuml
+component \"a\\\\b: c\" as n0
+component \"B\" as n1
+n0 --> n1 : x: &#34;y&#34;
+@enduml
+"
+    );
+}
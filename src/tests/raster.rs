@@ -0,0 +1,72 @@
+use crate::raster::{render, Options};
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+/// The rows of an image, with `#` for black pixels and `.` for white ones.
+fn rows(graph: &str, scale: usize) -> Vec<String> {
+    let image = render(&Graph::with_text(graph).unwrap(), &Options { scale });
+    (0..image.height)
+        .map(|y| {
+            (0..image.width)
+                .map(|x| if image.get(x, y) == 0 { '#' } else { '.' })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn strokes() {
+    assert_eq!(
+        rows("─━┄", 1),
+        vec![
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "##############...#",
+            ".....########.....",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+            "..................",
+        ]
+    );
+}
+
+#[test]
+fn text() {
+    assert_eq!(
+        rows("Hi", 1),
+        vec![
+            "............",
+            "............",
+            "#...#...#...",
+            "#...#.......",
+            "#...#..##...",
+            "#####...#...",
+            "#...#...#...",
+            "#...#...#...",
+            "#...#..###..",
+            "............",
+            "............",
+            "............",
+        ]
+    );
+}
+
+#[test]
+fn formats() {
+    let image = render(&Graph::with_text("│").unwrap(), &Options::default());
+    assert_eq!((image.width, image.height), (12, 24));
+
+    let ppm = image.to_ppm();
+    assert!(ppm.starts_with(b"P6\n12 24\n255\n"));
+    assert_eq!(ppm.len(), 13 + 12 * 24 * 3);
+
+    let png = image.to_png();
+    assert_eq!(&png[..16], b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR");
+    assert_eq!(&png[16..29], b"\0\0\0\x0c\0\0\0\x18\x08\0\0\0\0");
+    assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xaeB`\x82");
+}
@@ -0,0 +1,167 @@
+use crate::{Error, Graph, LineColumn};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn adjacent_boxes() {
+    let mut g = Graph::with_text("┌───┐     ┌───┐\n│ A │     │ B │\n└───┘     └───┘").unwrap();
+    g.route(LineColumn::new(2, 2), LineColumn::new(2, 12), true)
+        .unwrap();
+    assert_eq!(
+        g.to_string(),
+        "┌───┐     ┌───┐\n│ A ├────▶│ B │\n└───┘     └───┘"
+    );
+}
+
+#[test]
+fn around_obstacles() {
+    let mut g = Graph::with_text(
+        "┌───┐  ┌───┐  ┌───┐
+│ A │  │ X │  │ B │
+└───┘  └───┘  └───┘
+
+   │
+───┼────
+   │",
+    )
+    .unwrap();
+    g.route(LineColumn::new(2, 2), LineColumn::new(2, 16), true)
+        .unwrap();
+    assert_eq!(
+        g.to_string(),
+        "┌───┐  ┌───┐  ┌───┐
+│ A ├─┐│ X │  │ B │
+└───┘ │└───┘  └───┘
+      │        ▲
+   │  └────────┘
+───┼────
+   │"
+    );
+}
+
+#[test]
+fn crossing() {
+    let mut g = Graph::with_text(
+        "┌───┐
+│ A │
+└───┘
+  │
+──┼─────
+  │
+┌───┐
+│ B │
+└───┘",
+    )
+    .unwrap();
+    g.route(LineColumn::new(2, 2), LineColumn::new(8, 2), false)
+        .unwrap();
+    assert_eq!(
+        g.to_string(),
+        "┌───┐
+│ A │
+└┬──┘
+ ││
+─┼┼─────
+ ││
+┌┴──┐
+│ B │
+└───┘"
+    );
+}
+
+#[test]
+fn no_route() {
+    let mut g = Graph::with_text("┌───┐\n│ A │\n└───┘  label").unwrap();
+    match g.route(LineColumn::new(2, 2), LineColumn::new(3, 8), false) {
+        Err(Error::NoRoute) => {}
+        result => panic!("unexpected {:?}", result),
+    }
+}
+
+#[test]
+fn through_container() {
+    let mut g = Graph::with_text(
+        "┌┄┄┄┄┄┄┄┄┄┐
+┆ ┌───┐   ┆
+┆ │ A │   ┆
+┆ └───┘   ┆
+└┄┄┄┄┄┄┄┄┄┘
+
+  ┌───┐
+  │ B │
+  └───┘",
+    )
+    .unwrap();
+    g.route(LineColumn::new(3, 4), LineColumn::new(8, 4), true)
+        .unwrap();
+    assert_eq!(
+        g.to_string(),
+        "┌┄┄┄┄┄┄┄┄┄┐
+┆ ┌───┐   ┆
+┆ │ A │   ┆
+┆ └┬──┘   ┆
+└┄┄┼┄┄┄┄┄┄┘
+   ▼
+  ┌───┐
+  │ B │
+  └───┘"
+    );
+}
+
+#[test]
+fn into_container_sideways() {
+    let mut g = Graph::with_text(
+        "┌┄┄┄┄┄┄┄┐
+┆ ┌───┐ ┆  ┌───┐
+┆ │ A │ ┆  │ B │
+┆ └───┘ ┆  └───┘
+└┄┄┄┄┄┄┄┘",
+    )
+    .unwrap();
+    g.route(LineColumn::new(3, 12), LineColumn::new(3, 4), true)
+        .unwrap();
+    assert_eq!(
+        g.to_string(),
+        "┌┄┄┄┄┄┄┄┐
+┆ ┌───┐ ┆  ┌───┐
+┆ │ A │◀┼──┤ B │
+┆ └───┘ ┆  └───┘
+└┄┄┄┄┄┄┄┘"
+    );
+}
+
+#[test]
+fn around_boxes_in_container() {
+    // Only the container's side may be crossed, not the boxes inside it.
+    let mut g = Graph::with_text(
+        "┌┄┄┄┄┄┄┄┄┄┐
+┆ ┌───┐   ┆
+┆ │ A │   ┆
+┆ └───┘   ┆
+┆ ┌───┐   ┆
+┆ │ X │   ┆
+┆ └───┘   ┆
+└┄┄┄┄┄┄┄┄┄┘
+
+  ┌───┐
+  │ B │
+  └───┘",
+    )
+    .unwrap();
+    g.route(LineColumn::new(11, 4), LineColumn::new(3, 4), true)
+        .unwrap();
+    assert_eq!(
+        g.to_string(),
+        "┌┄┄┄┄┄┄┄┄┄┐
+┆ ┌───┐   ┆
+┆ │ A │◀┐ ┆
+┆ └───┘ │ ┆
+┆ ┌───┐ │ ┆
+┆ │ X │ │ ┆
+┆ └───┘ │ ┆
+└┄┄┄┄┄┄┄┼┄┘
+        │
+  ┌───┐ │
+  │ B ├─┘
+  └───┘"
+    );
+}
@@ -0,0 +1,50 @@
+use crate::schema::{SCHEMA, VERSION};
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn golden() {
+    let g = Graph::with_text("─").unwrap();
+    assert_eq!(
+        serde_json::to_string(&g).unwrap(),
+        concat!(
+            r#"{"version":1,"nodes":["#,
+            r#"{"character":"─","source":{"line":1,"column":0},"#,
+            r#""visual":{"line":1,"column":0},"region":["Center","West"]},"#,
+            r#"{"character":"─","source":{"line":1,"column":0},"#,
+            r#""visual":{"line":1,"column":0},"region":["Center","East"]}],"#,
+            r#""edges":[{"from":0,"to":1,"edge":[null,{"EastWest":"─"},null]}]}"#
+        )
+    );
+}
+
+#[test]
+fn round_trip() {
+    let g = Graph::with_text("┌─┐\n│A├──▶\n└─┘").unwrap();
+    let json = serde_json::to_string(&g).unwrap();
+    let h: Graph = serde_json::from_str(&json).unwrap();
+    assert_eq!(h.to_string(), g.to_string());
+    assert_eq!(serde_json::to_string(&h).unwrap(), json);
+}
+
+#[test]
+fn refused() {
+    let version = r#"{"version":2,"nodes":[],"edges":[]}"#;
+    assert_eq!(
+        serde_json::from_str::<Graph>(version)
+            .unwrap_err()
+            .to_string(),
+        "unsupported version 2, expected 1"
+    );
+    let missing = r#"{"version":1,"nodes":[],"edges":[{"from":0,"to":1,"edge":[null,{"EastWest":"─"},null]}]}"#;
+    assert!(serde_json::from_str::<Graph>(missing)
+        .unwrap_err()
+        .to_string()
+        .starts_with("edge refers to missing node 0"));
+}
+
+#[test]
+fn schema() {
+    let schema: serde_json::Value = serde_json::from_str(SCHEMA).unwrap();
+    assert_eq!(schema["properties"]["version"]["const"], VERSION);
+}
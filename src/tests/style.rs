@@ -0,0 +1,77 @@
+use crate::style::{restyle, Style};
+use pretty_assertions::assert_eq;
+
+const LIGHT: &str = "╭───────╮
+│ A     ├──┬──▶
+╰───┬───╯  │
+    ▼      ▼";
+
+#[test]
+fn weights() {
+    assert_eq!(
+        restyle(LIGHT, Style::Heavy),
+        "┏━━━━━━━┓
+┃ A     ┣━━┳━━▶
+┗━━━┳━━━┛  ┃
+    ▼      ▼"
+    );
+    assert_eq!(
+        restyle(LIGHT, Style::Double),
+        "╔═══════╗
+║ A     ╠══╦══▶
+╚═══╦═══╝  ║
+    ▼      ▼"
+    );
+    assert_eq!(
+        restyle(&restyle(LIGHT, Style::Heavy), Style::Light),
+        "┌───────┐
+│ A     ├──┬──▶
+└───┬───┘  │
+    ▼      ▼"
+    );
+}
+
+#[test]
+fn doubles() {
+    assert_eq!(
+        restyle(&restyle(LIGHT, Style::Double), Style::Light),
+        "┌───────┐
+│ A     ├──┬──▶
+└───┬───┘  │
+    ▼      ▼"
+    );
+}
+
+#[test]
+fn dashes() {
+    assert_eq!(
+        restyle("┌┄┄┐\n┆  ┆\n└┄┄┘", Style::Heavy),
+        "┏┅┅┓\n┇  ┇\n┗┅┅┛"
+    );
+    assert_eq!(
+        restyle("┌┄┄┐\n┆  ┆\n└┄┄┘", Style::Double),
+        "╔══╗\n║  ║\n╚══╝"
+    );
+}
+
+#[test]
+fn ascii() {
+    let ascii = restyle(LIGHT, Style::Ascii);
+    assert_eq!(
+        ascii,
+        "+-------+
+| A     +--+-->
++---+---+  |
+    v      v"
+    );
+    assert_eq!(
+        restyle(&ascii, Style::Light),
+        "┌───────┐
+│ A     ├──┬──▶
+└───┬───┘  │
+    ▼      ▼"
+    );
+    // Hyphens and bars between words are text.
+    assert_eq!(restyle("well-known a|b", Style::Light), "well-known a|b");
+    assert_eq!(restyle("x - y", Style::Light), "x ─ y");
+}
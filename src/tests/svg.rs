@@ -0,0 +1,85 @@
+use crate::svg::{render, Options};
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn light_line_and_label() {
+    let g = Graph::with_text("──── a & b").unwrap();
+    assert_eq!(
+        render(&g, &Options::default()),
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="80" height="16" viewBox="0 0 80 16">
+<g fill="none" stroke="black" stroke-width="1" stroke-linecap="square">
+<line x1="0" y1="8" x2="32" y2="8"/>
+</g>
+<g font-family="monospace" font-size="12" dominant-baseline="central">
+<text x="40" y="8" textLength="40">a &amp; b</text>
+</g>
+</svg>
+"#
+    );
+}
+
+#[test]
+fn weights_and_dashes() {
+    let g = Graph::with_text("━━\n══\n┄┄").unwrap();
+    assert_eq!(
+        render(&g, &Options::default()),
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="48" viewBox="0 0 16 48">
+<g fill="none" stroke="black" stroke-width="1" stroke-linecap="square">
+<line x1="0" y1="8" x2="16" y2="8" stroke-width="2"/>
+<line x1="0" y1="25" x2="16" y2="25"/>
+<line x1="0" y1="23" x2="16" y2="23"/>
+<line x1="0" y1="40" x2="16" y2="40" stroke-dasharray="2 2"/>
+</g>
+</svg>
+"#
+    );
+}
+
+#[test]
+fn arrows_and_aspect_ratio() {
+    let g = Graph::with_text("│\n▼ <").unwrap();
+    let options = Options {
+        cell_width: 10.0,
+        cell_height: 10.0,
+        stroke_width: 1.0,
+    };
+    assert_eq!(
+        render(&g, &options),
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="20" viewBox="0 0 30 20">
+<defs>
+<marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto">
+<path d="M 0 0 L 10 5 L 0 10 z"/>
+</marker>
+</defs>
+<g fill="none" stroke="black" stroke-width="1" stroke-linecap="square">
+<line x1="5" y1="0" x2="5" y2="10"/>
+<line x1="5" y1="10" x2="5" y2="20" marker-end="url(#arrow)"/>
+</g>
+<g font-family="monospace" font-size="7.5" dominant-baseline="central">
+<text x="20" y="15" textLength="10">&lt;</text>
+</g>
+</svg>
+"#
+    );
+}
+
+#[test]
+fn strokes_within_words() {
+    let g = Graph::with_text("┌─────────┐\n│ X-Y a/b │\n└─────────┘").unwrap();
+    assert_eq!(
+        render(&g, &Options::default()),
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="88" height="48" viewBox="0 0 88 48">
+<g fill="none" stroke="black" stroke-width="1" stroke-linecap="square">
+<line x1="4" y1="8" x2="84" y2="8"/>
+<line x1="4" y1="8" x2="4" y2="40"/>
+<line x1="84" y1="8" x2="84" y2="40"/>
+<line x1="4" y1="40" x2="84" y2="40"/>
+</g>
+<g font-family="monospace" font-size="12" dominant-baseline="central">
+<text x="16" y="24" textLength="56">X-Y a/b</text>
+</g>
+</svg>
+"#
+    );
+}
@@ -0,0 +1,15 @@
+use crate::{Graph, LineColumn, Node, Region};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn with_text() {
+    let input = "┌────┐\n│ Ab │\n└────┘";
+    let g = Graph::with_text(input).unwrap();
+    assert!(g.contains_node(Node {
+        character: 'A',
+        source: LineColumn { line: 2, column: 2 },
+        visual: LineColumn { line: 2, column: 2 },
+        region: (Region::Center, Region::Center),
+    }));
+    assert_eq!(g.to_string(), input);
+}
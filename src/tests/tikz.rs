@@ -0,0 +1,67 @@
+use crate::tikz::{render, Options};
+use crate::Graph;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn boxes_and_arrow() {
+    let g = Graph::with_text(
+        "┌───┐
+│ A ├─┐
+└───┘ │ 50%
+      ▼
+    ┌───┐
+    │ B │
+    └───┘",
+    )
+    .unwrap();
+    assert_eq!(
+        render(&g, &Options::default()),
+        r"\begin{tikzpicture}[x=0.2cm,y=-0.4cm,line width=0.4pt]
+\draw (4.5,1.5) -- (4.5,2.5) -- (0.5,2.5) -- (0.5,0.5) -- (4.5,0.5) -- cycle;
+\draw[-{Stealth}] (4.5,1.5) -- (6.5,1.5) -- (6.5,4);
+\draw (4.5,4.5) -- (8.5,4.5) -- (8.5,6.5) -- (4.5,6.5) -- cycle;
+\node[anchor=west,inner sep=0,font=\ttfamily] at (2,1.5) {A};
+\node[anchor=west,inner sep=0,font=\ttfamily] at (8,2.5) {50\%};
+\node[anchor=west,inner sep=0,font=\ttfamily] at (6,5.5) {B};
+\end{tikzpicture}
+"
+    );
+}
+
+#[test]
+fn brushes_and_units() {
+    let g = Graph::with_text("══ ━━ ┄┄ ◀─▶").unwrap();
+    let options = Options {
+        cell_width: 0.25,
+        cell_height: 0.5,
+        line_width: 0.5,
+    };
+    assert_eq!(
+        render(&g, &options),
+        r"\begin{tikzpicture}[x=0.25cm,y=-0.5cm,line width=0.5pt]
+\draw[double] (0,0.5) -- (2,0.5);
+\draw[line width=1pt] (3,0.5) -- (5,0.5);
+\draw[dashed] (6,0.5) -- (8,0.5);
+\draw[{Stealth}-{Stealth}] (9,0.5) -- (12,0.5);
+\end{tikzpicture}
+"
+    );
+}
+
+#[test]
+fn strokes_within_words() {
+    let g = Graph::with_text(
+        "┌───────────────────────┐
+│ EXIT a/b x=1 lib-core │
+└───────────────────────┘",
+    )
+    .unwrap();
+    assert_eq!(
+        render(&g, &Options::default()),
+        r"\begin{tikzpicture}[x=0.2cm,y=-0.4cm,line width=0.4pt]
+\draw (0.5,0.5) -- (24.5,0.5) -- (24.5,2.5) -- (0.5,2.5) -- cycle;
+\node[anchor=west,inner sep=0,font=\ttfamily] at (2,1.5) {EXIT a/b x=1 lib-core};
+\end{tikzpicture}
+"
+    );
+}
@@ -0,0 +1,83 @@
+use crate::mermaid::Flowchart;
+use crate::topology::{equivalent, Options};
+use crate::Graph;
+
+fn graph(text: &str) -> Graph {
+    Graph::with_text(text).unwrap()
+}
+
+#[test]
+fn regenerated() {
+    let flowchart: Flowchart = "flowchart LR\n  a[A] --> b[B]\n  a --- c[C]"
+        .parse()
+        .unwrap();
+    let drawn = graph(
+        "┌───┐    ┌───┐
+│ C ├────┤ A │
+└───┘    └─┬─┘
+           │
+           ▼
+         ┌───┐
+         │ B │
+         └───┘",
+    );
+    assert!(equivalent(
+        &flowchart.to_graph().unwrap(),
+        &drawn,
+        &Options::default()
+    ));
+}
+
+#[test]
+fn differences() {
+    let a = graph(
+        "┌───┐    ┌───┐
+│ A ├───▶│ B │
+└───┘    └───┘",
+    );
+    let reversed = graph(
+        "┌───┐    ┌───┐
+│ A │◀───┤ B │
+└───┘    └───┘",
+    );
+    let renamed = graph(
+        "┌───┐    ┌───┐
+│ A ├───▶│ C │
+└───┘    └───┘",
+    );
+    let labelled = graph(
+        "┌───┐ go ┌───┐
+│ A ├───▶│ B │
+└───┘    └───┘",
+    );
+    let dashed = graph(
+        "┌───┐    ┌───┐
+│ A ├┄┄┄▶│ B │
+└───┘    └───┘",
+    );
+    let options = Options::default();
+    assert!(equivalent(&a, &a, &options));
+    assert!(!equivalent(&a, &reversed, &options));
+    assert!(!equivalent(&a, &renamed, &options));
+    assert!(!equivalent(&a, &labelled, &options));
+    assert!(!equivalent(&a, &dashed, &options));
+    assert!(equivalent(&a, &dashed, &Options { ignore_brush: true }));
+}
+
+#[test]
+fn nesting() {
+    let inside = graph(
+        "┌─────────┐
+│ A       │
+│  ┌───┐  │
+│  │ B │  │
+│  └───┘  │
+└─────────┘",
+    );
+    let beside = graph(
+        "┌───┐ ┌───┐
+│ A │ │ B │
+└───┘ └───┘",
+    );
+    assert!(!equivalent(&inside, &beside, &Options::default()));
+}
@@ -0,0 +1,241 @@
+//! Rendering a drawing as a TikZ picture, for LaTeX documents.
+//!
+//! Strokes of the same brush that meet end to end are joined into one `\draw` path, styled after
+//! the brush: heavy brushes are drawn twice as wide, double brushes with TikZ's `double` style and
+//! dashed brushes with `dashed`. Arrowheads become arrow tips on the path that leads into them,
+//! and text becomes nodes in a typewriter font.
+//!
+//! ```
+//! use dottools::{tikz, Graph};
+//!
+//! let graph = Graph::with_text("──▶ done").unwrap();
+//! let tikz = tikz::render(&graph, &tikz::Options::default());
+//! assert!(tikz.contains(r"\draw[-{Stealth}] (0,0.5) -- (3,0.5);"));
+//! assert!(tikz.contains(r"{done};"));
+//! ```
+
+use crate::geometry::{Geometry, Stroke};
+use crate::{Brush, Graph, Weight};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// How a drawing is laid out in the picture.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// The width of a character cell, in centimetres.
+    pub cell_width: f64,
+    /// The height of a character cell, in centimetres.
+    pub cell_height: f64,
+    /// The width of a light stroke, in points; heavy strokes are twice as wide.
+    pub line_width: f64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            cell_width: 0.2,
+            cell_height: 0.4,
+            line_width: 0.4,
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\textbackslash{}"),
+            '~' => escaped.push_str(r"\textasciitilde{}"),
+            '^' => escaped.push_str(r"\textasciicircum{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A point on the half-cell grid that every stroke starts and ends on.
+fn key((x, y): (f64, f64)) -> (i64, i64) {
+    ((x * 2.0).round() as i64, (y * 2.0).round() as i64)
+}
+
+/// The strokes of one style that start or end at a point, by their index.
+type Meeting = HashMap<((i64, i64), (Weight, bool)), Vec<usize>>;
+
+/// A run of strokes of one style, joined end to end.
+struct Path {
+    points: Vec<(f64, f64)>,
+    brush: Brush,
+    start_tip: bool,
+    end_tip: bool,
+}
+
+/// Joins strokes that meet end to end, where no other stroke of the same style meets them, and
+/// drops the points in the middle of straight runs.
+fn paths(strokes: &[Stroke]) -> Vec<Path> {
+    let mut meeting: Meeting = HashMap::new();
+    for (i, stroke) in strokes.iter().enumerate() {
+        meeting
+            .entry((key(stroke.from), stroke.brush.style()))
+            .or_default()
+            .push(i);
+        meeting
+            .entry((key(stroke.to), stroke.brush.style()))
+            .or_default()
+            .push(i);
+    }
+
+    let mut used = vec![false; strokes.len()];
+    let mut paths = Vec::new();
+    for i in 0..strokes.len() {
+        if used[i] {
+            continue;
+        }
+        used[i] = true;
+        let brush = strokes[i].brush;
+        let mut points = vec![strokes[i].from, strokes[i].to];
+        for &forwards in &[true, false] {
+            loop {
+                let end = if forwards {
+                    points[points.len() - 1]
+                } else {
+                    points[0]
+                };
+                let next = match meeting.get(&(key(end), brush.style())) {
+                    Some(others) if others.len() == 2 => others.iter().find(|&&j| !used[j]),
+                    _ => None,
+                };
+                let j = match next {
+                    Some(&j) => j,
+                    None => break,
+                };
+                used[j] = true;
+                let other = if key(strokes[j].from) == key(end) {
+                    strokes[j].to
+                } else {
+                    strokes[j].from
+                };
+                if forwards {
+                    points.push(other);
+                } else {
+                    points.insert(0, other);
+                }
+            }
+        }
+        paths.push(Path {
+            points: straighten(points),
+            brush,
+            start_tip: false,
+            end_tip: false,
+        });
+    }
+    paths
+}
+
+/// Drops the points that lie on a straight line between their neighbours.
+fn straighten(points: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    let mut straight: Vec<(f64, f64)> = Vec::with_capacity(points.len());
+    for point in points {
+        if straight.len() >= 2 {
+            let (a, b) = (straight[straight.len() - 2], straight[straight.len() - 1]);
+            let cross = (b.0 - a.0) * (point.1 - b.1) - (b.1 - a.1) * (point.0 - b.0);
+            if cross.abs() < 1e-9 {
+                straight.pop();
+            }
+        }
+        straight.push(point);
+    }
+    straight
+}
+
+/// Renders the drawing as a `tikzpicture` environment.
+///
+/// The picture uses the `Stealth` arrow tip, so the document needs
+/// `\usetikzlibrary{arrows.meta}`.
+pub fn render(graph: &Graph, options: &Options) -> String {
+    let geometry = Geometry::from_graph(graph);
+    let mut paths = paths(&geometry.strokes);
+
+    // Arrowheads extend the path that leads into them, or are drawn on their own.
+    for arrow in &geometry.arrows {
+        let (from, to) = arrow.points();
+        let path = paths.iter_mut().find(|path| {
+            let (first, last) = (path.points[0], path.points[path.points.len() - 1]);
+            (key(last) == key(from) && !path.end_tip)
+                || (key(first) == key(from) && !path.start_tip)
+        });
+        match path {
+            Some(path) if key(path.points[path.points.len() - 1]) == key(from) => {
+                path.points.push(to);
+                path.points = straighten(path.points.clone());
+                path.end_tip = true;
+            }
+            Some(path) => {
+                path.points.insert(0, to);
+                path.points = straighten(path.points.clone());
+                path.start_tip = true;
+            }
+            None => paths.push(Path {
+                points: vec![from, to],
+                brush: Brush::EastWest('─'),
+                start_tip: false,
+                end_tip: true,
+            }),
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r"\begin{{tikzpicture}}[x={}cm,y=-{}cm,line width={}pt]",
+        options.cell_width, options.cell_height, options.line_width
+    )
+    .unwrap();
+    for path in &paths {
+        let mut style = Vec::new();
+        match (path.start_tip, path.end_tip) {
+            (true, true) => style.push(r"{Stealth}-{Stealth}".to_string()),
+            (true, false) => style.push(r"{Stealth}-".to_string()),
+            (false, true) => style.push(r"-{Stealth}".to_string()),
+            (false, false) => {}
+        }
+        match path.brush.weight() {
+            Weight::Light => {}
+            Weight::Heavy => style.push(format!("line width={}pt", options.line_width * 2.0)),
+            Weight::Double => style.push("double".to_string()),
+        }
+        if path.brush.is_dashed() {
+            style.push("dashed".to_string());
+        }
+        out.push_str(r"\draw");
+        if !style.is_empty() {
+            write!(out, "[{}]", style.join(",")).unwrap();
+        }
+        let mut points: Vec<String> = path
+            .points
+            .iter()
+            .map(|(x, y)| format!("({},{})", x, y))
+            .collect();
+        if points.len() > 2 && points[0] == points[points.len() - 1] {
+            points.pop();
+            points.push("cycle".to_string());
+        }
+        writeln!(out, " {};", points.join(" -- ")).unwrap();
+    }
+    for label in &geometry.labels {
+        writeln!(
+            out,
+            r"\node[anchor=west,inner sep=0,font=\ttfamily] at ({},{}) {{{}}};",
+            label.column,
+            label.line as f64 - 0.5,
+            escape(&label.text)
+        )
+        .unwrap();
+    }
+    out.push_str("\\end{tikzpicture}\n");
+    out
+}
@@ -0,0 +1,119 @@
+//! Whether two drawings show the same thing, however they are laid out.
+//!
+//! Two drawings are equivalent when their logical graphs are isomorphic: there is a one to one
+//! mapping between their boxes that keeps the boxes' labels, which box is inside which, and the
+//! connections between them along with the connections' direction and labels. Brushes must match
+//! too, unless they are ignored. Free text must be the same, wherever it is.
+//!
+//! ```
+//! use dottools::{topology, Graph};
+//!
+//! let a = Graph::with_text("┌───┐    ┌───┐\n│ A ├───▶│ B │\n└───┘    └───┘").unwrap();
+//! let b = Graph::with_text("┌───┐\n│ B │\n└───┘\n  ▲\n  │\n┏━┷━┓\n┃ A ┃\n┗━━━┛").unwrap();
+//! let options = topology::Options { ignore_brush: true };
+//! assert!(topology::equivalent(&a, &b, &options));
+//! assert!(!topology::equivalent(&a, &b, &topology::Options::default()));
+//! ```
+
+use crate::logical::Diagram;
+use crate::{Graph, Weight};
+use petgraph::algo::is_isomorphic_matching;
+
+/// What to take into account when comparing drawings.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Options {
+    /// Ignore the weight and dashing of boxes and wires.
+    pub ignore_brush: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Relation {
+    /// From a box to a box drawn inside it.
+    Contains,
+    /// From one box to another along a wire. Undirected wires are related both ways.
+    Wire {
+        directed: bool,
+        label: Option<String>,
+        style: (Weight, bool),
+    },
+}
+
+type Logical = petgraph::Graph<(String, (Weight, bool)), Relation>;
+
+fn logical(diagram: &Diagram) -> Logical {
+    let mut graph = Logical::new();
+    let nodes: Vec<_> = diagram
+        .blocks
+        .iter()
+        .map(|block| graph.add_node((block.label.clone(), block.brush.style())))
+        .collect();
+    for (i, block) in diagram.blocks.iter().enumerate() {
+        if let Some(parent) = block.parent {
+            graph.add_edge(nodes[parent], nodes[i], Relation::Contains);
+        }
+    }
+    for connection in diagram.connections() {
+        let wire = &diagram.wires[connection.wire];
+        let relation = Relation::Wire {
+            directed: connection.directed,
+            label: wire.label.clone(),
+            style: wire.brush.style(),
+        };
+        let (from, to) = (nodes[connection.from], nodes[connection.to]);
+        if !connection.directed {
+            graph.add_edge(to, from, relation.clone());
+        }
+        graph.add_edge(from, to, relation);
+    }
+    graph
+}
+
+/// The free text of a diagram, in order.
+fn texts(diagram: &Diagram) -> Vec<&str> {
+    let mut texts: Vec<&str> = diagram
+        .texts
+        .iter()
+        .map(|text| text.text.as_str())
+        .collect();
+    texts.sort();
+    texts
+}
+
+/// Whether the two drawings show the same boxes, connected in the same way.
+pub fn equivalent(a: &Graph, b: &Graph, options: &Options) -> bool {
+    equivalent_diagrams(&Diagram::from(a), &Diagram::from(b), options)
+}
+
+/// Whether the two diagrams have the same boxes, connected in the same way.
+pub fn equivalent_diagrams(a: &Diagram, b: &Diagram, options: &Options) -> bool {
+    if texts(a) != texts(b) {
+        return false;
+    }
+    let ignore_brush = options.ignore_brush;
+    is_isomorphic_matching(
+        &logical(a),
+        &logical(b),
+        |a, b| a.0 == b.0 && (ignore_brush || a.1 == b.1),
+        |a, b| match (a, b) {
+            (Relation::Contains, Relation::Contains) => true,
+            (
+                Relation::Wire {
+                    directed,
+                    label,
+                    style,
+                },
+                Relation::Wire {
+                    directed: other_directed,
+                    label: other_label,
+                    style: other_style,
+                },
+            ) => {
+                directed == other_directed
+                    && label == other_label
+                    && (ignore_brush || style == other_style)
+            }
+            _ => false,
+        },
+    )
+}
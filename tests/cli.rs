@@ -0,0 +1,139 @@
+#![cfg(feature = "cli")]
+
+use pretty_assertions::assert_eq;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const DRAWING: &str = "┌───┐    ┌───┐
+│ A ├───▶│ B │
+└───┘    └───┘";
+
+fn dottools(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_dottools"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: Output) -> String {
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn parse_and_render() {
+    let json = stdout(dottools(&["parse"], DRAWING));
+    assert!(json.starts_with("{\n  \"version\": 1,"));
+    assert_eq!(
+        stdout(dottools(&["render", "-"], &json)),
+        format!("{}\n", DRAWING)
+    );
+    assert!(stdout(dottools(&["parse", "--format=debug"], DRAWING)).starts_with("Graph("));
+}
+
+#[test]
+fn convert() {
+    assert_eq!(
+        stdout(dottools(&["convert", "--to", "mermaid"], DRAWING)),
+        "flowchart LR\n    n0[\"A\"]\n    n1[\"B\"]\n    n0 --> n1\n"
+    );
+    let ascii = "+---+    +---+\n| A |--->| B |\n+---+    +---+";
+    assert_eq!(
+        stdout(dottools(&["convert", "--to", "mermaid"], ascii)),
+        "flowchart LR\n    n0[\"A\"]\n    n1[\"B\"]\n    n0 --> n1\n"
+    );
+    let png = dottools(&["convert", "--to", "png"], DRAWING);
+    assert!(png.stdout.starts_with(b"\x89PNG"));
+}
+
+#[test]
+fn restyle() {
+    assert_eq!(
+        stdout(dottools(&["restyle", "--to", "ascii"], DRAWING)),
+        "+---+    +---+\n| A +--->| B |\n+---+    +---+"
+    );
+}
+
+#[test]
+fn failures() {
+    let output = dottools(&["convert", "--to", "jpeg"], DRAWING);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("dottools: unknown format `jpeg`"));
+    let output = dottools(&["render", "missing.json"], "");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn fmt() {
+    let untidy = "# Notes\n\n```\n┌───── \n│ A  │\n└────┘\n```\n";
+    let tidy = "# Notes\n\n```\n┌────┐\n│ A  │\n└────┘\n```\n";
+    let path = std::env::temp_dir().join(format!("dottools-fmt-{}.md", std::process::id()));
+    let file = path.to_str().unwrap();
+    std::fs::write(&path, untidy).unwrap();
+
+    let output = dottools(&["fmt", "--check", file], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{}\n", file)
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), untidy);
+
+    stdout(dottools(&["fmt", file], ""));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), tidy);
+    stdout(dottools(&["fmt", "--check", file], ""));
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        stdout(dottools(&["fmt", "--style", "double"], "┌──┐  \n└──┘\n")),
+        "╔══╗\n╚══╝\n"
+    );
+}
+
+#[test]
+fn check() {
+    let source = "fn main() {}\n\n// ┌──┐\n// │  │\n// └──┘ ─│\n";
+    let path = std::env::temp_dir().join(format!("dottools-check-{}.rs", std::process::id()));
+    let file = path.to_str().unwrap();
+    std::fs::write(&path, source).unwrap();
+
+    let output = dottools(&["check", file], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{}:5:9: `─` does not join `│` to its east\n", file)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("dottools: 1 problem found"));
+
+    let output = dottools(&["check", "--format", "github", file], "");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "::error file={},line=5,col=9::`─` does not join `│` to its east\n",
+            file
+        )
+    );
+    let output = dottools(&["check", "--format=sarif", file], "");
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let region = &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(
+        (&region["startLine"], &region["startColumn"]),
+        (&5.into(), &9.into())
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        stdout(dottools(&["check", "--format", "json"], DRAWING)),
+        "[]\n"
+    );
+}